@@ -23,6 +23,8 @@ use std::{
 pub const WORD_SIZE: usize = 4;
 pub const PC_WORD_SIZE: usize = 4;
 pub const PC_STEP_SIZE: usize = 4;
+/// The pc step of a 16-bit compressed instruction.
+pub const COMPRESSED_PC_STEP_SIZE: usize = 2;
 
 // Type aliases to clarify the code without wrapper types.
 pub type Word = u32;
@@ -92,6 +94,11 @@ impl ByteAddr {
         self.0 % WORD_SIZE as u32 == 0
     }
 
+    /// Whether an instruction may start at this address, given compressed instructions.
+    pub const fn is_insn_aligned(&self) -> bool {
+        self.0 % COMPRESSED_PC_STEP_SIZE as u32 == 0
+    }
+
     pub const fn is_null(&self) -> bool {
         self.0 == 0
     }
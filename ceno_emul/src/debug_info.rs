@@ -0,0 +1,119 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+};
+
+use anyhow::{Result, anyhow};
+use elf::{ElfBytes, endian::LittleEndian};
+use gimli::{Dwarf, EndianSlice, SectionId};
+
+/// An allocated section of the ELF file, such as `.text`, `.rodata`, `.data` or `.bss`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub addr: u32,
+    /// The size of the section in memory, in bytes
+    pub size: u32,
+}
+
+impl Section {
+    pub fn contains(&self, addr: u32) -> bool {
+        addr.wrapping_sub(self.addr) < self.size
+    }
+}
+
+/// A line of the source code of the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation<'a> {
+    pub file: &'a str,
+    pub line: u32,
+}
+
+impl Display for SourceLocation<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// The map from addresses to source lines, decoded from the DWARF line tables
+#[derive(Clone, Debug, Default)]
+pub struct SourceLines {
+    files: Vec<String>,
+    /// The file index and line of the code from each address up to the next one, or `None`
+    /// after the end of a sequence of code.
+    rows: BTreeMap<u32, Option<(usize, u32)>>,
+}
+
+impl SourceLines {
+    /// The source line of the code at `addr`, if known
+    pub fn find(&self, addr: u32) -> Option<SourceLocation> {
+        let (_, row) = self.rows.range(..=addr).next_back()?;
+        let (file, line) = (*row)?;
+        Some(SourceLocation {
+            file: &self.files[file],
+            line,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Decode the line tables of an ELF file, if it has DWARF debug info
+    pub(crate) fn load(elf: &ElfBytes<LittleEndian>) -> Result<Self> {
+        let dwarf = Dwarf::load(|id: SectionId| -> Result<_> {
+            let data = match elf
+                .section_header_by_name(id.name())
+                .map_err(|err| anyhow!("Section table parse error: {err}"))?
+            {
+                Some(header) => match elf
+                    .section_data(&header)
+                    .map_err(|err| anyhow!("Section {} parse error: {err}", id.name()))?
+                {
+                    // Compressed debug sections are not supported, and read as empty.
+                    (data, None) => data,
+                    (_, Some(_)) => &[],
+                },
+                None => &[],
+            };
+            Ok(EndianSlice::new(data, gimli::LittleEndian))
+        })?;
+
+        let mut lines = SourceLines::default();
+        let mut file_indices = HashMap::new();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            let unit = dwarf.unit(header)?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let mut rows = program.rows();
+            while let Some((header, row)) = rows.next_row()? {
+                let addr = row.address() as u32;
+                if row.end_sequence() {
+                    // Another sequence may start at the same address.
+                    lines.rows.entry(addr).or_insert(None);
+                    continue;
+                }
+                let Some(file) = row.file(header) else {
+                    continue;
+                };
+                let name = dwarf.attr_string(&unit, file.path_name())?;
+                let mut path = name.to_string_lossy().into_owned();
+                if !path.starts_with('/') {
+                    if let Some(dir) = file.directory(header) {
+                        let dir = dwarf.attr_string(&unit, dir)?;
+                        path = format!("{}/{path}", dir.to_string_lossy());
+                    }
+                }
+                let file = *file_indices.entry(path).or_insert_with_key(|path| {
+                    lines.files.push(path.clone());
+                    lines.files.len() - 1
+                });
+                let line = row.line().map_or(0, |line| line.get() as u32);
+                lines.rows.insert(addr, Some((file, line)));
+            }
+        }
+        Ok(lines)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::rv32im::{InsnKind, Instruction};
+use InsnKind::*;
+
+/// Expand a 16-bit compressed instruction (RVC) into the equivalent instruction.
+///
+/// Only RV32C is supported: the floating-point loads and stores, C.EBREAK, and the reserved
+/// encodings are not implemented.
+#[must_use]
+pub fn expand_compressed(half: u16) -> Instruction {
+    let raw = half as u32;
+    let insn = match decode(raw) {
+        Some((kind, rd, rs1, rs2, imm)) => Instruction {
+            kind,
+            rd,
+            rs1,
+            rs2,
+            imm,
+            raw,
+            compressed: true,
+        },
+        None => Instruction::unimp(raw),
+    };
+    Instruction {
+        compressed: true,
+        ..insn
+    }
+}
+
+/// Decode the kind, rd, rs1, rs2, and immediate of a compressed instruction.
+fn decode(x: u32) -> Option<(InsnKind, usize, usize, usize, i32)> {
+    // The full register fields of the CR, CI, and CSS formats.
+    let rd = bits(x, 11, 7) as usize;
+    let rs2 = bits(x, 6, 2) as usize;
+    // The registers x8 to x15 of the CIW, CL, CS, CA, and CB formats.
+    let rd_c = creg(x, 2);
+    let rs1_c = creg(x, 7);
+
+    let imm6 = sext(bit(x, 12) << 5 | bits(x, 6, 2), 6);
+    let imm_lw = bits(x, 12, 10) << 3 | bit(x, 6) << 2 | bit(x, 5) << 6;
+    let imm_j = sext(
+        bit(x, 12) << 11
+            | bit(x, 11) << 4
+            | bits(x, 10, 9) << 8
+            | bit(x, 8) << 10
+            | bit(x, 7) << 6
+            | bit(x, 6) << 7
+            | bits(x, 5, 3) << 1
+            | bit(x, 2) << 5,
+        12,
+    );
+    let imm_b = sext(
+        bit(x, 12) << 8
+            | bits(x, 11, 10) << 3
+            | bits(x, 6, 5) << 6
+            | bits(x, 4, 3) << 1
+            | bit(x, 2) << 5,
+        9,
+    );
+
+    match (bits(x, 1, 0), bits(x, 15, 13)) {
+        // C.ADDI4SPN
+        (0b00, 0b000) => {
+            let imm = bits(x, 12, 11) << 4 | bits(x, 10, 7) << 6 | bit(x, 6) << 2 | bit(x, 5) << 3;
+            (imm != 0).then_some((ADDI, rd_c, 2, 0, imm as i32))
+        }
+        (0b00, 0b010) => Some((LW, rd_c, rs1_c, 0, imm_lw as i32)),
+        (0b00, 0b110) => Some((SW, 0, rs1_c, rd_c, imm_lw as i32)),
+        // C.ADDI, and C.NOP.
+        (0b01, 0b000) => Some((ADDI, rd, rd, 0, imm6)),
+        (0b01, 0b001) => Some((JAL, 1, 0, 0, imm_j)),
+        // C.LI
+        (0b01, 0b010) => Some((ADDI, rd, 0, 0, imm6)),
+        // C.ADDI16SP
+        (0b01, 0b011) if rd == 2 => {
+            let imm = sext(
+                bit(x, 12) << 9
+                    | bit(x, 6) << 4
+                    | bit(x, 5) << 6
+                    | bits(x, 4, 3) << 7
+                    | bit(x, 2) << 5,
+                10,
+            );
+            (imm != 0).then_some((ADDI, 2, 2, 0, imm))
+        }
+        // C.LUI, converted to ADDI like LUI.
+        (0b01, 0b011) => (imm6 != 0).then_some((ADDI, rd, 0, 0, imm6 << 12)),
+        (0b01, 0b100) => match bits(x, 11, 10) {
+            // The shift amounts of RV32C are 5 bits.
+            0b00 => (bit(x, 12) == 0).then_some((SRLI, rs1_c, rs1_c, 0, rs2 as i32)),
+            0b01 => (bit(x, 12) == 0).then_some((SRAI, rs1_c, rs1_c, 0, rs2 as i32)),
+            0b10 => Some((ANDI, rs1_c, rs1_c, 0, imm6)),
+            _ => {
+                let kind = match (bit(x, 12), bits(x, 6, 5)) {
+                    (0, 0b00) => SUB,
+                    (0, 0b01) => XOR,
+                    (0, 0b10) => OR,
+                    (0, 0b11) => AND,
+                    _ => return None,
+                };
+                Some((kind, rs1_c, rs1_c, rd_c, 0))
+            }
+        },
+        // C.J
+        (0b01, 0b101) => Some((JAL, 0, 0, 0, imm_j)),
+        (0b01, 0b110) => Some((BEQ, 0, rs1_c, 0, imm_b)),
+        (0b01, 0b111) => Some((BNE, 0, rs1_c, 0, imm_b)),
+        (0b10, 0b000) => (bit(x, 12) == 0).then_some((SLLI, rd, rd, 0, rs2 as i32)),
+        // C.LWSP
+        (0b10, 0b010) => {
+            let imm = bit(x, 12) << 5 | bits(x, 6, 4) << 2 | bits(x, 3, 2) << 6;
+            (rd != 0).then_some((LW, rd, 2, 0, imm as i32))
+        }
+        (0b10, 0b100) => match (bit(x, 12), rd, rs2) {
+            (_, 0, 0) => None,
+            // C.JR
+            (0, _, 0) => Some((JALR, 0, rd, 0, 0)),
+            // C.MV
+            (0, _, _) => Some((ADD, rd, 0, rs2, 0)),
+            // C.JALR
+            (_, _, 0) => Some((JALR, 1, rd, 0, 0)),
+            // C.ADD
+            _ => Some((ADD, rd, rd, rs2, 0)),
+        },
+        // C.SWSP
+        (0b10, 0b110) => {
+            let imm = bits(x, 12, 9) << 2 | bits(x, 8, 7) << 6;
+            Some((SW, 0, 2, rs2, imm as i32))
+        }
+        _ => None,
+    }
+}
+
+fn bit(x: u32, i: u32) -> u32 {
+    (x >> i) & 1
+}
+
+fn bits(x: u32, hi: u32, lo: u32) -> u32 {
+    (x >> lo) & ((1 << (hi - lo + 1)) - 1)
+}
+
+/// A register x8 to x15, from its 3-bit field at `lo`.
+fn creg(x: u32, lo: u32) -> usize {
+    8 + bits(x, lo + 2, lo) as usize
+}
+
+/// Sign-extend the lowest `width` bits of `value`.
+fn sext(value: u32, width: u32) -> i32 {
+    ((value << (32 - width)) as i32) >> (32 - width)
+}
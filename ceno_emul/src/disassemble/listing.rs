@@ -0,0 +1,99 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, Write},
+};
+
+use crate::{
+    Program, SourceLocation,
+    rv32im::{InsnCategory, InsnKind, Instruction},
+};
+use InsnKind::*;
+
+/// The ABI names of the registers.
+const REG_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+const OPCODE_LUI: u32 = 0b0110111;
+const OPCODE_AUIPC: u32 = 0b0010111;
+
+fn reg(idx: usize) -> &'static str {
+    REG_NAMES.get(idx).copied().unwrap_or("x?")
+}
+
+/// Format an instruction in assembler syntax, from its fields.
+///
+/// Branch and jump targets are relative to the instruction, as `.+offset`. LUI and AUIPC are
+/// shown as the ADDI that they are transpiled to, followed by the original instruction.
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mnemonic = self.kind.to_string().to_lowercase();
+        let (rd, rs1, rs2, imm) = (reg(self.rd), reg(self.rs1), reg(self.rs2), self.imm);
+        match (self.kind, InsnCategory::from(self.kind)) {
+            (INVALID, _) => write!(f, "unimp"),
+            (ECALL, _) => write!(f, "ecall"),
+            (ADDI, _) if !self.compressed && self.raw & 0x7f == OPCODE_LUI => {
+                let upper = self.raw >> 12;
+                write!(
+                    f,
+                    "addi {rd}, {rs1}, {:#x}  # lui {rd}, {upper:#x}",
+                    imm as u32
+                )
+            }
+            (ADDI, _) if !self.compressed && self.raw & 0x7f == OPCODE_AUIPC => {
+                let upper = self.raw >> 12;
+                write!(
+                    f,
+                    "addi {rd}, {rs1}, {:#x}  # auipc {rd}, {upper:#x}",
+                    imm as u32
+                )
+            }
+            (JAL, _) => write!(f, "jal {rd}, .{imm:+}"),
+            (JALR, _) | (_, InsnCategory::Load) => write!(f, "{mnemonic} {rd}, {imm}({rs1})"),
+            (_, InsnCategory::Store) => write!(f, "{mnemonic} {rs2}, {imm}({rs1})"),
+            (_, InsnCategory::Branch) => write!(f, "{mnemonic} {rs1}, {rs2}, .{imm:+}"),
+            (ADDI | XORI | ORI | ANDI | SLLI | SRLI | SRAI | SLTI | SLTIU, _) => {
+                write!(f, "{mnemonic} {rd}, {rs1}, {imm}")
+            }
+            _ => write!(f, "{mnemonic} {rd}, {rs1}, {rs2}"),
+        }
+    }
+}
+
+/// Write the listing of a program in the style of `objdump -d -l`.
+///
+/// Each instruction is listed with its address and raw encoding, under the label of the function
+/// that starts there and the source line that it comes from, if known. The targets of branches
+/// and jumps are resolved to addresses and functions.
+pub fn write_listing(program: &Program, mut out: impl Write) -> io::Result<()> {
+    let mut last_source: Option<SourceLocation> = None;
+    for (&pc, insn) in program.pcs().iter().zip(&program.instructions) {
+        if let Some(symbol) = program.symbols.get(&pc) {
+            writeln!(out, "\n{pc:08x} <{}>:", symbol.name)?;
+            last_source = None;
+        }
+        let source = program.source_at(pc);
+        if source.is_some() && source != last_source {
+            writeln!(out, "; {}", source.unwrap())?;
+        }
+        last_source = source;
+
+        let raw = if insn.compressed {
+            format!("{:04x}    ", insn.raw)
+        } else {
+            format!("{:08x}", insn.raw)
+        };
+        write!(out, "{pc:8x}:\t{raw}\t{insn}")?;
+        if insn.kind == JAL || matches!(InsnCategory::from(insn.kind), InsnCategory::Branch) {
+            let target = pc.wrapping_add(insn.imm as u32);
+            write!(out, "  # {target:#x}")?;
+            if let Some((start, symbol)) = program.symbol_at(target) {
+                write!(out, " <{}+{:#x}>", symbol.name, target - start)?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -1,8 +1,15 @@
+mod compressed;
+pub use compressed::expand_compressed;
+
+mod listing;
+pub use listing::write_listing;
+
 use crate::rv32im::{InsnKind, Instruction};
 use itertools::izip;
 use rrs_lib::{
     InstructionProcessor,
     instruction_formats::{BType, IType, ITypeCSR, ITypeShamt, JType, RType, SType, UType},
+    instruction_string_outputter::InstructionStringOutputter,
     process_instruction,
 };
 
@@ -23,6 +30,7 @@ impl Instruction {
             rs2: dec_insn.rs2,
             imm: 0,
             raw,
+            compressed: false,
         }
     }
 
@@ -36,6 +44,7 @@ impl Instruction {
             imm: dec_insn.imm,
             rs2: 0,
             raw,
+            compressed: false,
         }
     }
 
@@ -49,6 +58,7 @@ impl Instruction {
             imm: dec_insn.shamt as i32,
             rs2: 0,
             raw,
+            compressed: false,
         }
     }
 
@@ -62,6 +72,7 @@ impl Instruction {
             rs2: dec_insn.rs2,
             imm: dec_insn.imm,
             raw,
+            compressed: false,
         }
     }
 
@@ -75,6 +86,7 @@ impl Instruction {
             rs2: dec_insn.rs2,
             imm: dec_insn.imm,
             raw,
+            compressed: false,
         }
     }
 
@@ -88,6 +100,7 @@ impl Instruction {
             rs2: 0,
             imm: 0,
             raw,
+            compressed: false,
         }
     }
 }
@@ -235,6 +248,7 @@ impl InstructionProcessor for InstructionTranspiler {
             rs2: 0,
             imm: dec_insn.imm,
             raw: self.word,
+            compressed: false,
         }
     }
 
@@ -246,6 +260,7 @@ impl InstructionProcessor for InstructionTranspiler {
             rs2: 0,
             imm: dec_insn.imm,
             raw: self.word,
+            compressed: false,
         }
     }
 
@@ -267,6 +282,7 @@ impl InstructionProcessor for InstructionTranspiler {
             rs2: 0,
             imm: dec_insn.imm,
             raw: self.word,
+            compressed: false,
         }
     }
 
@@ -302,6 +318,7 @@ impl InstructionProcessor for InstructionTranspiler {
             rs2: 0,
             imm: dec_insn.imm.wrapping_add(pc as i32),
             raw: self.word,
+            compressed: false,
         }
     }
 
@@ -313,6 +330,7 @@ impl InstructionProcessor for InstructionTranspiler {
             rs2: 0,
             imm: 0,
             raw: self.word,
+            compressed: false,
         }
     }
 
@@ -400,6 +418,46 @@ pub fn transpile(base: u32, instructions_u32: &[u32]) -> Vec<Instruction> {
         .collect()
 }
 
+/// Transpile the [`Instruction`]s from the 16-bit parcels of code with compressed instructions.
+///
+/// A 32-bit instruction spans two parcels, and starts on any 2-byte boundary.
+#[must_use]
+pub fn transpile_compressed(base: u32, parcels: &[u16]) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(parcels.len());
+    let mut i = 0;
+    while i < parcels.len() {
+        let pc = base + (i * 2) as u32;
+        let low = parcels[i];
+        match parcels.get(i + 1) {
+            Some(&high) if low & 0b11 == 0b11 => {
+                let word = low as u32 | (high as u32) << 16;
+                instructions.push(
+                    process_instruction(&mut InstructionTranspiler { pc, word }, word)
+                        .unwrap_or(Instruction::unimp(word)),
+                );
+                i += 2;
+            }
+            _ => {
+                instructions.push(expand_compressed(low));
+                i += 1;
+            }
+        }
+    }
+    instructions
+}
+
+/// Format an instruction at `pc` as assembly.
+///
+/// Instructions that were not decoded from a word, or expanded from a compressed instruction,
+/// are formatted from their fields, by [`Instruction`]'s `Display`.
+#[must_use]
+pub fn disassemble(pc: u32, insn: &Instruction) -> String {
+    (!insn.compressed)
+        .then(|| process_instruction(&mut InstructionStringOutputter { insn_pc: pc }, insn.raw))
+        .flatten()
+        .unwrap_or_else(|| insn.to_string())
+}
+
 fn enumerate(start: u32, step: u32) -> impl Iterator<Item = u32> {
     std::iter::successors(Some(start), move |&i| Some(i + step))
 }
@@ -17,12 +17,22 @@
 extern crate alloc;
 
 use alloc::collections::BTreeMap;
+use core::ops::Range;
 
-use crate::{CENO_PLATFORM, addr::WORD_SIZE, disassemble::transpile, rv32im::Instruction};
+use crate::{
+    CENO_PLATFORM,
+    addr::{COMPRESSED_PC_STEP_SIZE, WORD_SIZE},
+    debug_info::{Section, SourceLines, SourceLocation},
+    disassemble::{transpile, transpile_compressed},
+    rv32im::Instruction,
+};
 use anyhow::{Context, Result, anyhow, bail};
 use elf::{
     ElfBytes,
-    abi::{PF_R, PF_W, PF_X},
+    abi::{
+        EF_RISCV_RVC, PF_R, PF_W, PF_X, SHF_ALLOC, STB_GLOBAL, STB_LOCAL, STT_FUNC, STT_NOTYPE,
+        STT_OBJECT,
+    },
     endian::LittleEndian,
     file::Class,
 };
@@ -38,16 +48,35 @@ pub struct Program {
     pub instructions: Vec<Instruction>,
     /// The initial memory image
     pub image: BTreeMap<u32, u32>,
+    /// The functions of the program by start address, from the ELF symbol table
+    pub symbols: BTreeMap<u32, Symbol>,
+    /// The address of each named function or object, from the ELF symbol table
+    pub addresses: BTreeMap<String, u32>,
+    /// The allocated sections of the ELF file, by address
+    pub sections: Vec<Section>,
+    /// The source lines of the code, from the DWARF debug info
+    pub source_lines: SourceLines,
+    /// The address of each instruction, after the instructions before it
+    pcs: Vec<u32>,
+}
+
+/// A function symbol of the program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    /// The demangled name of the function
+    pub name: String,
+    /// The size of the function in bytes, or 0 if unknown
+    pub size: u32,
 }
 
 impl From<&[Instruction]> for Program {
     fn from(insn_codes: &[Instruction]) -> Program {
-        Self {
-            entry: CENO_PLATFORM.pc_base(),
-            base_address: CENO_PLATFORM.pc_base(),
-            instructions: insn_codes.to_vec(),
-            image: Default::default(),
-        }
+        Program::new(
+            CENO_PLATFORM.pc_base(),
+            CENO_PLATFORM.pc_base(),
+            insn_codes.to_vec(),
+            Default::default(),
+        )
     }
 }
 
@@ -59,11 +88,86 @@ impl Program {
         instructions: Vec<Instruction>,
         image: BTreeMap<u32, u32>,
     ) -> Program {
+        let pcs = instructions
+            .iter()
+            .scan(base_address, |pc, insn| {
+                let insn_pc = *pc;
+                *pc += insn.size();
+                Some(insn_pc)
+            })
+            .collect();
         Self {
             entry,
             base_address,
             instructions,
             image,
+            symbols: Default::default(),
+            addresses: Default::default(),
+            sections: Default::default(),
+            source_lines: Default::default(),
+            pcs,
+        }
+    }
+
+    /// The address of each instruction
+    pub fn pcs(&self) -> &[u32] {
+        &self.pcs
+    }
+
+    /// The address after the last instruction
+    pub fn code_end(&self) -> u32 {
+        match (self.pcs.last(), self.instructions.last()) {
+            (Some(pc), Some(insn)) => pc + insn.size(),
+            _ => self.base_address,
+        }
+    }
+
+    /// The index of the instruction at `pc`, if an instruction starts there
+    pub fn index_at(&self, pc: u32) -> Option<usize> {
+        // Without compressed instructions, the index follows from the address.
+        let idx = (pc.wrapping_sub(self.base_address) / WORD_SIZE as u32) as usize;
+        if self.pcs.get(idx) == Some(&pc) {
+            return Some(idx);
+        }
+        self.pcs.binary_search(&pc).ok()
+    }
+
+    /// The instruction at `pc`, if any
+    pub fn instruction_at(&self, pc: u32) -> Option<Instruction> {
+        self.instructions.get(self.index_at(pc)?).copied()
+    }
+
+    /// Find the function that contains `pc`, and its start address
+    pub fn symbol_at(&self, pc: u32) -> Option<(u32, &Symbol)> {
+        let (&addr, symbol) = self.symbols.range(..=pc).next_back()?;
+        (symbol.size == 0 || pc - addr < symbol.size).then_some((addr, symbol))
+    }
+
+    /// The address of the function or object `name`, if any
+    pub fn addr_of(&self, name: &str) -> Option<u32> {
+        self.addresses.get(name).copied()
+    }
+
+    /// The section that contains `addr`, if any
+    pub fn section_at(&self, addr: u32) -> Option<&Section> {
+        self.sections.iter().find(|section| section.contains(addr))
+    }
+
+    /// The source line of the code at `pc`, if the program has debug info
+    pub fn source_at(&self, pc: u32) -> Option<SourceLocation> {
+        self.source_lines.find(pc)
+    }
+
+    /// Describe `pc` as `function+offset`, followed by the source line if known
+    pub fn describe(&self, pc: u32) -> Option<String> {
+        let function = self
+            .symbol_at(pc)
+            .map(|(addr, symbol)| format!("{}+{:#x}", symbol.name, pc - addr));
+        match (function, self.source_at(pc)) {
+            (Some(function), Some(source)) => Some(format!("{function} at {source}")),
+            (Some(function), None) => Some(function),
+            (None, Some(source)) => Some(format!("{source}")),
+            (None, None) => None,
         }
     }
 
@@ -89,7 +193,14 @@ impl Program {
             .e_entry
             .try_into()
             .map_err(|err| anyhow!("e_entry was larger than 32 bits. {err}"))?;
-        if entry >= max_mem || entry % WORD_SIZE as u32 != 0 {
+        // With compressed instructions, the instructions are aligned on 2 bytes.
+        let is_compressed = elf.ehdr.e_flags & EF_RISCV_RVC != 0;
+        let insn_align = if is_compressed {
+            COMPRESSED_PC_STEP_SIZE
+        } else {
+            WORD_SIZE
+        };
+        if entry >= max_mem || entry % insn_align as u32 != 0 {
             bail!("Invalid entrypoint");
         }
         let segments = elf.segments().ok_or(anyhow!("Missing segment table"))?;
@@ -174,13 +285,110 @@ impl Program {
         assert!(entry >= base_address);
         assert!((entry - base_address) as usize <= instructions.len() * WORD_SIZE);
 
-        let instructions = transpile(base_address, &instructions);
+        let instructions = if is_compressed {
+            let parcels = instructions
+                .iter()
+                .flat_map(|&word| [word as u16, (word >> 16) as u16])
+                .collect::<Vec<_>>();
+            transpile_compressed(base_address, &parcels)
+        } else {
+            transpile(base_address, &instructions)
+        };
+        let mut program = Program::new(entry, base_address, instructions, image);
+        program.symbols = load_symbols(&elf, base_address..program.code_end())?;
+        program.addresses = load_addresses(&elf)?;
+        program.sections = load_sections(&elf)?;
+        // Source lines only annotate reports, so the program loads without them.
+        program.source_lines = SourceLines::load(&elf).unwrap_or_else(|err| {
+            tracing::warn!("ignoring the DWARF line info of the ELF: {err}");
+            SourceLines::default()
+        });
+        Ok(program)
+    }
+}
 
-        Ok(Program {
-            entry,
-            base_address,
-            image,
-            instructions,
-        })
+/// Collect the function symbols in `code` of an ELF file, if it has a symbol table
+///
+/// Global labels without a type, such as `_start` defined in assembly, count as functions too.
+fn load_symbols(elf: &ElfBytes<LittleEndian>, code: Range<u32>) -> Result<BTreeMap<u32, Symbol>> {
+    let mut symbols = BTreeMap::new();
+    let Some((symtab, strtab)) = elf
+        .symbol_table()
+        .map_err(|err| anyhow!("Symbol table parse error: {err}"))?
+    else {
+        return Ok(symbols);
+    };
+    for sym in symtab.iter() {
+        let is_func = sym.st_symtype() == STT_FUNC;
+        let is_label = sym.st_symtype() == STT_NOTYPE && sym.st_bind() == STB_GLOBAL;
+        let addr = sym.st_value as u32;
+        if !(is_func || is_label) || !code.contains(&addr) {
+            continue;
+        }
+        // Prefer functions over labels at the same address.
+        if is_label && symbols.contains_key(&addr) {
+            continue;
+        }
+        let name = strtab
+            .get(sym.st_name as usize)
+            .map_err(|err| anyhow!("Symbol name parse error: {err}"))?;
+        symbols.insert(addr, Symbol {
+            name: format!("{:#}", rustc_demangle::demangle(name)),
+            size: sym.st_size as u32,
+        });
     }
+    Ok(symbols)
+}
+
+/// Collect the addresses of the named functions and objects of an ELF file
+///
+/// Global symbols take precedence over local symbols of the same name.
+fn load_addresses(elf: &ElfBytes<LittleEndian>) -> Result<BTreeMap<String, u32>> {
+    let mut addresses = BTreeMap::new();
+    let Some((symtab, strtab)) = elf
+        .symbol_table()
+        .map_err(|err| anyhow!("Symbol table parse error: {err}"))?
+    else {
+        return Ok(addresses);
+    };
+    for sym in symtab.iter() {
+        if !matches!(sym.st_symtype(), STT_FUNC | STT_OBJECT | STT_NOTYPE) || sym.st_name == 0 {
+            continue;
+        }
+        let name = strtab
+            .get(sym.st_name as usize)
+            .map_err(|err| anyhow!("Symbol name parse error: {err}"))?;
+        let name = format!("{:#}", rustc_demangle::demangle(name));
+        if sym.st_bind() == STB_LOCAL && addresses.contains_key(&name) {
+            continue;
+        }
+        addresses.insert(name, sym.st_value as u32);
+    }
+    Ok(addresses)
+}
+
+/// Collect the sections of an ELF file that occupy memory
+fn load_sections(elf: &ElfBytes<LittleEndian>) -> Result<Vec<Section>> {
+    let (Some(headers), Some(strtab)) = elf
+        .section_headers_with_strtab()
+        .map_err(|err| anyhow!("Section table parse error: {err}"))?
+    else {
+        return Ok(vec![]);
+    };
+    let mut sections = headers
+        .iter()
+        .filter(|header| header.sh_flags & SHF_ALLOC as u64 != 0 && header.sh_size != 0)
+        .map(|header| {
+            Ok(Section {
+                name: strtab
+                    .get(header.sh_name as usize)
+                    .map_err(|err| anyhow!("Section name parse error: {err}"))?
+                    .to_string(),
+                addr: header.sh_addr as u32,
+                size: header.sh_size as u32,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    sections.sort_by_key(|section| section.addr);
+    Ok(sections)
 }
@@ -0,0 +1,117 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    ByteAddr, Cycle, Platform, Program, Word, disassemble::disassemble, platform::Segment,
+    rv32im::TrapCause,
+};
+
+/// An error that stops the emulation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmuError {
+    /// An instruction trapped.
+    Trap {
+        cause: TrapCause,
+        pc: ByteAddr,
+        /// The instruction word, if there is an instruction at `pc`.
+        raw: Option<Word>,
+        cycle: Cycle,
+        /// The address and segment of a memory access that trapped.
+        access: Option<(ByteAddr, Segment)>,
+    },
+    /// An instruction jumped to itself without halting.
+    StuckInLoop { pc: ByteAddr, cycle: Cycle },
+}
+
+impl EmuError {
+    /// The error of the instruction at `pc` of `program`, which trapped with `cause`.
+    pub(crate) fn trap(
+        cause: TrapCause,
+        pc: ByteAddr,
+        cycle: Cycle,
+        program: &Program,
+        platform: &Platform,
+    ) -> Self {
+        EmuError::Trap {
+            cause,
+            pc,
+            raw: program.instruction_at(pc.0).map(|insn| insn.raw),
+            cycle,
+            access: cause
+                .access_addr()
+                .map(|addr| (addr, platform.segment(addr.0))),
+        }
+    }
+
+    /// The address of the instruction that failed.
+    pub fn pc(&self) -> ByteAddr {
+        match self {
+            EmuError::Trap { pc, .. } | EmuError::StuckInLoop { pc, .. } => *pc,
+        }
+    }
+
+    /// The cycle of the instruction that failed.
+    pub fn cycle(&self) -> Cycle {
+        match self {
+            EmuError::Trap { cycle, .. } | EmuError::StuckInLoop { cycle, .. } => *cycle,
+        }
+    }
+
+    /// Describe the error, followed by the disassembly of the program around the failing
+    /// instruction.
+    pub fn report(&self, program: &Program) -> String {
+        const CONTEXT: usize = 4; // instructions before and after.
+
+        let pc = self.pc().0;
+        let mut report = format!("{self}\n");
+        if let Some(location) = program.describe(pc) {
+            report += &format!("in {location}\n");
+        }
+        // The closest instruction, if the pc is not at one.
+        let idx = program
+            .index_at(pc)
+            .unwrap_or_else(|| program.pcs().partition_point(|&insn_pc| insn_pc < pc));
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + CONTEXT + 1).min(program.instructions.len());
+        for (&insn_pc, insn) in program.pcs()[start..end]
+            .iter()
+            .zip(&program.instructions[start..end])
+        {
+            let marker = if insn_pc == pc { "=>" } else { "  " };
+            report += &format!(
+                "{marker} {:?}: {}\n",
+                ByteAddr(insn_pc),
+                disassemble(insn_pc, insn)
+            );
+        }
+        report
+    }
+}
+
+impl Display for EmuError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            EmuError::Trap {
+                cause,
+                pc,
+                raw,
+                cycle,
+                access,
+            } => {
+                write!(f, "Trap {cause:?} at pc={pc:?}")?;
+                if let Some(raw) = raw {
+                    write!(f, " (instruction 0x{raw:08x})")?;
+                }
+                write!(f, ", cycle {cycle}")?;
+                if let Some((addr, segment)) = access {
+                    write!(f, ", accessing {addr:?} in {segment:?}")?;
+                }
+                Ok(())
+            }
+            EmuError::StuckInLoop { pc, cycle } => {
+                write!(f, "Stuck in loop at pc={pc:?}, cycle {cycle}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmuError {}
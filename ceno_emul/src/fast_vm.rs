@@ -0,0 +1,241 @@
+use std::{collections::HashMap, ops::Deref, sync::Arc};
+
+use anyhow::Result;
+
+use crate::{
+    EmuError, PC_STEP_SIZE, Program, VMSnapshot, VMState,
+    addr::{ByteAddr, Cycle, RegIdx, Word, WordAddr},
+    platform::Platform,
+    rv32im::{EmuContext, Instruction, TrapCause},
+    syscalls::handle_syscall,
+    tracer::Tracer,
+};
+
+/// A machine that executes a program without recording a trace.
+///
+/// It follows the semantics of [`VMState`] through the same `rv32im::step`, for runs that only
+/// need the final state, the messages of the guest, or the number of cycles.
+pub struct FastVM {
+    program: Arc<Program>,
+    platform: Platform,
+    pc: Word,
+    /// Map a word-address (addr/4) to a word.
+    memory: HashMap<WordAddr, Word>,
+    registers: [Word; VMState::REG_COUNT],
+    /// The cycle of the next instruction, counted as by the [`Tracer`].
+    cycle: Cycle,
+    // Termination.
+    exit_code: Option<Word>,
+}
+
+impl FastVM {
+    pub fn new(platform: Platform, program: Arc<Program>) -> Self {
+        let mut vm = Self {
+            pc: program.entry,
+            platform,
+            program: program.clone(),
+            memory: HashMap::new(),
+            registers: [0; VMState::REG_COUNT],
+            cycle: Tracer::SUBCYCLES_PER_INSN,
+            exit_code: None,
+        };
+
+        for (&addr, &value) in &program.image {
+            vm.init_memory(ByteAddr(addr).waddr(), value);
+        }
+
+        vm
+    }
+
+    pub fn new_from_elf(platform: Platform, elf: &[u8]) -> Result<Self> {
+        let program = Arc::new(Program::load_elf(elf, u32::MAX)?);
+        let platform = Platform {
+            prog_data: program.image.keys().copied().collect(),
+            ..platform
+        };
+        Ok(Self::new(platform, program))
+    }
+
+    pub fn halted(&self) -> bool {
+        self.exit_code.is_some()
+    }
+
+    /// The exit code passed to the halt ecall, once the program halted.
+    pub fn exit_code(&self) -> Option<Word> {
+        self.exit_code
+    }
+
+    /// The cycle of the next instruction, the same as [`Tracer::cycle`] of a traced run.
+    pub fn cycle(&self) -> Cycle {
+        self.cycle
+    }
+
+    /// The number of instructions executed so far.
+    pub fn steps(&self) -> usize {
+        ((self.cycle - Tracer::SUBCYCLES_PER_INSN) / Tracer::SUBCYCLES_PER_INSN) as usize
+    }
+
+    pub fn platform(&self) -> &Platform {
+        &self.platform
+    }
+
+    pub fn program(&self) -> &Program {
+        self.program.deref()
+    }
+
+    /// Set a word in memory without side effects.
+    pub fn init_memory(&mut self, addr: WordAddr, value: Word) {
+        self.memory.insert(addr, value);
+    }
+
+    pub fn init_register_unsafe(&mut self, idx: RegIdx, value: Word) {
+        self.registers[idx] = value;
+    }
+
+    /// Copy the current machine state. The latest accesses are not tracked, and left empty.
+    pub fn snapshot(&self) -> VMSnapshot {
+        VMSnapshot {
+            pc: self.pc,
+            cycle: self.cycle,
+            halted: self.halted(),
+            registers: self.registers,
+            memory: self.memory.clone(),
+            latest_accesses: HashMap::new(),
+        }
+    }
+
+    /// Continue the execution from a state copied by a snapshot. The accesses are not tracked.
+    pub fn restore(&mut self, snapshot: VMSnapshot) {
+        self.pc = snapshot.pc;
+        self.cycle = snapshot.cycle;
+        self.exit_code = snapshot
+            .halted
+            .then(|| snapshot.registers[Platform::reg_arg0()]);
+        self.registers = snapshot.registers;
+        self.memory = snapshot.memory;
+    }
+
+    /// Execute until the program halts or `max_steps` instructions were executed, and return the
+    /// number of instructions of this run.
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, EmuError> {
+        let start = self.steps();
+        while !self.halted() && self.steps() - start < max_steps {
+            self.step()?;
+        }
+        Ok(self.steps() - start)
+    }
+
+    /// Execute one instruction.
+    pub fn step(&mut self) -> Result<(), EmuError> {
+        let pc = self.pc;
+        let cycle = self.cycle;
+        crate::rv32im::step(self)?;
+        self.cycle += Tracer::SUBCYCLES_PER_INSN;
+        if self.pc == pc && !self.halted() {
+            Err(EmuError::StuckInLoop {
+                pc: ByteAddr(pc),
+                cycle,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn halt(&mut self, exit_code: Word) {
+        self.set_pc(0.into());
+        self.exit_code = Some(exit_code);
+    }
+}
+
+impl EmuContext for FastVM {
+    // Expect an ecall to terminate the program: function HALT with argument exit_code.
+    fn ecall(&mut self) -> Result<bool, EmuError> {
+        let function = self.peek_register(Platform::reg_ecall());
+        if function == Platform::ecall_halt() {
+            let exit_code = self.peek_register(Platform::reg_arg0());
+            tracing::debug!("halt with exit_code={}", exit_code);
+            self.halt(exit_code);
+            return Ok(true);
+        }
+        match handle_syscall(self, function) {
+            Ok(effects) => {
+                for (addr, value) in effects.iter_mem_values() {
+                    self.memory.insert(addr, value);
+                }
+                for (idx, value) in effects.iter_reg_values() {
+                    self.registers[idx] = value;
+                }
+                let next_pc = effects.next_pc.unwrap_or(self.pc + PC_STEP_SIZE as u32);
+                self.set_pc(next_pc.into());
+                Ok(true)
+            }
+            Err(err) if self.platform.unsafe_ecall_nop => {
+                tracing::warn!("ecall ignored with unsafe_ecall_nop: {:?}", err);
+                self.set_pc(ByteAddr(self.pc) + PC_STEP_SIZE);
+                Ok(true)
+            }
+            Err(err) => {
+                tracing::error!("ecall error: {:?}", err);
+                self.trap(TrapCause::EcallError)
+            }
+        }
+    }
+
+    fn trap(&self, cause: TrapCause) -> Result<bool, EmuError> {
+        Err(EmuError::trap(
+            cause,
+            ByteAddr(self.pc),
+            self.cycle,
+            &self.program,
+            &self.platform,
+        )) // Crash.
+    }
+
+    fn get_pc(&self) -> ByteAddr {
+        ByteAddr(self.pc)
+    }
+
+    fn set_pc(&mut self, after: ByteAddr) {
+        self.pc = after.0;
+    }
+
+    fn load_register(&mut self, idx: RegIdx) -> Result<Word, EmuError> {
+        Ok(self.peek_register(idx))
+    }
+
+    fn store_register(&mut self, idx: RegIdx, after: Word) -> Result<(), EmuError> {
+        if idx != 0 {
+            self.registers[idx] = after;
+        }
+        Ok(())
+    }
+
+    fn load_memory(&mut self, addr: WordAddr) -> Result<Word, EmuError> {
+        Ok(self.peek_memory(addr))
+    }
+
+    fn store_memory(&mut self, addr: WordAddr, after: Word) -> Result<(), EmuError> {
+        self.memory.insert(addr, after);
+        Ok(())
+    }
+
+    fn peek_register(&self, idx: RegIdx) -> Word {
+        self.registers[idx]
+    }
+
+    fn peek_memory(&self, addr: WordAddr) -> Word {
+        *self.memory.get(&addr).unwrap_or(&0)
+    }
+
+    fn fetch(&mut self, pc: ByteAddr) -> Option<Instruction> {
+        self.program.instruction_at(pc.0)
+    }
+
+    fn check_data_load(&self, addr: ByteAddr) -> bool {
+        self.platform.can_read(addr.0)
+    }
+
+    fn check_data_store(&self, addr: ByteAddr) -> bool {
+        self.platform.can_write(addr.0)
+    }
+}
@@ -0,0 +1,426 @@
+//! A stub of the GDB remote serial protocol, to debug a guest running in a [`VMState`].
+//!
+//! Attach with `target remote <addr>` from a RISC-V `gdb` loaded with the same ELF. Supported:
+//! reading registers and memory, single-step, continue (interruptible with Ctrl-C), breakpoints
+//! on pc and watchpoints on memory words.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use itertools::Itertools;
+
+use crate::{
+    ByteAddr, EmuContext, EmuError, InsnCategory, Platform, StepRecord, VMState, WORD_SIZE,
+    WordAddr, rv32im::TrapCause,
+};
+
+/// The kind of memory access that triggers a watchpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watch {
+    Write,
+    Read,
+    Access,
+}
+
+/// Why the execution stopped.
+#[derive(Debug)]
+enum Stop {
+    Step,
+    Interrupted,
+    Breakpoint,
+    /// A watchpoint, with the address that `gdb` asked to watch.
+    Watchpoint(Watch, ByteAddr),
+    Halted(u32),
+    Error(EmuError),
+}
+
+/// The target description of `gdb`: the general purpose registers and the pc.
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+<architecture>riscv:rv32</architecture>
+<feature name="org.gnu.gdb.riscv.cpu">
+<reg name="zero" bitsize="32" type="int" regnum="0"/>
+<reg name="ra" bitsize="32" type="code_ptr"/>
+<reg name="sp" bitsize="32" type="data_ptr"/>
+<reg name="gp" bitsize="32" type="data_ptr"/>
+<reg name="tp" bitsize="32" type="data_ptr"/>
+<reg name="t0" bitsize="32" type="int"/>
+<reg name="t1" bitsize="32" type="int"/>
+<reg name="t2" bitsize="32" type="int"/>
+<reg name="fp" bitsize="32" type="data_ptr"/>
+<reg name="s1" bitsize="32" type="int"/>
+<reg name="a0" bitsize="32" type="int"/>
+<reg name="a1" bitsize="32" type="int"/>
+<reg name="a2" bitsize="32" type="int"/>
+<reg name="a3" bitsize="32" type="int"/>
+<reg name="a4" bitsize="32" type="int"/>
+<reg name="a5" bitsize="32" type="int"/>
+<reg name="a6" bitsize="32" type="int"/>
+<reg name="a7" bitsize="32" type="int"/>
+<reg name="s2" bitsize="32" type="int"/>
+<reg name="s3" bitsize="32" type="int"/>
+<reg name="s4" bitsize="32" type="int"/>
+<reg name="s5" bitsize="32" type="int"/>
+<reg name="s6" bitsize="32" type="int"/>
+<reg name="s7" bitsize="32" type="int"/>
+<reg name="s8" bitsize="32" type="int"/>
+<reg name="s9" bitsize="32" type="int"/>
+<reg name="s10" bitsize="32" type="int"/>
+<reg name="s11" bitsize="32" type="int"/>
+<reg name="t3" bitsize="32" type="int"/>
+<reg name="t4" bitsize="32" type="int"/>
+<reg name="t5" bitsize="32" type="int"/>
+<reg name="t6" bitsize="32" type="int"/>
+<reg name="pc" bitsize="32" type="code_ptr"/>
+</feature>
+</target>
+"#;
+
+/// The number of registers in the target description: 32 general purpose registers and the pc.
+const GDB_REGS: usize = 33;
+
+/// The maximum size of a packet from `gdb`, as advertised in `qSupported`.
+const PACKET_SIZE: usize = 0x4000;
+
+/// The maximum number of bytes of memory to read at once: each byte takes two hex digits in a
+/// reply, which `gdb` reads into a buffer of `PACKET_SIZE`.
+const MAX_READ_BYTES: u32 = (PACKET_SIZE / 2) as u32;
+
+/// How many steps to run between checks for an interrupt from `gdb`.
+const INTERRUPT_CHECK_STEPS: usize = 1 << 12;
+
+/// A debugging session of a VM, controlled by `gdb`.
+pub struct GdbStub {
+    vm: VMState,
+    breakpoints: BTreeSet<ByteAddr>,
+    /// The watched words, with the kind and the address of their watchpoint.
+    watchpoints: BTreeMap<WordAddr, (Watch, ByteAddr)>,
+}
+
+impl GdbStub {
+    pub fn new(vm: VMState) -> Self {
+        Self {
+            vm,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+        }
+    }
+
+    pub fn vm(&self) -> &VMState {
+        &self.vm
+    }
+
+    /// Wait for `gdb` to connect on `addr`, and serve it until it detaches or kills the guest.
+    pub fn serve(&mut self, addr: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        tracing::info!("waiting for gdb on {}", listener.local_addr()?);
+        let (stream, peer) = listener.accept()?;
+        tracing::info!("gdb connected from {peer}");
+        self.run(stream)
+    }
+
+    /// Serve `gdb` on a connected stream, until it detaches or kills the guest.
+    pub fn run(&mut self, stream: TcpStream) -> io::Result<()> {
+        // Packets are small and interactive.
+        stream.set_nodelay(true)?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        while let Some(packet) = read_packet(&mut reader, &mut writer)? {
+            tracing::trace!("gdb <- {packet}");
+            let reply = match packet.chars().next() {
+                Some('k') => return Ok(()),
+                Some('D') => {
+                    write_packet(&mut writer, "OK")?;
+                    return Ok(());
+                }
+                Some('c') => {
+                    let stop = self.resume(&mut reader)?;
+                    self.stop_reply(stop)
+                }
+                Some('s') => {
+                    let stop = self.step();
+                    self.stop_reply(stop)
+                }
+                _ => self.reply(&packet),
+            };
+            tracing::trace!("gdb -> {reply}");
+            write_packet(&mut writer, &reply)?;
+        }
+        Ok(())
+    }
+
+    /// Answer the packets that do not run the guest.
+    fn reply(&mut self, packet: &str) -> String {
+        if packet.is_empty() || !packet.is_char_boundary(1) {
+            return String::new();
+        }
+        let (command, args) = packet.split_at(1);
+        match command {
+            "?" => "S05".to_string(),
+            "g" => (0..GDB_REGS)
+                .map(|idx| hex_word(self.gdb_register(idx)))
+                .join(""),
+            "p" => usize::from_str_radix(args, 16)
+                .ok()
+                .filter(|&idx| idx < GDB_REGS)
+                .map_or("E01".to_string(), |idx| hex_word(self.gdb_register(idx))),
+            // A shorter read than requested is allowed, and `gdb` asks for the rest.
+            "m" => parse_range(args).map_or("E01".to_string(), |(addr, len)| {
+                (addr..addr.wrapping_add(len.min(MAX_READ_BYTES)))
+                    .map(|addr| format!("{:02x}", self.peek_byte(addr)))
+                    .join("")
+            }),
+            "Z" | "z" => self.update_point(command == "Z", args),
+            "H" | "T" => "OK".to_string(),
+            "q" => self.query(args),
+            _ => String::new(), // Not supported.
+        }
+    }
+
+    fn query(&self, query: &str) -> String {
+        if query.starts_with("Supported") {
+            format!("PacketSize={PACKET_SIZE:x};qXfer:features:read+;swbreak+")
+        } else if let Some(range) = query.strip_prefix("Xfer:features:read:target.xml:") {
+            let Some((offset, len)) = parse_range(range) else {
+                return "E01".to_string();
+            };
+            let start = (offset as usize).min(TARGET_XML.len());
+            let end = start.saturating_add(len as usize).min(TARGET_XML.len());
+            let more = if end < TARGET_XML.len() { 'm' } else { 'l' };
+            format!("{more}{}", &TARGET_XML[start..end])
+        } else {
+            match query {
+                "Attached" => "1",
+                "C" => "QC1",
+                "fThreadInfo" => "m1",
+                "sThreadInfo" => "l",
+                _ => "",
+            }
+            .to_string()
+        }
+    }
+
+    /// Insert or remove a breakpoint (`0` or `1`) or a watchpoint (`2`, `3`, or `4`).
+    fn update_point(&mut self, insert: bool, args: &str) -> String {
+        let Some((kind, range)) = args.split_once(',') else {
+            return "E01".to_string();
+        };
+        let Some((addr, len)) = parse_range(range) else {
+            return "E01".to_string();
+        };
+        let watch = match kind {
+            "0" | "1" => {
+                if insert {
+                    self.breakpoints.insert(ByteAddr(addr));
+                } else {
+                    self.breakpoints.remove(&ByteAddr(addr));
+                }
+                return "OK".to_string();
+            }
+            "2" => Watch::Write,
+            "3" => Watch::Read,
+            "4" => Watch::Access,
+            _ => return String::new(),
+        };
+        let first = ByteAddr(addr).waddr();
+        let last = ByteAddr(addr.wrapping_add(len.max(1) - 1)).waddr();
+        for word in first..=last {
+            if insert {
+                self.watchpoints.insert(word, (watch, ByteAddr(addr)));
+            } else {
+                self.watchpoints.remove(&word);
+            }
+        }
+        "OK".to_string()
+    }
+
+    /// Run until a breakpoint, a watchpoint, the end of the program, an error, or an interrupt.
+    fn resume(&mut self, reader: &mut BufReader<TcpStream>) -> io::Result<Stop> {
+        for steps in 1.. {
+            let stop = self.step();
+            if !matches!(stop, Stop::Step) {
+                return Ok(stop);
+            }
+            if self.breakpoints.contains(&self.vm.get_pc()) {
+                return Ok(Stop::Breakpoint);
+            }
+            if steps % INTERRUPT_CHECK_STEPS == 0 && interrupted(reader)? {
+                return Ok(Stop::Interrupted);
+            }
+        }
+        unreachable!("the steps do not overflow")
+    }
+
+    fn step(&mut self) -> Stop {
+        if self.vm.halted() {
+            return Stop::Halted(self.exit_code());
+        }
+        match self.vm.step() {
+            Ok(_) if self.vm.halted() => Stop::Halted(self.exit_code()),
+            Ok(step) => match self.watch_hit(&step) {
+                Some((watch, addr)) => Stop::Watchpoint(watch, addr),
+                None => Stop::Step,
+            },
+            Err(err) => Stop::Error(err),
+        }
+    }
+
+    /// Find a watchpoint triggered by the memory accesses of a step.
+    fn watch_hit(&self, step: &StepRecord) -> Option<(Watch, ByteAddr)> {
+        let is_store = matches!(InsnCategory::from(step.insn().kind), InsnCategory::Store);
+        let insn_ops = step.memory_op().map(|op| (op.addr, is_store));
+        let syscall_ops = step.syscall().into_iter().flat_map(|syscall| {
+            syscall
+                .mem_ops
+                .iter()
+                .map(|op| (op.addr, op.value.before != op.value.after))
+        });
+        insn_ops
+            .into_iter()
+            .chain(syscall_ops)
+            .find_map(|(addr, is_write)| {
+                let (watch, watched) = *self.watchpoints.get(&addr)?;
+                let hit = match watch {
+                    Watch::Write => is_write,
+                    Watch::Read => !is_write,
+                    Watch::Access => true,
+                };
+                hit.then_some((watch, watched))
+            })
+    }
+
+    fn stop_reply(&self, stop: Stop) -> String {
+        match stop {
+            Stop::Step => "S05".to_string(),
+            Stop::Interrupted => "S02".to_string(),
+            Stop::Breakpoint => "T05swbreak:;".to_string(),
+            Stop::Watchpoint(watch, addr) => {
+                let kind = match watch {
+                    Watch::Write => "watch",
+                    Watch::Read => "rwatch",
+                    Watch::Access => "awatch",
+                };
+                format!("T05{kind}:{:x};", addr.0)
+            }
+            Stop::Halted(exit_code) => format!("W{:02x}", exit_code & 0xff),
+            Stop::Error(err) => {
+                tracing::warn!("{}", err.report(self.vm.program()));
+                match err {
+                    EmuError::Trap {
+                        cause: TrapCause::IllegalInstruction(_),
+                        ..
+                    } => "S04", // SIGILL
+                    EmuError::Trap {
+                        access: Some(_), ..
+                    } => "S0b", // SIGSEGV
+                    _ => "S05", // SIGTRAP
+                }
+                .to_string()
+            }
+        }
+    }
+
+    /// Registers in the numbering of `gdb`: `x0` to `x31`, then the pc.
+    fn gdb_register(&self, idx: usize) -> u32 {
+        if idx < 32 {
+            self.vm.peek_register(idx)
+        } else {
+            self.vm.get_pc().0
+        }
+    }
+
+    fn peek_byte(&self, addr: u32) -> u8 {
+        let word = self.vm.peek_memory(ByteAddr(addr).waddr());
+        (word >> (8 * (addr % WORD_SIZE as u32))) as u8
+    }
+
+    fn exit_code(&self) -> u32 {
+        self.vm.peek_register(Platform::reg_arg0())
+    }
+}
+
+/// Read the next packet `$data#checksum`, skipping acknowledgments and interrupts outside of
+/// packets. A packet is acknowledged with `+`, or with `-` if its checksum is wrong so that
+/// `gdb` sends it again. Return `None` at the end of the stream.
+fn read_packet(reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<Option<String>> {
+    loop {
+        let mut skipped = vec![];
+        if reader.read_until(b'$', &mut skipped)? == 0 || skipped.last() != Some(&b'$') {
+            return Ok(None);
+        }
+        let mut data = vec![];
+        reader.read_until(b'#', &mut data)?;
+        if data.pop() != Some(b'#') {
+            return Ok(None);
+        }
+        let mut sum = [0; 2];
+        reader.read_exact(&mut sum)?;
+        let sum = std::str::from_utf8(&sum)
+            .ok()
+            .and_then(|sum| u8::from_str_radix(sum, 16).ok());
+        if sum != Some(checksum(&data)) {
+            tracing::warn!("gdb packet with a wrong checksum");
+            writer.write_all(b"-")?;
+            continue;
+        }
+        writer.write_all(b"+")?;
+        return String::from_utf8(data)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err));
+    }
+}
+
+fn write_packet(writer: &mut impl Write, data: &str) -> io::Result<()> {
+    write!(writer, "${data}#{:02x}", checksum(data.as_bytes()))?;
+    writer.flush()
+}
+
+/// The sum of the bytes of a packet, modulo 256.
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Whether `gdb` sent an interrupt (Ctrl-C) while the guest was running.
+fn interrupted(reader: &mut BufReader<TcpStream>) -> io::Result<bool> {
+    let mut byte = [0];
+    if let Some(&buffered) = reader.buffer().first() {
+        byte[0] = buffered;
+    } else {
+        let stream = reader.get_ref();
+        stream.set_nonblocking(true)?;
+        let peeked = stream.peek(&mut byte);
+        stream.set_nonblocking(false)?;
+        match peeked {
+            Ok(1) => {}
+            Ok(_) => return Ok(false),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+            Err(err) => return Err(err),
+        }
+    }
+    if byte[0] != 0x03 {
+        return Ok(false);
+    }
+    reader.read_exact(&mut byte)?;
+    Ok(true)
+}
+
+/// Parse `addr,length` in hexadecimal.
+fn parse_range(args: &str) -> Option<(u32, u32)> {
+    let (addr, len) = args.split_once(',')?;
+    Some((
+        u32::from_str_radix(addr, 16).ok()?,
+        u32::from_str_radix(len, 16).ok()?,
+    ))
+}
+
+/// A word in the little-endian hexadecimal encoding of `gdb`.
+fn hex_word(word: u32) -> String {
+    word.to_le_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .join("")
+}
@@ -1,29 +1,292 @@
-use std::iter::from_fn;
+use std::{collections::BTreeMap, iter::from_fn, ops::Range};
 
-use crate::{ByteAddr, EmuContext, VMState, WordAddr};
+use crate::{Addr, ByteAddr, EmuContext, IterAddresses, Platform, Program, WordAddr};
 
 const WORD_SIZE: usize = 4;
 const INFO_OUT_ADDR: WordAddr = ByteAddr(0xC000_0000).waddr();
 
-pub fn read_all_messages(state: &VMState) -> Vec<Vec<u8>> {
+/// The top byte of the length word of a message is its stream.
+const STREAM_SHIFT: u32 = 24;
+const INFO_STREAM: u32 = 0;
+const STDOUT_STREAM: u32 = 1;
+const STDERR_STREAM: u32 = 2;
+
+/// The output of a guest at `INFO_OUT_ADDR`, by stream.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Messages {
+    /// The messages of `ceno_rt::print!` and `ceno_rt::println!`, one per write.
+    pub info: Vec<Vec<u8>>,
+    /// The bytes written to stdout with `sys_write`, as by `std::println!`.
+    pub stdout: Vec<u8>,
+    /// The bytes written to stderr with `sys_write`, as by `std::eprintln!`.
+    pub stderr: Vec<u8>,
+}
+
+pub fn read_all_messages(state: &impl EmuContext) -> Messages {
+    let mut messages = Messages::default();
     let mut offset: WordAddr = WordAddr::from(0);
-    from_fn(move || match read_message(state, offset) {
-        out if out.is_empty() => None,
-        out => {
-            offset += out.len().div_ceil(WORD_SIZE) as u32 + 1;
-            Some(out)
+    loop {
+        let header = state.peek_memory(INFO_OUT_ADDR + offset);
+        if header == 0 {
+            break;
         }
-    })
-    .collect()
+        let (stream, byte_len) = (header >> STREAM_SHIFT, header & ((1 << STREAM_SHIFT) - 1));
+        let msg = read_message(state, offset, byte_len as usize);
+        offset += msg.len().div_ceil(WORD_SIZE) as u32 + 1;
+        match stream {
+            INFO_STREAM => messages.info.push(msg),
+            STDOUT_STREAM => messages.stdout.extend(msg),
+            STDERR_STREAM => messages.stderr.extend(msg),
+            _ => {}
+        }
+    }
+    messages
 }
 
-fn read_message(state: &VMState, offset: WordAddr) -> Vec<u8> {
+fn read_message(state: &impl EmuContext, offset: WordAddr, byte_len: usize) -> Vec<u8> {
     let out_addr = INFO_OUT_ADDR + offset;
-    let byte_len = state.peek_memory(out_addr) as usize;
-
     (out_addr + 1_usize..)
         .map(|address| state.peek_memory(address))
         .flat_map(u32::to_le_bytes)
         .take(byte_len)
         .collect::<Vec<_>>()
 }
+
+/// The panic report of a guest that halted with [`Platform::code_panic`]: the message and
+/// location of the panic.
+///
+/// The report is a frame at the address in `a1`: [`Platform::panic_report_magic`], the length in
+/// bytes, then the bytes. Without the magic word, the guest halted with this code on its own and
+/// there is no report. Reports longer than `MAX_PANIC_REPORT` bytes are truncated.
+pub fn read_panic_message(state: &impl EmuContext) -> Option<String> {
+    const MAX_PANIC_REPORT: usize = 1 << 16;
+
+    if state.peek_register(Platform::reg_arg0()) != Platform::code_panic() {
+        return None;
+    }
+    let frame = ByteAddr(state.peek_register(Platform::reg_arg1()));
+    if !frame.is_aligned() || state.peek_memory(frame.waddr()) != Platform::panic_report_magic() {
+        return None;
+    }
+    let byte_len = (state.peek_memory(frame.waddr() + 1_usize) as usize).min(MAX_PANIC_REPORT);
+    let bytes = (frame.waddr() + 2_usize..)
+        .map(|address| state.peek_memory(address))
+        .flat_map(u32::to_le_bytes)
+        .take(byte_len)
+        .collect::<Vec<_>>();
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The words of the public output segment, as bound to the proof.
+pub fn read_public_output_words(state: &impl EmuContext, platform: &Platform) -> Vec<u32> {
+    platform
+        .public_output
+        .iter_addresses()
+        .map(|addr| state.peek_memory(addr.into()))
+        .collect()
+}
+
+/// The messages committed by the guest with `ceno_rt::commit`.
+pub fn read_public_output(state: &impl EmuContext, platform: &Platform) -> Vec<Vec<u8>> {
+    decode_frames(&read_public_output_words(state, platform))
+}
+
+/// The program arguments and environment variables of a guest, read with `ceno_rt::args` and
+/// `ceno_rt::var`, or with `std::env`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuestEnv {
+    pub args: Vec<String>,
+    pub vars: BTreeMap<String, String>,
+}
+
+impl GuestEnv {
+    pub fn arg(&mut self, arg: impl Into<String>) -> &mut Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn var(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// The words of the env segment: the number of arguments and of variables, then each
+    /// argument and each `NAME=value`, as its length in bytes followed by the bytes.
+    pub fn to_words(&self) -> Vec<u32> {
+        let vars = self
+            .vars
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"));
+        let mut words = vec![self.args.len() as u32, self.vars.len() as u32];
+        for entry in self.args.iter().cloned().chain(vars) {
+            words.push(entry.len() as u32);
+            words.extend(entry.as_bytes().chunks(WORD_SIZE).map(|chunk| {
+                let mut word = [0; WORD_SIZE];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            }));
+        }
+        words
+    }
+}
+
+/// Place `env` in the initial memory of `program`, from the start of the `segment` of the
+/// platform, and return the part of `segment` that it takes.
+///
+/// As program data, the env is part of the proven initial memory.
+pub fn load_env(program: &mut Program, segment: &Range<Addr>, env: &GuestEnv) -> Range<Addr> {
+    let words = env.to_words();
+    let env_range = segment.start..segment.start + (words.len() * WORD_SIZE) as Addr;
+    assert!(
+        env_range.end <= segment.end,
+        "the env must fit in {} bytes",
+        segment.len()
+    );
+    program.image.extend(env_range.iter_addresses().zip(words));
+    env_range
+}
+
+/// Split words into length-prefixed messages, up to the first empty or truncated one.
+pub fn decode_frames(words: &[u32]) -> Vec<Vec<u8>> {
+    let mut rest = words;
+    from_fn(move || {
+        let (&byte_len, tail) = rest.split_first()?;
+        let word_len = (byte_len as usize).div_ceil(WORD_SIZE);
+        if byte_len == 0 || word_len > tail.len() {
+            return None;
+        }
+        let msg = tail[..word_len]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(byte_len as usize)
+            .collect();
+        rest = &tail[word_len..];
+        Some(msg)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{CENO_PLATFORM, InsnKind, Program, VMState, encode_rv32, encode_rv32u};
+
+    #[test]
+    fn test_decode_frames() {
+        let words = [3, u32::from_le_bytes([1, 2, 3, 0]), 5, 4, 5, 0, 0];
+        assert_eq!(decode_frames(&words), vec![vec![1, 2, 3], vec![
+            4, 0, 0, 0, 5
+        ]]);
+        // A frame longer than the segment is not returned.
+        assert_eq!(decode_frames(&[3, 7, 9, 100]), vec![vec![7, 0, 0]]);
+        assert!(decode_frames(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_read_panic_message() {
+        let report = CENO_PLATFORM.heap.start;
+        let program = Program::from(
+            &[
+                encode_rv32u(InsnKind::ADDI, 0, 0, Platform::reg_arg1() as u32, report),
+                encode_rv32u(
+                    InsnKind::ADDI,
+                    0,
+                    0,
+                    Platform::reg_arg0() as u32,
+                    Platform::code_panic(),
+                ),
+                encode_rv32u(
+                    InsnKind::ADDI,
+                    0,
+                    0,
+                    Platform::reg_ecall() as u32,
+                    Platform::ecall_halt(),
+                ),
+                encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            ][..],
+        );
+        let halt = |magic: u32| {
+            let mut vm = VMState::new(CENO_PLATFORM, Arc::new(program.clone()));
+            let words = [
+                magic,
+                5,
+                u32::from_le_bytes(*b"oops"),
+                u32::from_le_bytes(*b"!\0\0\0"),
+            ];
+            for (addr, word) in (ByteAddr(report).waddr()..).zip(words) {
+                vm.init_memory(addr, word);
+            }
+            assert_eq!(read_panic_message(&vm), None);
+
+            vm.iter_until_halt().for_each(|step| {
+                step.unwrap();
+            });
+            assert!(vm.halted());
+            read_panic_message(&vm)
+        };
+        assert_eq!(
+            halt(Platform::panic_report_magic()).as_deref(),
+            Some("oops!")
+        );
+        // A guest that halts with the exit code of a panic by itself did not panic.
+        assert_eq!(halt(0), None);
+    }
+
+    #[test]
+    fn test_read_all_messages() {
+        let program = Program::from(&[encode_rv32(InsnKind::ECALL, 0, 0, 0, 0)][..]);
+        let mut vm = VMState::new(CENO_PLATFORM, Arc::new(program));
+        let words = [
+            // print!("hi")
+            2,
+            u32::from_le_bytes(*b"hi\0\0"),
+            // Writes of 3 and 2 bytes to stdout, and 1 byte to stderr.
+            STDOUT_STREAM << STREAM_SHIFT | 3,
+            u32::from_le_bytes(*b"abc\0"),
+            STDERR_STREAM << STREAM_SHIFT | 1,
+            u32::from_le_bytes(*b"!\0\0\0"),
+            STDOUT_STREAM << STREAM_SHIFT | 2,
+            u32::from_le_bytes(*b"de\0\0"),
+        ];
+        for (addr, word) in (INFO_OUT_ADDR..).zip(words) {
+            vm.init_memory(addr, word);
+        }
+        assert_eq!(read_all_messages(&vm), Messages {
+            info: vec![b"hi".to_vec()],
+            stdout: b"abcde".to_vec(),
+            stderr: b"!".to_vec(),
+        });
+    }
+
+    #[test]
+    fn test_load_env() {
+        let mut program = Program::from(&[encode_rv32(InsnKind::ECALL, 0, 0, 0, 0)][..]);
+        let mut env = GuestEnv::default();
+        env.arg("guest").var("MODE", "fast");
+        let segment = CENO_PLATFORM.env;
+        let env_range = load_env(&mut program, &segment, &env);
+
+        let words = [
+            1,
+            1,
+            5,
+            u32::from_le_bytes(*b"gues"),
+            u32::from_le_bytes(*b"t\0\0\0"),
+            9,
+            u32::from_le_bytes(*b"MODE"),
+            u32::from_le_bytes(*b"=fas"),
+            u32::from_le_bytes(*b"t\0\0\0"),
+        ];
+        assert_eq!(
+            env_range,
+            segment.start..segment.start + 4 * words.len() as u32
+        );
+        let image = env_range
+            .iter_addresses()
+            .map(|addr| program.image[&addr])
+            .collect::<Vec<_>>();
+        assert_eq!(image, words);
+    }
+}
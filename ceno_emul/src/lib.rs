@@ -4,7 +4,10 @@ mod addr;
 pub use addr::*;
 
 mod platform;
-pub use platform::{CENO_PLATFORM, Platform};
+pub use platform::{CENO_PLATFORM, Platform, Segment};
+
+mod error;
+pub use error::EmuError;
 
 mod tracer;
 pub use tracer::{Change, MemOp, ReadOp, StepRecord, Tracer, WriteOp};
@@ -12,18 +15,38 @@ pub use tracer::{Change, MemOp, ReadOp, StepRecord, Tracer, WriteOp};
 mod vm_state;
 pub use vm_state::VMState;
 
+mod snapshot;
+pub use snapshot::VMSnapshot;
+
+mod fast_vm;
+pub use fast_vm::FastVM;
+
 mod rv32im;
 pub use rv32im::{
-    EmuContext, InsnCategory, InsnFormat, InsnKind, Instruction, encode_rv32, encode_rv32u,
+    EmuContext, InsnCategory, InsnFormat, InsnKind, Instruction, TrapCause, encode_rv32,
+    encode_rv32u,
 };
 
 mod elf;
-pub use elf::Program;
+pub use elf::{Program, Symbol};
+
+mod debug_info;
+pub use debug_info::{Section, SourceLines, SourceLocation};
+
+pub mod gdb;
+
+mod profiler;
+pub use profiler::Profiler;
 
 pub mod disassemble;
 
 mod syscalls;
-pub use syscalls::{KECCAK_PERMUTE, keccak_permute::KECCAK_WORDS};
+pub use syscalls::{
+    ED25519_ADD, KECCAK_PERMUTE, SECP256K1_ADD, SECP256K1_DOUBLE, SHA_COMPRESS, SHA_EXTEND,
+    ec::{EC_FIELD_WORDS, EC_POINT_WORDS, ED25519_D, ED25519_P, SECP256K1_P},
+    keccak_permute::KECCAK_WORDS,
+    sha256::{SHA_EXTEND_WORDS, SHA_ROUND_CONSTANTS, SHA_STATE_WORDS},
+};
 
 pub mod test_utils;
 
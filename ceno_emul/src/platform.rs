@@ -15,11 +15,31 @@ pub struct Platform {
     pub stack: Range<Addr>,
     pub heap: Range<Addr>,
     pub public_io: Range<Addr>,
+    /// Values committed by the guest, bound to the proof as public output.
+    pub public_output: Range<Addr>,
     pub hints: Range<Addr>,
+    /// The program arguments and environment variables, part of the initial memory.
+    /// See [`crate::host_utils::load_env`].
+    pub env: Range<Addr>,
     /// If true, ecall instructions are no-op instead of trap. Testing only.
     pub unsafe_ecall_nop: bool,
 }
 
+/// A region of the virtual memory of a [`Platform`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Rom,
+    ProgData,
+    Stack,
+    Heap,
+    PublicIo,
+    PublicOutput,
+    Hints,
+    Env,
+    /// Not part of any region.
+    Unmapped,
+}
+
 impl Display for Platform {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let prog_data: Option<Range<Addr>> = match (self.prog_data.first(), self.prog_data.last()) {
@@ -28,13 +48,15 @@ impl Display for Platform {
         };
         write!(
             f,
-            "Platform {{ rom: {:?}, prog_data: {:?}, stack: {:?}, heap: {:?}, public_io: {:?}, hints: {:?}, unsafe_ecall_nop: {} }}",
+            "Platform {{ rom: {:?}, prog_data: {:?}, stack: {:?}, heap: {:?}, public_io: {:?}, public_output: {:?}, hints: {:?}, env: {:?}, unsafe_ecall_nop: {} }}",
             self.rom,
             prog_data,
             self.stack,
             self.heap,
             self.public_io,
+            self.public_output,
             self.hints,
+            self.env,
             self.unsafe_ecall_nop
         )
     }
@@ -46,7 +68,9 @@ pub const CENO_PLATFORM: Platform = Platform {
     stack: 0xB0000000..0xC0000000,
     heap: 0x8000_0000..0xFFFF_0000,
     public_io: 0x3000_1000..0x3000_2000,
+    public_output: 0x3000_2000..0x3000_3000,
     hints: 0x4000_0000..0x5000_0000,
+    env: 0x3000_3000..0x4000_0000,
     unsafe_ecall_nop: false,
 };
 
@@ -69,10 +93,18 @@ impl Platform {
         self.public_io.contains(&addr)
     }
 
+    pub fn is_pub_output(&self, addr: Addr) -> bool {
+        self.public_output.contains(&addr)
+    }
+
     pub fn is_hints(&self, addr: Addr) -> bool {
         self.hints.contains(&addr)
     }
 
+    pub fn is_env(&self, addr: Addr) -> bool {
+        self.env.contains(&addr)
+    }
+
     /// Virtual address of a register.
     pub const fn register_vma(index: RegIdx) -> Addr {
         // Register VMAs are aligned, cannot be confused with indices, and readable in hex.
@@ -84,6 +116,31 @@ impl Platform {
         (vma >> 8) as RegIdx
     }
 
+    /// The region that an address belongs to.
+    pub fn segment(&self, addr: Addr) -> Segment {
+        if self.is_env(addr) {
+            // The env is also in the program data.
+            Segment::Env
+        } else if self.is_prog_data(addr) {
+            Segment::ProgData
+        } else if self.is_rom(addr) {
+            Segment::Rom
+        } else if self.stack.contains(&addr) {
+            // The stack is checked before the heap that may contain it.
+            Segment::Stack
+        } else if self.heap.contains(&addr) {
+            Segment::Heap
+        } else if self.is_pub_io(addr) {
+            Segment::PublicIo
+        } else if self.is_pub_output(addr) {
+            Segment::PublicOutput
+        } else if self.is_hints(addr) {
+            Segment::Hints
+        } else {
+            Segment::Unmapped
+        }
+    }
+
     // Startup.
 
     pub const fn pc_base(&self) -> Addr {
@@ -97,7 +154,11 @@ impl Platform {
     }
 
     pub fn can_write(&self, addr: Addr) -> bool {
-        self.is_ram(addr) || self.is_pub_io(addr) || self.is_hints(addr)
+        self.is_ram(addr)
+            || self.is_pub_io(addr)
+            || self.is_pub_output(addr)
+            || self.is_hints(addr)
+            || self.is_env(addr)
     }
 
     // Environment calls.
@@ -126,6 +187,28 @@ impl Platform {
     pub const fn code_success() -> u32 {
         0
     }
+
+    /// The exit code of a guest that panicked, with the address of its panic report in `a1`.
+    ///
+    /// See `ceno_rt::PANIC_EXIT_CODE` and [`crate::host_utils::read_panic_message`].
+    pub const fn code_panic() -> u32 {
+        1
+    }
+
+    /// The first word of a panic report, so that a guest that halts with [`Self::code_panic`]
+    /// on its own is not taken for a panic.
+    ///
+    /// See `ceno_rt::PANIC_REPORT_MAGIC`.
+    pub const fn panic_report_magic() -> u32 {
+        u32::from_le_bytes(*b"PNC!")
+    }
+
+    /// The exit code of a guest that ran out of heap memory.
+    ///
+    /// See `ceno_rt::OUT_OF_MEMORY_EXIT_CODE`.
+    pub const fn code_out_of_memory() -> u32 {
+        2
+    }
 }
 
 #[cfg(test)]
@@ -141,6 +224,12 @@ mod tests {
         assert!(!p.is_rom(p.heap.end - WORD_SIZE as Addr));
         assert!(!p.is_ram(p.rom.start));
         assert!(!p.is_ram(p.rom.end - WORD_SIZE as Addr));
+        // Public input and public output do not overlap.
+        assert!(!p.is_pub_io(p.public_output.start));
+        assert!(!p.is_pub_output(p.public_io.end - WORD_SIZE as Addr));
+        // The env is between the public output and the hints.
+        assert!(!p.is_env(p.public_output.end - WORD_SIZE as Addr));
+        assert!(!p.is_env(p.hints.start));
         // Registers do not overlap with ROM or RAM.
         for reg in [
             Platform::register_vma(0),
@@ -150,4 +239,17 @@ mod tests {
             assert!(!p.is_ram(reg));
         }
     }
+
+    #[test]
+    fn test_segment() {
+        let p = CENO_PLATFORM;
+        assert_eq!(p.segment(p.rom.start), Segment::Rom);
+        assert_eq!(p.segment(p.stack.end - WORD_SIZE as Addr), Segment::Stack);
+        assert_eq!(p.segment(p.heap.start), Segment::Heap);
+        assert_eq!(p.segment(p.public_io.start), Segment::PublicIo);
+        assert_eq!(p.segment(p.public_output.start), Segment::PublicOutput);
+        assert_eq!(p.segment(p.hints.start), Segment::Hints);
+        assert_eq!(p.segment(p.env.start), Segment::Env);
+        assert_eq!(p.segment(0), Segment::Unmapped);
+    }
 }
@@ -0,0 +1,89 @@
+use std::{collections::HashMap, io};
+
+use itertools::Itertools;
+
+use crate::{InsnKind, Program, RegIdx, StepRecord};
+
+/// Attribute the steps of an execution to the functions of the guest, by call stack.
+///
+/// Calls and returns are recognized by the RISC-V link register conventions: a jump that
+/// writes `ra` (or the alternate link register `t0`) is a call, and a `jalr` from a link
+/// register that writes `x0` is a return. The profile is written in the folded-stack format of
+/// flamegraph tools, with one count per step.
+pub struct Profiler<'a> {
+    program: &'a Program,
+    /// The functions of the active frames, by start address. `None` for unknown code.
+    stack: Vec<Option<u32>>,
+    /// The number of steps of each stack, including the function of the step itself.
+    samples: HashMap<Vec<Option<u32>>, u64>,
+}
+
+impl<'a> Profiler<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            stack: vec![],
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Count a step in its stack, then follow the call or return that it makes.
+    pub fn observe(&mut self, step: &StepRecord) {
+        let function = self.function_at(step.pc().before.0);
+        self.stack.push(function);
+        match self.samples.get_mut(&self.stack) {
+            Some(count) => *count += 1,
+            None => {
+                self.samples.insert(self.stack.clone(), 1);
+            }
+        }
+        self.stack.pop();
+
+        let insn = step.insn();
+        match insn.kind {
+            InsnKind::JAL | InsnKind::JALR if is_link(insn.rd) => self.stack.push(function),
+            InsnKind::JALR if insn.rd == 0 && is_link(insn.rs1) => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// The total number of steps observed.
+    pub fn total(&self) -> u64 {
+        self.samples.values().sum()
+    }
+
+    /// Write one line per stack, `caller;...;callee count`, sorted by stack.
+    pub fn write_folded(&self, mut out: impl io::Write) -> io::Result<()> {
+        let lines = self
+            .samples
+            .iter()
+            .map(|(stack, count)| {
+                let frames = stack.iter().map(|&f| self.function_name(f)).join(";");
+                (frames, count)
+            })
+            .sorted();
+        for (frames, count) in lines {
+            writeln!(out, "{frames} {count}")?;
+        }
+        Ok(())
+    }
+
+    fn function_at(&self, pc: u32) -> Option<u32> {
+        self.program.symbol_at(pc).map(|(addr, _)| addr)
+    }
+
+    fn function_name(&self, function: Option<u32>) -> &str {
+        function
+            .and_then(|addr| self.program.symbols.get(&addr))
+            .map_or("[unknown]", |symbol| &symbol.name)
+    }
+}
+
+/// Whether a register is `ra` or `t0`, the link registers of the calling convention.
+fn is_link(reg: RegIdx) -> bool {
+    const RA: RegIdx = 1;
+    const T0: RegIdx = 5;
+    reg == RA || reg == T0
+}
@@ -14,11 +14,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{Result, anyhow};
 use num_derive::ToPrimitive;
 use strum_macros::{Display, EnumIter};
 
-use super::addr::{ByteAddr, RegIdx, WORD_SIZE, Word, WordAddr};
+use super::addr::{ByteAddr, COMPRESSED_PC_STEP_SIZE, PC_STEP_SIZE, RegIdx, Word, WordAddr};
+use crate::EmuError;
+
+type Result<T> = std::result::Result<T, EmuError>;
 
 /// Convenience function to create an `Instruction` with the given fields.
 ///
@@ -31,6 +33,7 @@ pub const fn encode_rv32(kind: InsnKind, rs1: u32, rs2: u32, rd: u32, imm: i32)
         rd: rd as usize,
         imm,
         raw: 0,
+        compressed: false,
     }
 }
 
@@ -45,6 +48,7 @@ pub const fn encode_rv32u(kind: InsnKind, rs1: u32, rs2: u32, rd: u32, imm: u32)
         rd: rd as usize,
         imm: imm as i32,
         raw: 0,
+        compressed: false,
     }
 }
 
@@ -83,7 +87,7 @@ pub trait EmuContext {
     fn peek_memory(&self, addr: WordAddr) -> Word;
 
     /// Load from instruction cache
-    fn fetch(&mut self, pc: WordAddr) -> Option<Instruction>;
+    fn fetch(&mut self, pc: ByteAddr) -> Option<Instruction>;
 
     // Check access for data load
     fn check_data_load(&self, _addr: ByteAddr) -> bool {
@@ -96,19 +100,32 @@ pub trait EmuContext {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TrapCause {
     InstructionAddressMisaligned,
     InstructionAccessFault,
     IllegalInstruction(u32),
     Breakpoint,
-    LoadAddressMisaligned,
+    LoadAddressMisaligned(ByteAddr),
     LoadAccessFault(ByteAddr),
     StoreAddressMisaligned(ByteAddr),
-    StoreAccessFault,
+    StoreAccessFault(ByteAddr),
     EcallError,
 }
 
+impl TrapCause {
+    /// The address of the memory access that trapped, if any.
+    pub fn access_addr(&self) -> Option<ByteAddr> {
+        match *self {
+            TrapCause::LoadAddressMisaligned(addr)
+            | TrapCause::LoadAccessFault(addr)
+            | TrapCause::StoreAddressMisaligned(addr)
+            | TrapCause::StoreAccessFault(addr) => Some(addr),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Instruction {
     pub kind: InsnKind,
@@ -121,6 +138,8 @@ pub struct Instruction {
     /// Set to 0, if you are creating an instruction directly,
     /// instead of decoding it from a raw 32-bit `Word`.
     pub raw: Word,
+    /// Whether the instruction was expanded from a 16-bit compressed instruction (RVC).
+    pub compressed: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -235,6 +254,16 @@ impl From<InsnKind> for InsnFormat {
 
 impl Instruction {
     pub const RD_NULL: u32 = 32;
+
+    /// The size of the instruction in bytes, which is the step of the pc to the next one.
+    pub const fn size(&self) -> u32 {
+        if self.compressed {
+            COMPRESSED_PC_STEP_SIZE as u32
+        } else {
+            PC_STEP_SIZE as u32
+        }
+    }
+
     pub fn rd_internal(&self) -> u32 {
         match InsnFormat::from(self.kind) {
             R | I | U | J if self.rd != 0 => self.rd as u32,
@@ -260,11 +289,9 @@ impl Instruction {
 pub fn step<C: EmuContext>(ctx: &mut C) -> Result<()> {
     let pc = ctx.get_pc();
 
-    let Some(insn) = ctx.fetch(pc.waddr()) else {
+    let Some(insn) = ctx.fetch(pc) else {
         ctx.trap(TrapCause::InstructionAccessFault)?;
-        return Err(anyhow!(
-            "Fatal: could not fetch instruction at pc={pc:?}, ELF does not have instructions there."
-        ));
+        return Ok(());
     };
 
     tracing::trace!("pc: {:x}, kind: {:?}", pc.0, insn.kind);
@@ -287,13 +314,13 @@ fn step_compute<M: EmuContext>(ctx: &mut M, kind: InsnKind, insn: &Instruction)
     use super::InsnKind::*;
 
     let pc = ctx.get_pc();
-    let mut new_pc = pc + WORD_SIZE;
+    let mut new_pc = pc + insn.size();
     let imm_i = insn.imm as u32;
     let out = match kind {
         // Instructions that do not read rs1 nor rs2.
         JAL => {
             new_pc = pc.wrapping_add(insn.imm as u32);
-            (pc + WORD_SIZE).0
+            (pc + insn.size()).0
         }
         _ => {
             // Instructions that read rs1 but not rs2.
@@ -323,7 +350,7 @@ fn step_compute<M: EmuContext>(ctx: &mut M, kind: InsnKind, insn: &Instruction)
                 }
                 JALR => {
                     new_pc = ByteAddr(rs1.wrapping_add(imm_i) & !1);
-                    (pc + WORD_SIZE).0
+                    (pc + insn.size()).0
                 }
 
                 _ => {
@@ -394,7 +421,7 @@ fn step_compute<M: EmuContext>(ctx: &mut M, kind: InsnKind, insn: &Instruction)
             }
         }
     };
-    if !new_pc.is_aligned() {
+    if !new_pc.is_insn_aligned() {
         return ctx.trap(TrapCause::InstructionAddressMisaligned);
     }
     ctx.store_register(insn.rd_internal() as usize, out)?;
@@ -422,10 +449,10 @@ fn step_branch<M: EmuContext>(ctx: &mut M, kind: InsnKind, decoded: &Instruction
     let new_pc = if taken {
         pc.wrapping_add(decoded.imm as u32)
     } else {
-        pc + WORD_SIZE
+        pc + decoded.size()
     };
 
-    if !new_pc.is_aligned() {
+    if !new_pc.is_insn_aligned() {
         return ctx.trap(TrapCause::InstructionAddressMisaligned);
     }
     ctx.set_pc(new_pc);
@@ -451,7 +478,7 @@ fn step_load<M: EmuContext>(ctx: &mut M, kind: InsnKind, decoded: &Instruction)
         }
         InsnKind::LH => {
             if addr.0 & 0x01 != 0 {
-                return ctx.trap(TrapCause::LoadAddressMisaligned);
+                return ctx.trap(TrapCause::LoadAddressMisaligned(addr));
             }
             let mut out = (data >> shift) & 0xffff;
             if out & 0x8000 != 0 {
@@ -461,21 +488,21 @@ fn step_load<M: EmuContext>(ctx: &mut M, kind: InsnKind, decoded: &Instruction)
         }
         InsnKind::LW => {
             if addr.0 & 0x03 != 0 {
-                return ctx.trap(TrapCause::LoadAddressMisaligned);
+                return ctx.trap(TrapCause::LoadAddressMisaligned(addr));
             }
             data
         }
         InsnKind::LBU => (data >> shift) & 0xff,
         InsnKind::LHU => {
             if addr.0 & 0x01 != 0 {
-                return ctx.trap(TrapCause::LoadAddressMisaligned);
+                return ctx.trap(TrapCause::LoadAddressMisaligned(addr));
             }
             (data >> shift) & 0xffff
         }
         _ => unreachable!(),
     };
     ctx.store_register(decoded.rd_internal() as usize, out)?;
-    ctx.set_pc(ctx.get_pc() + WORD_SIZE);
+    ctx.set_pc(ctx.get_pc() + decoded.size());
     Ok(true)
 }
 
@@ -486,7 +513,7 @@ fn step_store<M: EmuContext>(ctx: &mut M, kind: InsnKind, decoded: &Instruction)
     let shift = 8 * (addr.0 & 3);
     if !ctx.check_data_store(addr) {
         tracing::error!("mstore: addr={:x?},rs1={:x}", addr, rs1);
-        return ctx.trap(TrapCause::StoreAccessFault);
+        return ctx.trap(TrapCause::StoreAccessFault(addr));
     }
     let mut data = ctx.peek_memory(addr.waddr());
     match kind {
@@ -512,7 +539,7 @@ fn step_store<M: EmuContext>(ctx: &mut M, kind: InsnKind, decoded: &Instruction)
         _ => unreachable!(),
     }
     ctx.store_memory(addr.waddr(), data)?;
-    ctx.set_pc(ctx.get_pc() + WORD_SIZE);
+    ctx.set_pc(ctx.get_pc() + decoded.size());
     Ok(true)
 }
 
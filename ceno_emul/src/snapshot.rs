@@ -0,0 +1,152 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Read, Write},
+};
+
+use anyhow::{Result, ensure};
+
+use crate::{
+    VMState, WORD_SIZE,
+    addr::{Cycle, Word, WordAddr},
+};
+
+/// A copy of the machine state, to resume an execution from.
+///
+/// It is taken at segment boundaries, and can be saved to checkpoint long executions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VMSnapshot {
+    pub pc: Word,
+    /// The cycle of the next instruction.
+    pub cycle: Cycle,
+    pub halted: bool,
+    pub registers: [Word; VMState::REG_COUNT],
+    pub memory: HashMap<WordAddr, Word>,
+    /// The cycle of the latest access to each address, if tracing.
+    pub latest_accesses: HashMap<WordAddr, Cycle>,
+}
+
+impl VMSnapshot {
+    const MAGIC: &'static [u8; 8] = b"CENOSNAP";
+    const VERSION: u32 = 1;
+    /// The number of words in a page of memory.
+    const PAGE_WORDS: u32 = 1024;
+    const PAGE_SIZE: u32 = Self::PAGE_WORDS * WORD_SIZE as u32;
+
+    /// Write the snapshot in a binary format.
+    ///
+    /// All integers are little-endian, and addresses are byte addresses. The memory is written in
+    /// pages of `PAGE_WORDS` words: only the pages with words set, each as its index, a bitmap of
+    /// the words set, and these words.
+    pub fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(Self::MAGIC)?;
+        out.write_all(&Self::VERSION.to_le_bytes())?;
+        out.write_all(&self.pc.to_le_bytes())?;
+        out.write_all(&self.cycle.to_le_bytes())?;
+        out.write_all(&[self.halted as u8])?;
+        for register in &self.registers {
+            out.write_all(&register.to_le_bytes())?;
+        }
+
+        let mut pages: BTreeMap<u32, BTreeMap<u32, Word>> = BTreeMap::new();
+        for (&addr, &value) in &self.memory {
+            let addr = u32::from(addr);
+            pages
+                .entry(addr / Self::PAGE_SIZE)
+                .or_default()
+                .insert(addr % Self::PAGE_SIZE / WORD_SIZE as u32, value);
+        }
+        out.write_all(&(pages.len() as u32).to_le_bytes())?;
+        for (index, words) in &pages {
+            let mut bitmap = [0u32; Self::PAGE_WORDS as usize / 32];
+            for offset in words.keys() {
+                bitmap[*offset as usize / 32] |= 1 << (offset % 32);
+            }
+            out.write_all(&index.to_le_bytes())?;
+            for bits in bitmap {
+                out.write_all(&bits.to_le_bytes())?;
+            }
+            for value in words.values() {
+                out.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        let accesses = self.latest_accesses.iter().collect::<BTreeMap<_, _>>();
+        out.write_all(&(accesses.len() as u32).to_le_bytes())?;
+        for (&addr, &cycle) in accesses {
+            out.write_all(&u32::from(addr).to_le_bytes())?;
+            out.write_all(&cycle.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read a snapshot written by [`VMSnapshot::write_to`].
+    pub fn read_from(mut input: impl Read) -> Result<Self> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        ensure!(&magic == Self::MAGIC, "not a VM snapshot");
+        let version = read_u32(&mut input)?;
+        ensure!(
+            version == Self::VERSION,
+            "unsupported VM snapshot version {version}"
+        );
+
+        let pc = read_u32(&mut input)?;
+        let cycle = read_u64(&mut input)?;
+        let mut halted = [0];
+        input.read_exact(&mut halted)?;
+        let mut registers = [0; VMState::REG_COUNT];
+        for register in &mut registers {
+            *register = read_u32(&mut input)?;
+        }
+
+        let mut memory = HashMap::new();
+        for _ in 0..read_u32(&mut input)? {
+            let index = read_u32(&mut input)?;
+            ensure!(
+                index <= u32::MAX / Self::PAGE_SIZE,
+                "page {index} out of the address space"
+            );
+            let mut bitmap = [0u32; Self::PAGE_WORDS as usize / 32];
+            for bits in &mut bitmap {
+                *bits = read_u32(&mut input)?;
+            }
+            for offset in 0..Self::PAGE_WORDS {
+                if bitmap[offset as usize / 32] & (1 << (offset % 32)) != 0 {
+                    let addr = WordAddr::from(index * Self::PAGE_SIZE + offset * WORD_SIZE as u32);
+                    memory.insert(addr, read_u32(&mut input)?);
+                }
+            }
+        }
+
+        let mut latest_accesses = HashMap::new();
+        for _ in 0..read_u32(&mut input)? {
+            let addr = read_u32(&mut input)?;
+            ensure!(
+                addr % WORD_SIZE as u32 == 0,
+                "unaligned access address {addr:#x}"
+            );
+            latest_accesses.insert(WordAddr::from(addr), read_u64(&mut input)?);
+        }
+
+        Ok(VMSnapshot {
+            pc,
+            cycle,
+            halted: halted[0] != 0,
+            registers,
+            memory,
+            latest_accesses,
+        })
+    }
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
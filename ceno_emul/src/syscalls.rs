@@ -1,17 +1,26 @@
-use crate::{RegIdx, Tracer, VMState, Word, WordAddr, WriteOp};
+use crate::{EmuContext, RegIdx, Tracer, Word, WordAddr, WriteOp};
 use anyhow::Result;
 
+pub mod ec;
 pub mod keccak_permute;
+pub mod sha256;
 
 // Using the same function codes as sp1:
 // https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/code.rs
 
-pub use ceno_rt::syscalls::KECCAK_PERMUTE;
+pub use ceno_rt::syscalls::{
+    ED25519_ADD, KECCAK_PERMUTE, SECP256K1_ADD, SECP256K1_DOUBLE, SHA_COMPRESS, SHA_EXTEND,
+};
 
 /// Trace the inputs and effects of a syscall.
-pub fn handle_syscall(vm: &VMState, function_code: u32) -> Result<SyscallEffects> {
+pub fn handle_syscall(vm: &impl EmuContext, function_code: u32) -> Result<SyscallEffects> {
     match function_code {
         KECCAK_PERMUTE => Ok(keccak_permute::keccak_permute(vm)),
+        SHA_EXTEND => Ok(sha256::sha_extend(vm)),
+        SHA_COMPRESS => Ok(sha256::sha_compress(vm)),
+        SECP256K1_ADD => ec::secp256k1_add(vm),
+        SECP256K1_DOUBLE => ec::secp256k1_double(vm),
+        ED25519_ADD => ec::ed25519_add(vm),
         // TODO: introduce error types.
         _ => Err(anyhow::anyhow!("Unknown syscall: {}", function_code)),
     }
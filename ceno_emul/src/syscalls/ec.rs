@@ -0,0 +1,190 @@
+use anyhow::{Result, bail};
+use itertools::{Itertools, izip};
+use num_bigint::BigUint;
+
+use crate::{Change, EmuContext, Platform, WORD_SIZE, Word, WordAddr, WriteOp};
+
+use super::{SyscallEffects, SyscallWitness};
+
+pub const EC_FIELD_WORDS: usize = 8; // u32 words
+pub const EC_POINT_WORDS: usize = EC_FIELD_WORDS * 2; // u32 words
+
+/// The modulus of the base field of secp256k1, in little-endian words.
+pub const SECP256K1_P: [Word; EC_FIELD_WORDS] = [
+    0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+];
+
+/// The modulus of the base field of ed25519, in little-endian words.
+pub const ED25519_P: [Word; EC_FIELD_WORDS] = [
+    0xffffffed, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0x7fffffff,
+];
+
+/// The parameter `d = -121665 / 121666` of ed25519, in little-endian words.
+pub const ED25519_D: [Word; EC_FIELD_WORDS] = [
+    0x135978a3, 0x75eb4dca, 0x4141d8ab, 0x00700a4d, 0x7779e898, 0x8cc74079, 0x2b6ffe73, 0x52036cee,
+];
+
+/// A point in affine coordinates.
+type Point = (BigUint, BigUint);
+
+/// Trace the addition of two secp256k1 points, `p = p + q` where `p != ±q`.
+///
+/// Compatible with:
+/// https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/precompiles/weierstrass/add.rs
+pub fn secp256k1_add(vm: &impl EmuContext) -> Result<SyscallEffects> {
+    let modulus = BigUint::from_slice(&SECP256K1_P);
+    binary_op(vm, &modulus, |(x1, y1), (x2, y2)| {
+        if x1 == x2 {
+            bail!("secp256k1_add of points with the same x coordinate");
+        }
+        let lambda = sub(y2, y1, &modulus) * inv(&sub(x2, x1, &modulus), &modulus) % &modulus;
+        let x3 = sub(&sub(&(&lambda * &lambda), x1, &modulus), x2, &modulus);
+        let y3 = sub(&(lambda * sub(x1, &x3, &modulus)), y1, &modulus);
+        Ok((x3, y3))
+    })
+}
+
+/// Trace the doubling of a secp256k1 point, `p = p + p` where `p` has a non-zero `y`.
+///
+/// Compatible with:
+/// https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/precompiles/weierstrass/double.rs
+pub fn secp256k1_double(vm: &impl EmuContext) -> Result<SyscallEffects> {
+    let modulus = BigUint::from_slice(&SECP256K1_P);
+    let p_ptr = vm.peek_register(Platform::reg_arg0());
+    let reg_ops = vec![read_register(vm, Platform::reg_arg0())];
+
+    let p_addrs = word_addrs(p_ptr);
+    let (x, y) = read_point(vm, &p_addrs, &modulus)?;
+    if y == BigUint::ZERO {
+        bail!("secp256k1_double of a point with y = 0");
+    }
+    let lambda = BigUint::from(3u32) * &x * &x * inv(&(&y + &y), &modulus) % &modulus;
+    let x3 = sub(&sub(&(&lambda * &lambda), &x, &modulus), &x, &modulus);
+    let y3 = sub(&(lambda * sub(&x, &x3, &modulus)), &y, &modulus);
+
+    Ok(SyscallEffects {
+        witness: SyscallWitness {
+            mem_ops: write_point(vm, p_addrs, &(x3, y3)),
+            reg_ops,
+        },
+        next_pc: None,
+    })
+}
+
+/// Trace the addition of two ed25519 points, `p = p + q`, where the points are on the curve.
+///
+/// Compatible with:
+/// https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/precompiles/edwards/add.rs
+pub fn ed25519_add(vm: &impl EmuContext) -> Result<SyscallEffects> {
+    let modulus = BigUint::from_slice(&ED25519_P);
+    let d = BigUint::from_slice(&ED25519_D);
+    binary_op(vm, &modulus, |(x1, y1), (x2, y2)| {
+        // On the curve, the divisors are not zero since `d` is not a square.
+        for (x, y) in [(x1, y1), (x2, y2)] {
+            let (xx, yy) = (x * x % &modulus, y * y % &modulus);
+            if (&d * &xx * &yy + xx + 1u32) % &modulus != yy {
+                bail!("ed25519_add of a point off the curve");
+            }
+        }
+        let k = &d * x1 * x2 * y1 * y2 % &modulus;
+        let x3 = (x1 * y2 + y1 * x2) * inv(&(BigUint::from(1u32) + &k), &modulus) % &modulus;
+        let y3 = (y1 * y2 + x1 * x2) * inv(&sub(&BigUint::from(1u32), &k, &modulus), &modulus)
+            % &modulus;
+        Ok((x3, y3))
+    })
+}
+
+/// Trace `p = op(p, q)` with `p` pointed to by `x10` and `q` by `x11`, which must not overlap.
+///
+/// The memory operations are the updates of the words of `p`, followed by the reads of `q`.
+fn binary_op(
+    vm: &impl EmuContext,
+    modulus: &BigUint,
+    op: impl Fn(&Point, &Point) -> Result<Point>,
+) -> Result<SyscallEffects> {
+    let p_ptr = vm.peek_register(Platform::reg_arg0());
+    let q_ptr = vm.peek_register(Platform::reg_arg1());
+    let reg_ops = vec![
+        read_register(vm, Platform::reg_arg0()),
+        read_register(vm, Platform::reg_arg1()),
+    ];
+
+    if p_ptr.abs_diff(q_ptr) < (EC_POINT_WORDS * WORD_SIZE) as u32 {
+        bail!("ec syscall on overlapping points");
+    }
+    let p_addrs = word_addrs(p_ptr);
+    let q_addrs = word_addrs(q_ptr);
+    let p = read_point(vm, &p_addrs, modulus)?;
+    let q = read_point(vm, &q_addrs, modulus)?;
+    let output = op(&p, &q)?;
+
+    let mut mem_ops = write_point(vm, p_addrs, &output);
+    mem_ops.extend(q_addrs.into_iter().map(|addr| {
+        let value = vm.peek_memory(addr);
+        WriteOp {
+            addr,
+            value: Change::new(value, value),
+            previous_cycle: 0, // Cycle set later in finalize().
+        }
+    }));
+
+    Ok(SyscallEffects {
+        witness: SyscallWitness { mem_ops, reg_ops },
+        next_pc: None,
+    })
+}
+
+fn read_register(vm: &impl EmuContext, idx: usize) -> WriteOp {
+    let value = vm.peek_register(idx);
+    WriteOp::new_register_op(
+        idx,
+        Change::new(value, value),
+        0, // Cycle set later in finalize().
+    )
+}
+
+fn word_addrs(ptr: Word) -> Vec<WordAddr> {
+    (ptr..)
+        .step_by(WORD_SIZE)
+        .take(EC_POINT_WORDS)
+        .map(WordAddr::from)
+        .collect_vec()
+}
+
+fn read_point(vm: &impl EmuContext, addrs: &[WordAddr], modulus: &BigUint) -> Result<Point> {
+    let words = addrs.iter().map(|&addr| vm.peek_memory(addr)).collect_vec();
+    let (x, y) = words.split_at(EC_FIELD_WORDS);
+    let (x, y) = (BigUint::from_slice(x), BigUint::from_slice(y));
+    if &x >= modulus || &y >= modulus {
+        bail!("point coordinates are not reduced");
+    }
+    Ok((x, y))
+}
+
+fn write_point(vm: &impl EmuContext, addrs: Vec<WordAddr>, (x, y): &Point) -> Vec<WriteOp> {
+    let after = [x, y].into_iter().flat_map(to_words).collect_vec();
+    izip!(addrs, after)
+        .map(|(addr, after)| WriteOp {
+            addr,
+            value: Change::new(vm.peek_memory(addr), after),
+            previous_cycle: 0, // Cycle set later in finalize().
+        })
+        .collect_vec()
+}
+
+/// The little-endian words of a field element.
+pub fn to_words(x: &BigUint) -> [Word; EC_FIELD_WORDS] {
+    let mut words = [0; EC_FIELD_WORDS];
+    for (word, digit) in izip!(&mut words, x.iter_u32_digits()) {
+        *word = digit;
+    }
+    words
+}
+
+fn sub(a: &BigUint, b: &BigUint, modulus: &BigUint) -> BigUint {
+    (a + modulus - b) % modulus
+}
+
+fn inv(a: &BigUint, modulus: &BigUint) -> BigUint {
+    a.modpow(&(modulus - 2u32), modulus)
+}
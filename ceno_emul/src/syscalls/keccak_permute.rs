@@ -1,7 +1,7 @@
 use itertools::{Itertools, izip};
 use tiny_keccak::keccakf;
 
-use crate::{Change, EmuContext, Platform, VMState, WORD_SIZE, WordAddr, WriteOp};
+use crate::{Change, EmuContext, Platform, WORD_SIZE, WordAddr, WriteOp};
 
 use super::{SyscallEffects, SyscallWitness};
 
@@ -14,7 +14,7 @@ pub const KECCAK_WORDS: usize = KECCAK_CELLS * 2; // u32 words
 /// https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/precompiles/keccak256/permute.rs
 ///
 /// TODO: test compatibility.
-pub fn keccak_permute(vm: &VMState) -> SyscallEffects {
+pub fn keccak_permute(vm: &impl EmuContext) -> SyscallEffects {
     let state_ptr = vm.peek_register(Platform::reg_arg0());
 
     // Read the argument `state_ptr`.
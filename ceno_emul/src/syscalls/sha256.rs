@@ -0,0 +1,152 @@
+use itertools::{Itertools, izip};
+
+use crate::{Change, EmuContext, Platform, WORD_SIZE, Word, WordAddr, WriteOp};
+
+use super::{SyscallEffects, SyscallWitness};
+
+pub const SHA_EXTEND_WORDS: usize = 64; // u32 words
+pub const SHA_STATE_WORDS: usize = 8; // u32 words
+
+/// The round constants of SHA-256.
+pub const SHA_ROUND_CONSTANTS: [u32; SHA_EXTEND_WORDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Trace the execution of the SHA-256 message schedule.
+///
+/// The words `w[0..16]` at `w_ptr` are read and the words `w[16..64]` are written, once each.
+///
+/// Compatible with:
+/// https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/precompiles/sha256/extend.rs
+pub fn sha_extend(vm: &impl EmuContext) -> SyscallEffects {
+    let w_ptr = vm.peek_register(Platform::reg_arg0());
+
+    // Read the argument `w_ptr`.
+    let reg_ops = vec![WriteOp::new_register_op(
+        Platform::reg_arg0(),
+        Change::new(w_ptr, w_ptr),
+        0, // Cycle set later in finalize().
+    )];
+
+    let addrs = word_addrs(w_ptr, SHA_EXTEND_WORDS);
+    let input = addrs.iter().map(|&addr| vm.peek_memory(addr)).collect_vec();
+
+    let mut w = input.clone();
+    for i in 16..SHA_EXTEND_WORDS {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = s1
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 16]);
+    }
+
+    let mem_ops = write_ops(addrs, input, w);
+
+    assert_eq!(mem_ops.len(), SHA_EXTEND_WORDS);
+    SyscallEffects {
+        witness: SyscallWitness { mem_ops, reg_ops },
+        next_pc: None,
+    }
+}
+
+/// Trace the execution of the SHA-256 compression function.
+///
+/// The memory operations are the reads of the words `w[0..64]` at `w_ptr`, followed by the
+/// updates of the 8 words of the hash state at `state_ptr`.
+///
+/// Compatible with:
+/// https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/core/executor/src/syscalls/precompiles/sha256/compress.rs
+pub fn sha_compress(vm: &impl EmuContext) -> SyscallEffects {
+    let w_ptr = vm.peek_register(Platform::reg_arg0());
+    let state_ptr = vm.peek_register(Platform::reg_arg1());
+
+    // Read the arguments `w_ptr` and `state_ptr`.
+    let reg_ops = vec![
+        WriteOp::new_register_op(
+            Platform::reg_arg0(),
+            Change::new(w_ptr, w_ptr),
+            0, // Cycle set later in finalize().
+        ),
+        WriteOp::new_register_op(
+            Platform::reg_arg1(),
+            Change::new(state_ptr, state_ptr),
+            0, // Cycle set later in finalize().
+        ),
+    ];
+
+    let w_addrs = word_addrs(w_ptr, SHA_EXTEND_WORDS);
+    let w = w_addrs
+        .iter()
+        .map(|&addr| vm.peek_memory(addr))
+        .collect_vec();
+    let state_addrs = word_addrs(state_ptr, SHA_STATE_WORDS);
+    let state = state_addrs
+        .iter()
+        .map(|&addr| vm.peek_memory(addr))
+        .collect_vec();
+
+    // Compute the compression function.
+    let mut vars: [Word; SHA_STATE_WORDS] = state.clone().try_into().unwrap();
+    for (&w, &k) in izip!(&w, &SHA_ROUND_CONSTANTS) {
+        let [a, b, c, d, e, f, g, h] = vars;
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(k)
+            .wrapping_add(w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+        vars = [
+            temp1.wrapping_add(temp2),
+            a,
+            b,
+            c,
+            d.wrapping_add(temp1),
+            e,
+            f,
+            g,
+        ];
+    }
+    let output = izip!(&state, vars)
+        .map(|(&h, v)| h.wrapping_add(v))
+        .collect_vec();
+
+    // Read the message schedule, and write the updated state.
+    let mut mem_ops = write_ops(w_addrs, w.clone(), w);
+    mem_ops.extend(write_ops(state_addrs, state, output));
+
+    assert_eq!(mem_ops.len(), SHA_EXTEND_WORDS + SHA_STATE_WORDS);
+    SyscallEffects {
+        witness: SyscallWitness { mem_ops, reg_ops },
+        next_pc: None,
+    }
+}
+
+fn word_addrs(ptr: Word, count: usize) -> Vec<WordAddr> {
+    (ptr..)
+        .step_by(WORD_SIZE)
+        .take(count)
+        .map(WordAddr::from)
+        .collect_vec()
+}
+
+fn write_ops(addrs: Vec<WordAddr>, before: Vec<Word>, after: Vec<Word>) -> Vec<WriteOp> {
+    izip!(addrs, before, after)
+        .map(|(addr, before, after)| WriteOp {
+            addr,
+            value: Change { before, after },
+            previous_cycle: 0, // Cycle set later in finalize().
+        })
+        .collect_vec()
+}
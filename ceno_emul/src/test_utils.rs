@@ -2,7 +2,6 @@ use crate::{
     CENO_PLATFORM, InsnKind, Instruction, Platform, Program, StepRecord, VMState, encode_rv32,
     encode_rv32u, syscalls::KECCAK_PERMUTE,
 };
-use anyhow::Result;
 
 pub fn keccak_step() -> (StepRecord, Vec<Instruction>) {
     let instructions = vec![
@@ -18,7 +17,7 @@ pub fn keccak_step() -> (StepRecord, Vec<Instruction>) {
     let pc = CENO_PLATFORM.pc_base();
     let program = Program::new(pc, pc, instructions.clone(), Default::default());
     let mut vm = VMState::new(CENO_PLATFORM, program.into());
-    let steps = vm.iter_until_halt().collect::<Result<Vec<_>>>().unwrap();
+    let steps = vm.iter_until_halt().collect::<Result<Vec<_>, _>>().unwrap();
 
     (steps[2].clone(), instructions)
 }
@@ -73,7 +73,7 @@ impl StepRecord {
         rd: Change<Word>,
         prev_cycle: Cycle,
     ) -> StepRecord {
-        let pc = Change::new(pc, pc + PC_STEP_SIZE);
+        let pc = Change::new(pc, pc + insn_code.size());
         StepRecord::new_insn(
             cycle,
             pc,
@@ -135,7 +135,7 @@ impl StepRecord {
         mem_op: ReadOp,
         prev_cycle: Cycle,
     ) -> StepRecord {
-        let pc = Change::new(pc, pc + PC_STEP_SIZE);
+        let pc = Change::new(pc, pc + insn_code.size());
         StepRecord::new_insn(
             cycle,
             pc,
@@ -162,7 +162,7 @@ impl StepRecord {
         rd: Change<Word>,
         prev_cycle: Cycle,
     ) -> StepRecord {
-        let pc = Change::new(pc, pc + PC_STEP_SIZE);
+        let pc = Change::new(pc, pc + insn_code.size());
         StepRecord::new_insn(cycle, pc, insn_code, None, None, Some(rd), None, prev_cycle)
     }
 
@@ -185,7 +185,7 @@ impl StepRecord {
         memory_op: WriteOp,
         prev_cycle: Cycle,
     ) -> StepRecord {
-        let pc = Change::new(pc, pc + PC_STEP_SIZE);
+        let pc = Change::new(pc, pc + insn_code.size());
         StepRecord::new_insn(
             cycle,
             pc,
@@ -323,6 +323,17 @@ impl Tracer {
         }
     }
 
+    /// A tracer that continues an execution at `cycle`, after the given latest accesses.
+    pub fn resume(cycle: Cycle, latest_accesses: HashMap<WordAddr, Cycle>) -> Tracer {
+        Tracer {
+            record: StepRecord {
+                cycle,
+                ..StepRecord::default()
+            },
+            latest_accesses,
+        }
+    }
+
     /// Return the completed step and advance to the next cycle.
     pub fn advance(&mut self) -> StepRecord {
         let next_cycle = self.record.cycle + Self::SUBCYCLES_PER_INSN;
@@ -336,8 +347,8 @@ impl Tracer {
         self.record.pc.after = pc;
     }
 
-    pub fn fetch(&mut self, pc: WordAddr, value: Instruction) {
-        self.record.pc.before = pc.baddr();
+    pub fn fetch(&mut self, pc: ByteAddr, value: Instruction) {
+        self.record.pc.before = pc;
         self.record.insn = value;
     }
 
@@ -409,6 +420,11 @@ impl Tracer {
             .unwrap_or(0)
     }
 
+    /// Forget all previous accesses, as if the state so far were the initial state at cycle 0.
+    pub fn reset_accesses(&mut self) {
+        self.latest_accesses.clear();
+    }
+
     /// Return all the addresses that were accessed and the cycle when they were last accessed.
     pub fn final_accesses(&self) -> &HashMap<WordAddr, Cycle> {
         &self.latest_accesses
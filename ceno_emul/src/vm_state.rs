@@ -2,14 +2,14 @@ use std::collections::HashMap;
 
 use super::rv32im::EmuContext;
 use crate::{
-    PC_STEP_SIZE, Program, WORD_SIZE,
+    EmuError, PC_STEP_SIZE, Program, VMSnapshot, WORD_SIZE,
     addr::{ByteAddr, RegIdx, Word, WordAddr},
     platform::Platform,
     rv32im::{Instruction, TrapCause},
     syscalls::{SyscallEffects, handle_syscall},
     tracer::{Change, StepRecord, Tracer},
 };
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use std::{iter::from_fn, ops::Deref, sync::Arc};
 
 /// An implementation of the machine state and of the side-effects of operations.
@@ -81,7 +81,39 @@ impl VMState {
         self.memory.insert(addr, value);
     }
 
-    pub fn iter_until_halt(&mut self) -> impl Iterator<Item = Result<StepRecord>> + '_ {
+    /// Copy the current machine state.
+    pub fn snapshot(&self) -> VMSnapshot {
+        VMSnapshot {
+            pc: self.pc,
+            cycle: self.tracer.cycle(),
+            halted: self.halted,
+            registers: self.registers,
+            memory: self.memory.clone(),
+            latest_accesses: self.tracer.final_accesses().clone(),
+        }
+    }
+
+    /// Continue the execution from a state copied by [`VMState::snapshot`].
+    ///
+    /// The snapshot must come from an execution of the same program on the same platform.
+    pub fn restore(&mut self, snapshot: VMSnapshot) {
+        self.pc = snapshot.pc;
+        self.halted = snapshot.halted;
+        self.registers = snapshot.registers;
+        self.memory = snapshot.memory;
+        self.tracer = Tracer::resume(snapshot.cycle, snapshot.latest_accesses);
+    }
+
+    /// Begin a new segment of the execution, and return the state it starts from.
+    ///
+    /// The accesses of previous segments are forgotten: the first access to each address in the
+    /// new segment refers to the initial state of the segment, at cycle 0.
+    pub fn start_segment(&mut self) -> VMSnapshot {
+        self.tracer.reset_accesses();
+        self.snapshot()
+    }
+
+    pub fn iter_until_halt(&mut self) -> impl Iterator<Item = Result<StepRecord, EmuError>> + '_ {
         from_fn(move || {
             if self.halted() {
                 None
@@ -91,11 +123,15 @@ impl VMState {
         })
     }
 
-    fn step(&mut self) -> Result<StepRecord> {
+    /// Execute one instruction.
+    pub fn step(&mut self) -> Result<StepRecord, EmuError> {
         crate::rv32im::step(self)?;
         let step = self.tracer.advance();
         if step.is_busy_loop() && !self.halted() {
-            Err(anyhow!("Stuck in loop {}", "{}"))
+            Err(EmuError::StuckInLoop {
+                pc: step.pc().before,
+                cycle: step.cycle(),
+            })
         } else {
             Ok(step)
         }
@@ -110,7 +146,7 @@ impl VMState {
         self.halted = true;
     }
 
-    fn apply_syscall(&mut self, effects: SyscallEffects) -> Result<()> {
+    fn apply_syscall(&mut self, effects: SyscallEffects) {
         for (addr, value) in effects.iter_mem_values() {
             self.memory.insert(addr, value);
         }
@@ -123,13 +159,12 @@ impl VMState {
         self.set_pc(next_pc.into());
 
         self.tracer.track_syscall(effects);
-        Ok(())
     }
 }
 
 impl EmuContext for VMState {
     // Expect an ecall to terminate the program: function HALT with argument exit_code.
-    fn ecall(&mut self) -> Result<bool> {
+    fn ecall(&mut self) -> Result<bool, EmuError> {
         let function = self.load_register(Platform::reg_ecall())?;
         if function == Platform::ecall_halt() {
             let exit_code = self.load_register(Platform::reg_arg0())?;
@@ -139,7 +174,7 @@ impl EmuContext for VMState {
         } else {
             match handle_syscall(self, function) {
                 Ok(effects) => {
-                    self.apply_syscall(effects)?;
+                    self.apply_syscall(effects);
                     Ok(true)
                 }
                 Err(err) if self.platform.unsafe_ecall_nop => {
@@ -163,8 +198,14 @@ impl EmuContext for VMState {
         }
     }
 
-    fn trap(&self, cause: TrapCause) -> Result<bool> {
-        Err(anyhow!("Trap {:?}", cause)) // Crash.
+    fn trap(&self, cause: TrapCause) -> Result<bool, EmuError> {
+        Err(EmuError::trap(
+            cause,
+            ByteAddr(self.pc),
+            self.tracer.cycle(),
+            &self.program,
+            &self.platform,
+        )) // Crash.
     }
 
     fn on_normal_end(&mut self, _decoded: &Instruction) {
@@ -180,13 +221,13 @@ impl EmuContext for VMState {
     }
 
     /// Load a register and record this operation.
-    fn load_register(&mut self, idx: RegIdx) -> Result<Word> {
+    fn load_register(&mut self, idx: RegIdx) -> Result<Word, EmuError> {
         self.tracer.load_register(idx, self.peek_register(idx));
         Ok(self.peek_register(idx))
     }
 
     /// Store a register and record this operation.
-    fn store_register(&mut self, idx: RegIdx, after: Word) -> Result<()> {
+    fn store_register(&mut self, idx: RegIdx, after: Word) -> Result<(), EmuError> {
         if idx != 0 {
             let before = self.peek_register(idx);
             self.tracer.store_register(idx, Change { before, after });
@@ -196,14 +237,14 @@ impl EmuContext for VMState {
     }
 
     /// Load a memory word and record this operation.
-    fn load_memory(&mut self, addr: WordAddr) -> Result<Word> {
+    fn load_memory(&mut self, addr: WordAddr) -> Result<Word, EmuError> {
         let value = self.peek_memory(addr);
         self.tracer.load_memory(addr, value);
         Ok(value)
     }
 
     /// Store a memory word and record this operation.
-    fn store_memory(&mut self, addr: WordAddr, after: Word) -> Result<()> {
+    fn store_memory(&mut self, addr: WordAddr, after: Word) -> Result<(), EmuError> {
         let before = self.peek_memory(addr);
         self.tracer.store_memory(addr, Change { after, before });
         self.memory.insert(addr, after);
@@ -220,11 +261,8 @@ impl EmuContext for VMState {
         *self.memory.get(&addr).unwrap_or(&0)
     }
 
-    fn fetch(&mut self, pc: WordAddr) -> Option<Instruction> {
-        let byte_pc: ByteAddr = pc.into();
-        let relative_pc = byte_pc.0.wrapping_sub(self.program.base_address);
-        let idx = (relative_pc / WORD_SIZE as u32) as usize;
-        let word = self.program.instructions.get(idx).copied()?;
+    fn fetch(&mut self, pc: ByteAddr) -> Option<Instruction> {
+        let word = self.program.instruction_at(pc.0)?;
         self.tracer.fetch(pc, word);
         Some(word)
     }
@@ -0,0 +1,20 @@
+#!/bin/bash
+# Build the test fixtures in bin/ from the sources in isa/.
+#
+# Requires a C preprocessor, llvm-mc, and ld.lld (or rust-lld from a Rust toolchain). The debug
+# info of the binaries refers to the lines of the sources.
+set -euo pipefail
+cd "$(dirname "$0")"
+
+LD=${LD:-$(find "$(rustc --print sysroot)" -name rust-lld | head -n 1)}
+
+mkdir -p bin
+for src in isa/rv32u*/*.S; do
+    suite=$(basename "$(dirname "$src")")
+    name=$(basename "$src" .S)
+    out="bin/$suite-ceno-$name"
+    cpp -nostdinc -Ienv -x assembler-with-cpp "$src" > "$out.s"
+    llvm-mc -g --fdebug-compilation-dir=. -triple=riscv32 -mattr=+m -filetype=obj "$out.s" -o "$out.o"
+    "$LD" -flavor gnu -T env/link.ld -z max-page-size=16 "$out.o" -o "$out"
+    rm "$out.s" "$out.o"
+done
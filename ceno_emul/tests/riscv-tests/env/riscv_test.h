@@ -0,0 +1,53 @@
+// The environment of the tests on Ceno: bare code from the entrypoint, without privileged
+// instructions. The result is written to `tohost` as in riscv-tests, 1 for a pass or
+// (TESTNUM << 1) | 1 for a failure, then the program halts with exit code 0 or TESTNUM.
+
+#ifndef _ENV_CENO_H
+#define _ENV_CENO_H
+
+#define RVTEST_RV32U
+#define RVTEST_RV32M
+
+#define TESTNUM gp
+
+// The halt ecall of ceno_emul::Platform.
+#define CENO_REG_ECALL t0
+#define CENO_ECALL_HALT 0
+
+#define RVTEST_CODE_BEGIN                                               \
+        .section .text.init;                                            \
+        .align  2;                                                      \
+        .globl _start;                                                  \
+_start:                                                                 \
+        li TESTNUM, 0;
+
+#define RVTEST_CODE_END                                                 \
+        unimp
+
+#define RVTEST_PASS                                                     \
+        li TESTNUM, 1;                                                  \
+        la t1, tohost;                                                  \
+        sw TESTNUM, 0(t1);                                              \
+        li a0, 0;                                                       \
+        li CENO_REG_ECALL, CENO_ECALL_HALT;                             \
+        ecall
+
+#define RVTEST_FAIL                                                     \
+        mv a0, TESTNUM;                                                 \
+        sll TESTNUM, TESTNUM, 1;                                        \
+        or TESTNUM, TESTNUM, 1;                                         \
+        la t1, tohost;                                                  \
+        sw TESTNUM, 0(t1);                                              \
+        li CENO_REG_ECALL, CENO_ECALL_HALT;                             \
+        ecall
+
+#define RVTEST_DATA_BEGIN                                               \
+        .data;                                                          \
+        .align 4;                                                       \
+        .globl tohost;                                                  \
+tohost: .word 0;                                                        \
+        .align 4;
+
+#define RVTEST_DATA_END
+
+#endif
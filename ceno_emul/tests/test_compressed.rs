@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use ceno_emul::{
+    CENO_PLATFORM, EmuContext, FastVM, InsnKind, Instruction, Platform, Program, StepRecord,
+    VMState, disassemble::expand_compressed, encode_rv32, encode_rv32u,
+};
+
+#[test]
+fn test_expand_compressed() {
+    use InsnKind::*;
+    let sp = 2;
+    let (ra, a0, a1) = (1, 10, 11);
+    for (half, (kind, rd, rs1, rs2, imm)) in [
+        (0x0085, (ADDI, ra, ra, 0, 1)),     // c.addi ra, 1
+        (0x557d, (ADDI, a0, 0, 0, -1)),     // c.li a0, -1
+        (0x6505, (ADDI, a0, 0, 0, 0x1000)), // c.lui a0, 0x1
+        (0x717d, (ADDI, sp, sp, 0, -16)),   // c.addi16sp sp, -16
+        (0x852e, (ADD, a0, 0, a1, 0)),      // c.mv a0, a1
+        (0x952e, (ADD, a0, a0, a1, 0)),     // c.add a0, a1
+        (0x8082, (JALR, 0, ra, 0, 0)),      // c.jr ra
+        (0x41c8, (LW, a0, a1, 0, 4)),       // c.lw a0, 4(a1)
+        (0xc02a, (SW, 0, sp, a0, 0)),       // c.swsp a0, 0(sp)
+        (0xc101, (BEQ, 0, a0, 0, 0)),       // c.beqz a0, 0
+        (0x0000, (INVALID, 0, 0, 0, 0)),    // c.unimp
+        (0x9002, (INVALID, 0, 0, 0, 0)),    // c.ebreak
+    ] {
+        assert_eq!(expand_compressed(half), Instruction {
+            kind,
+            rd,
+            rs1,
+            rs2,
+            imm,
+            raw: half as u32,
+            compressed: true,
+        });
+    }
+}
+
+#[test]
+fn test_compressed_program() -> Result<()> {
+    let pc = CENO_PLATFORM.pc_base();
+    let program = Arc::new(Program::new(pc, pc, program_mixed(), Default::default()));
+    assert_eq!(
+        program.pcs(),
+        [0, 2, 6, 8, 10, 14, 16, 20].map(|offset| pc + offset)
+    );
+    assert_eq!(program.code_end(), pc + 24);
+    assert_eq!(program.instruction_at(pc + 4), None);
+
+    let mut ctx = VMState::new(CENO_PLATFORM, program.clone());
+    let steps = ctx
+        .iter_until_halt()
+        .collect::<Result<Vec<StepRecord>, _>>()?;
+    let step_pcs = steps
+        .iter()
+        .map(|step| step.pc().before.0)
+        .collect::<Vec<_>>();
+    assert_eq!(step_pcs, [0, 2, 6, 8, 10, 16, 20].map(|offset| pc + offset));
+    assert_eq!(ctx.peek_register(1), pc + 14);
+    assert_eq!(ctx.peek_register(Platform::reg_arg0()), 4);
+
+    let mut fast = FastVM::new(CENO_PLATFORM, program);
+    fast.run(usize::MAX)?;
+    assert_eq!(fast.exit_code(), Some(4));
+    Ok(())
+}
+
+/// Compressed instructions between 32-bit instructions on 2-byte boundaries.
+fn program_mixed() -> Vec<Instruction> {
+    vec![
+        // a0 = -1 + 5;
+        expand_compressed(0x557d),
+        encode_rv32(InsnKind::ADDI, 0, 0, 11, 5),
+        expand_compressed(0x952e),
+        // Not taken.
+        expand_compressed(0xc101),
+        // Jump over c.unimp.
+        encode_rv32(InsnKind::JAL, 0, 0, 1, 6),
+        expand_compressed(0x0000),
+        // Halt with exit code a0.
+        encode_rv32u(
+            InsnKind::ADDI,
+            0,
+            0,
+            Platform::reg_ecall() as u32,
+            Platform::ecall_halt(),
+        ),
+        encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+    ]
+}
@@ -0,0 +1,91 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use ceno_emul::{
+    InsnKind, Program,
+    disassemble::{expand_compressed, transpile, write_listing},
+    encode_rv32,
+};
+use elf::{ElfBytes, endian::LittleEndian};
+
+#[test]
+fn test_instruction_display() {
+    use InsnKind::*;
+    let (ra, sp, a0, a1) = (1, 2, 10, 11);
+    for (insn, expected) in [
+        (encode_rv32(ADD, a0, a1, a0, 0), "add a0, a0, a1"),
+        (encode_rv32(ADDI, sp, 0, sp, -16), "addi sp, sp, -16"),
+        (encode_rv32(SRAI, a1, 0, a0, 3), "srai a0, a1, 3"),
+        (encode_rv32(LW, sp, 0, a0, 12), "lw a0, 12(sp)"),
+        (encode_rv32(SB, a0, a1, 0, -1), "sb a1, -1(a0)"),
+        (encode_rv32(BNE, a0, 0, 0, -8), "bne a0, zero, .-8"),
+        (encode_rv32(JAL, 0, 0, ra, 16), "jal ra, .+16"),
+        (encode_rv32(JALR, ra, 0, 0, 0), "jalr zero, 0(ra)"),
+        (encode_rv32(ECALL, 0, 0, 0, 0), "ecall"),
+        (encode_rv32(INVALID, 0, 0, 0, 0), "unimp"),
+        // c.mv a0, a1
+        (expand_compressed(0x852e), "add a0, zero, a1"),
+    ] {
+        assert_eq!(insn.to_string(), expected);
+    }
+
+    // LUI and AUIPC are transpiled to ADDI, with the pc of AUIPC in the immediate.
+    let [lui, auipc] = transpile(0x1000, &[0x12345537, 0x00001597])[..] else {
+        unreachable!()
+    };
+    assert_eq!(
+        lui.to_string(),
+        "addi a0, zero, 0x12345000  # lui a0, 0x12345"
+    );
+    assert_eq!(auipc.to_string(), "addi a1, zero, 0x2004  # auipc a1, 0x1");
+}
+
+#[test]
+fn test_write_listing() -> Result<()> {
+    let elf = fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/riscv-tests/bin/rv32ui-ceno-jal"),
+    )?;
+    let program = Program::load_elf(&elf, u32::MAX)?;
+    let mut listing = vec![];
+    write_listing(&program, &mut listing)?;
+    let listing = String::from_utf8(listing)?;
+
+    let lines = listing.lines().collect::<Vec<_>>();
+    assert_eq!(&lines[..4], [
+        "",
+        "20000000 <_start>:",
+        "; isa/rv32ui/jal.S:14",
+        "20000000:\t00000193\taddi gp, zero, 0",
+    ]);
+    // jal x4, target_2, at the line 24 of the source.
+    let target = program.addr_of("target_2").unwrap();
+    let jal = format!(
+        "jal tp, .+16  # {target:#x} <_start+{:#x}>",
+        target - program.entry
+    );
+    let idx = lines.iter().position(|line| line.ends_with(&jal)).unwrap();
+    assert_eq!(lines[idx - 1], "; isa/rv32ui/jal.S:24");
+    assert_eq!(
+        lines.iter().filter(|line| line.contains(":\t")).count(),
+        program.instructions.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_load_elf_with_bad_line_info() -> Result<()> {
+    let mut elf = fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/riscv-tests/bin/rv32ui-ceno-jal"),
+    )?;
+    let lines = ElfBytes::<LittleEndian>::minimal_parse(&elf)?
+        .section_header_by_name(".debug_line")?
+        .unwrap();
+    let start = lines.sh_offset as usize;
+    elf[start..start + lines.sh_size as usize].fill(0xff);
+
+    // The program loads without its source lines.
+    let program = Program::load_elf(&elf, u32::MAX)?;
+    assert!(program.source_lines.is_empty());
+    assert!(program.addr_of("target_2").is_some());
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+use anyhow::Result;
+use ceno_emul::{
+    CENO_PLATFORM, InsnKind, Instruction, Platform, Program, VMState, encode_rv32, encode_rv32u,
+    gdb::GdbStub,
+};
+use itertools::Itertools;
+
+#[test]
+fn test_gdb_session() -> Result<()> {
+    let pc = CENO_PLATFORM.pc_base();
+    let heap = CENO_PLATFORM.heap.start;
+    let program = Program::new(pc, pc, program_store_and_halt(), Default::default());
+    let vm = VMState::new(CENO_PLATFORM, Arc::new(program));
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = thread::spawn(move || -> Result<()> {
+        let (stream, _) = listener.accept()?;
+        GdbStub::new(vm).run(stream)?;
+        Ok(())
+    });
+    let mut gdb = Client::connect(addr)?;
+
+    assert!(
+        gdb.request("qSupported:swbreak+")?
+            .contains("qXfer:features:read+")
+    );
+    assert!(
+        gdb.request("qXfer:features:read:target.xml:0,4000")?
+            .starts_with('l')
+    );
+
+    // All registers are zero, then the pc.
+    let registers = gdb.request("g")?;
+    assert_eq!(registers.len(), 33 * 8);
+    assert_eq!(registers[..32 * 8], "0".repeat(32 * 8));
+    assert_eq!(registers[32 * 8..], hex_word(pc));
+
+    // Break on the store.
+    assert_eq!(gdb.request(&format!("Z0,{:x},4", pc + 8))?, "OK");
+    assert_eq!(gdb.request("c")?, "T05swbreak:;");
+    assert_eq!(gdb.request("p20")?, hex_word(pc + 8));
+    assert_eq!(gdb.request("p1")?, hex_word(3));
+    assert_eq!(gdb.request(&format!("z0,{:x},4", pc + 8))?, "OK");
+
+    // Watch a byte of the stored word, which is reported at its own address.
+    assert_eq!(gdb.request(&format!("Z2,{:x},1", heap + 2))?, "OK");
+    assert_eq!(gdb.request("c")?, format!("T05watch:{:x};", heap + 2));
+    assert_eq!(gdb.request(&format!("m{heap:x},4"))?, hex_word(3));
+    assert_eq!(gdb.request(&format!("m{:x},2", heap + 1))?, "0000");
+
+    // A long read is cut to fit in a packet.
+    assert_eq!(gdb.request(&format!("m{heap:x},ffffffff"))?.len(), 0x4000);
+
+    // A packet with a wrong checksum is refused, and gdb sends it again.
+    write!(gdb.writer, "$g#00")?;
+    let mut nack = [0];
+    gdb.reader.read_exact(&mut nack)?;
+    assert_eq!(nack[0], b'-');
+    assert_eq!(gdb.request("p1")?, hex_word(3));
+
+    assert_eq!(gdb.request("s")?, "S05");
+    assert_eq!(gdb.request("c")?, "W07");
+
+    gdb.send("k")?;
+    server.join().unwrap()?;
+    Ok(())
+}
+
+/// Store 3 on the heap, then halt with exit code 7.
+fn program_store_and_halt() -> Vec<Instruction> {
+    vec![
+        encode_rv32u(InsnKind::ADDI, 0, 0, 2, CENO_PLATFORM.heap.start),
+        encode_rv32(InsnKind::ADDI, 0, 0, 1, 3),
+        encode_rv32(InsnKind::SW, 2, 1, 0, 0),
+        encode_rv32u(
+            InsnKind::ADDI,
+            0,
+            0,
+            Platform::reg_ecall() as u32,
+            Platform::ecall_halt(),
+        ),
+        encode_rv32(InsnKind::ADDI, 0, 0, Platform::reg_arg0() as u32, 7),
+        encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+    ]
+}
+
+/// The side of `gdb` in the remote serial protocol.
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(addr: impl std::net::ToSocketAddrs) -> Result<Self> {
+        let writer = TcpStream::connect(addr)?;
+        writer.set_nodelay(true)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self { reader, writer })
+    }
+
+    fn send(&mut self, data: &str) -> Result<()> {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.writer, "${data}#{checksum:02x}")?;
+        Ok(())
+    }
+
+    fn request(&mut self, data: &str) -> Result<String> {
+        self.send(data)?;
+        let mut ack = [0];
+        self.reader.read_exact(&mut ack)?;
+        assert_eq!(ack[0], b'+');
+
+        let mut reply = vec![];
+        self.reader.read_until(b'#', &mut reply)?;
+        let mut checksum = [0; 2];
+        self.reader.read_exact(&mut checksum)?;
+        self.writer.write_all(b"+")?;
+
+        assert_eq!(reply.first(), Some(&b'$'));
+        assert_eq!(reply.pop(), Some(b'#'));
+        Ok(String::from_utf8(reply[1..].to_vec())?)
+    }
+}
+
+fn hex_word(word: u32) -> String {
+    word.to_le_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .join("")
+}
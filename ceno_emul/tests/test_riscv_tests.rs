@@ -0,0 +1,104 @@
+//! The rv32ui and rv32um conformance tests of riscv-tests, built for Ceno in `tests/riscv-tests`.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use ceno_emul::{ByteAddr, CENO_PLATFORM, EmuContext, Platform, Program, VMState};
+use itertools::Itertools;
+
+/// The longest test runs a few thousand instructions.
+const MAX_STEPS: usize = 1 << 16;
+
+#[test]
+fn test_riscv_tests() -> Result<()> {
+    let dir = bin_dir();
+    let paths = fs::read_dir(&dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .sorted()
+        .collect_vec();
+    assert!(!paths.is_empty(), "no tests in {dir:?}");
+
+    let mut failures = vec![];
+    for path in &paths {
+        let name = path.file_name().unwrap().to_string_lossy();
+        match run_test(&fs::read(path)?) {
+            Ok(()) => println!("{name} ... pass"),
+            Err(err) => {
+                println!("{name} ... FAIL: {err:#}");
+                failures.push(name);
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} tests failed: {failures:?}",
+        failures.len(),
+        paths.len()
+    );
+    Ok(())
+}
+
+/// Run a test until it halts, and check the result that it wrote to `tohost`.
+///
+/// As in riscv-tests, `tohost` is 1 for a pass, or `(TESTNUM << 1) | 1` for the failed case
+/// `TESTNUM`. The test halts with the exit code 0, or `TESTNUM`.
+fn run_test(elf: &[u8]) -> Result<()> {
+    let mut vm = VMState::new_from_elf(CENO_PLATFORM, elf)?;
+    let tohost = vm.program().addr_of("tohost").context("no symbol tohost")?;
+    let run = vm
+        .iter_until_halt()
+        .take(MAX_STEPS)
+        .try_for_each(|step| step.map(|_| ()));
+    if let Err(err) = run {
+        bail!("{}", err.report(vm.program()));
+    }
+    if !vm.halted() {
+        bail!("did not halt after {MAX_STEPS} steps");
+    }
+
+    let exit_code = vm.peek_register(Platform::reg_arg0());
+    match vm.peek_memory(ByteAddr(tohost).waddr()) {
+        1 if exit_code == 0 => Ok(()),
+        1 => bail!("passed, but halted with exit code {exit_code}"),
+        result if result & 1 == 1 => bail!("failed case {}", result >> 1),
+        result => bail!("halted with exit code {exit_code} and tohost = {result}"),
+    }
+}
+
+#[test]
+fn test_program_metadata() -> Result<()> {
+    let program = Program::load_elf(&fs::read(bin_dir().join("rv32ui-ceno-jal"))?, u32::MAX)?;
+
+    let names = program
+        .sections
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect_vec();
+    assert_eq!(names, [".text", ".data"]);
+    let tohost = program.addr_of("tohost").unwrap();
+    assert_eq!(program.section_at(tohost).unwrap().name, ".data");
+    assert_eq!(program.section_at(program.entry).unwrap().name, ".text");
+    assert_eq!(program.addr_of("missing"), None);
+
+    // A local label, in the function `_start`, at the line of `nop` after `jal x4, target_2`.
+    let pc = program.addr_of("linkaddr_2").unwrap();
+    let (start, symbol) = program.symbol_at(pc).unwrap();
+    assert_eq!((start, symbol.name.as_str()), (program.entry, "_start"));
+    let source = program.source_at(pc).unwrap();
+    assert!(source.file.ends_with("isa/rv32ui/jal.S"), "{source}");
+    assert_eq!(source.line, 26);
+    assert_eq!(
+        program.describe(pc).unwrap(),
+        format!("_start+{:#x} at {source}", pc - start)
+    );
+    assert_eq!(program.source_at(tohost), None);
+    Ok(())
+}
+
+fn bin_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/riscv-tests/bin")
+}
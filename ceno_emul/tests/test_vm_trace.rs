@@ -6,8 +6,9 @@ use std::{
 };
 
 use ceno_emul::{
-    CENO_PLATFORM, Cycle, EmuContext, InsnKind, Instruction, Platform, Program, StepRecord, Tracer,
-    VMState, WordAddr, encode_rv32,
+    ByteAddr, CENO_PLATFORM, Cycle, EmuContext, EmuError, FastVM, InsnKind, Instruction, Platform,
+    Profiler, Program, Segment, StepRecord, Symbol, Tracer, TrapCause, VMSnapshot, VMState,
+    WordAddr, encode_rv32, encode_rv32u,
 };
 
 #[test]
@@ -38,6 +39,78 @@ fn test_vm_trace() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_fast_vm() -> Result<()> {
+    let program = Arc::new(Program::new(
+        CENO_PLATFORM.pc_base(),
+        CENO_PLATFORM.pc_base(),
+        program_fibonacci_20(),
+        Default::default(),
+    ));
+    let mut traced = VMState::new(CENO_PLATFORM, program.clone());
+    let steps = run(&mut traced)?;
+
+    let mut fast = FastVM::new(CENO_PLATFORM, program.clone());
+    assert_eq!(fast.run(10)?, 10);
+    assert_eq!(fast.run(usize::MAX)?, steps.len() - 10);
+    assert_eq!(fast.exit_code(), Some(0));
+    // The fast VM does not track accesses.
+    assert_eq!(fast.snapshot(), VMSnapshot {
+        latest_accesses: HashMap::new(),
+        ..traced.snapshot()
+    });
+
+    // Both stop at the same error, here an unknown ecall.
+    let mut traced = VMState::new(CENO_PLATFORM, program.clone());
+    traced.init_register_unsafe(Platform::reg_ecall(), 0x1234);
+    let mut fast = FastVM::new(CENO_PLATFORM, program);
+    fast.init_register_unsafe(Platform::reg_ecall(), 0x1234);
+    let err = run(&mut traced).unwrap_err();
+    assert!(matches!(err, EmuError::Trap {
+        cause: TrapCause::EcallError,
+        ..
+    }));
+    assert_eq!(fast.run(usize::MAX), Err(err));
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_restore() -> Result<()> {
+    let program = Arc::new(Program::new(
+        CENO_PLATFORM.pc_base(),
+        CENO_PLATFORM.pc_base(),
+        program_fibonacci_20(),
+        Default::default(),
+    ));
+    let mut traced = VMState::new(CENO_PLATFORM, program.clone());
+    let steps = run(&mut traced)?;
+
+    // Save the state after 10 steps.
+    let mut vm = VMState::new(CENO_PLATFORM, program.clone());
+    vm.iter_until_halt()
+        .take(10)
+        .collect::<Result<Vec<_>, _>>()?;
+    let snapshot = vm.snapshot();
+    let mut saved = vec![];
+    snapshot.write_to(&mut saved)?;
+    assert_eq!(VMSnapshot::read_from(saved.as_slice())?, snapshot);
+
+    // Resume in a new VM, with the same steps as the uninterrupted run.
+    let mut resumed = VMState::new(CENO_PLATFORM, program);
+    resumed.restore(VMSnapshot::read_from(saved.as_slice())?);
+    assert_eq!(run(&mut resumed)?, steps[10..]);
+    assert_eq!(resumed.snapshot(), traced.snapshot());
+
+    // Once halted, a restored VM stays halted.
+    let mut halted = VMState::new(CENO_PLATFORM, resumed.program().clone().into());
+    halted.restore(resumed.snapshot());
+    assert!(halted.halted());
+
+    saved[0] ^= 1;
+    assert!(VMSnapshot::read_from(saved.as_slice()).is_err());
+    Ok(())
+}
+
 #[test]
 fn test_empty_program() -> Result<()> {
     let empty_program = Program::new(
@@ -48,11 +121,148 @@ fn test_empty_program() -> Result<()> {
     );
     let mut ctx = VMState::new(CENO_PLATFORM, Arc::new(empty_program));
     let res = run(&mut ctx);
-    assert!(matches!(res, Err(e) if e.to_string().contains("InstructionAccessFault")),);
+    assert!(matches!(
+        res,
+        Err(EmuError::Trap {
+            cause: TrapCause::InstructionAccessFault,
+            raw: None,
+            ..
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_store_access_fault() {
+    let pc = CENO_PLATFORM.pc_base();
+    let program = Program::new(
+        pc,
+        pc,
+        vec![
+            encode_rv32(InsnKind::ADDI, 0, 0, 1, 8),
+            encode_rv32(InsnKind::SW, 1, 0, 0, 0),
+        ],
+        Default::default(),
+    );
+    let mut ctx = VMState::new(CENO_PLATFORM, Arc::new(program));
+    let err = run(&mut ctx).unwrap_err();
+    assert_eq!(err, EmuError::Trap {
+        cause: TrapCause::StoreAccessFault(ByteAddr(8)),
+        pc: ByteAddr(pc + 4),
+        raw: Some(0),
+        cycle: 2 * Tracer::SUBCYCLES_PER_INSN,
+        access: Some((ByteAddr(8), Segment::Unmapped)),
+    });
+
+    let report = err.report(ctx.program());
+    assert!(report.starts_with("Trap StoreAccessFault"));
+    assert!(report.contains(&format!("=> {:?}: sw zero, 0(ra)", ByteAddr(pc + 4))));
+}
+
+#[test]
+fn test_stuck_in_loop() {
+    let pc = CENO_PLATFORM.pc_base();
+    let program = Program::new(
+        pc,
+        pc,
+        vec![encode_rv32(InsnKind::JAL, 0, 0, 0, 0)],
+        Default::default(),
+    );
+    let mut ctx = VMState::new(CENO_PLATFORM, Arc::new(program));
+    assert_eq!(run(&mut ctx).unwrap_err(), EmuError::StuckInLoop {
+        pc: ByteAddr(pc),
+        cycle: Tracer::SUBCYCLES_PER_INSN,
+    });
+}
+
+#[test]
+fn test_vm_segments() -> Result<()> {
+    let program = Arc::new(Program::new(
+        CENO_PLATFORM.pc_base(),
+        CENO_PLATFORM.pc_base(),
+        program_fibonacci_20(),
+        Default::default(),
+    ));
+    let expected = run(&mut VMState::new(CENO_PLATFORM, program.clone()))?;
+
+    let mut ctx = VMState::new(CENO_PLATFORM, program);
+    let mut steps = vec![];
+    while !ctx.halted() {
+        let start = ctx.start_segment();
+        let segment = ctx
+            .iter_until_halt()
+            .take(10)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(start.pc, segment[0].pc().before.0);
+        assert_eq!(start.cycle, segment[0].cycle());
+
+        // The first access to x1 in a later segment refers to the initial state of the segment.
+        if !steps.is_empty() {
+            let x1 = segment
+                .iter()
+                .find_map(|step| step.rs1().filter(|op| op.register_index() == 1))
+                .unwrap();
+            assert_eq!(x1.previous_cycle, 0);
+            assert_eq!(x1.value, start.registers[1]);
+        }
+        steps.extend(segment);
+    }
+
+    let pcs = |steps: &[StepRecord]| {
+        steps
+            .iter()
+            .map(|step| step.pc().before)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(pcs(&steps), pcs(&expected));
+
+    let (x1, x2, x3) = expected_fibonacci_20();
+    assert_eq!(ctx.snapshot().registers[1..4], [x1, x2, x3]);
+    Ok(())
+}
+
+#[test]
+fn test_profiler() -> Result<()> {
+    let pc = CENO_PLATFORM.pc_base();
+    let mut program = Program::new(
+        pc,
+        pc,
+        vec![
+            // main: call f, then halt.
+            encode_rv32(InsnKind::JAL, 0, 0, 1, 12),
+            encode_rv32u(
+                InsnKind::ADDI,
+                0,
+                0,
+                Platform::reg_ecall() as u32,
+                Platform::ecall_halt(),
+            ),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            // f: return.
+            encode_rv32(InsnKind::ADDI, 0, 0, 2, 1),
+            encode_rv32(InsnKind::JALR, 1, 0, 0, 0),
+        ],
+        Default::default(),
+    );
+    for (addr, name, size) in [(pc, "main", 12), (pc + 12, "f", 8)] {
+        program.symbols.insert(addr, Symbol {
+            name: name.to_string(),
+            size,
+        });
+    }
+    let mut ctx = VMState::new(CENO_PLATFORM, Arc::new(program.clone()));
+
+    let mut profiler = Profiler::new(&program);
+    for step in run(&mut ctx)? {
+        profiler.observe(&step);
+    }
+    let mut folded = vec![];
+    profiler.write_folded(&mut folded)?;
+    assert_eq!(String::from_utf8(folded)?, "main 3\nmain;f 2\n");
     Ok(())
 }
 
-fn run(state: &mut VMState) -> Result<Vec<StepRecord>> {
+fn run(state: &mut VMState) -> Result<Vec<StepRecord>, EmuError> {
     state.iter_until_halt().collect()
 }
 
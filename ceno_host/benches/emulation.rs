@@ -0,0 +1,48 @@
+use ceno_emul::{CENO_PLATFORM, FastVM, VMState};
+use criterion::*;
+
+criterion_group!(emulation_group, emulation);
+criterion_main!(emulation_group);
+
+/// The examples that run without hints.
+const EXAMPLES: [(&str, &[u8]); 6] = [
+    ("ceno_rt_mini", ceno_examples::ceno_rt_mini),
+    ("ceno_rt_mem", ceno_examples::ceno_rt_mem),
+    ("ceno_rt_alloc", ceno_examples::ceno_rt_alloc),
+    ("ceno_rt_keccak", ceno_examples::ceno_rt_keccak),
+    ("ceno_rt_sha256", ceno_examples::ceno_rt_sha256),
+    ("ceno_rt_ecdsa", ceno_examples::ceno_rt_ecdsa),
+];
+
+/// Compare the traced emulator with the execute-only one.
+fn emulation(c: &mut Criterion) {
+    for (name, elf) in EXAMPLES {
+        let mut group = c.benchmark_group(format!("emulate_{name}"));
+
+        group.bench_function("traced", |b| {
+            b.iter_batched(
+                || VMState::new_from_elf(CENO_PLATFORM, elf).unwrap(),
+                |mut vm| {
+                    for step in vm.iter_until_halt() {
+                        black_box(step.unwrap());
+                    }
+                    vm
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.bench_function("fast", |b| {
+            b.iter_batched(
+                || FastVM::new_from_elf(CENO_PLATFORM, elf).unwrap(),
+                |mut vm| {
+                    black_box(vm.run(usize::MAX).unwrap());
+                    vm
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.finish();
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::{Result, anyhow, ensure};
+use ceno_emul::{
+    CENO_PLATFORM, EmuContext, IterAddresses, Platform, Program, WORD_SIZE,
+    host_utils::{
+        GuestEnv, Messages, load_env, read_all_messages, read_panic_message, read_public_output,
+    },
+};
+use ceno_zkvm::{
+    e2e::{E2EProvingSetup, Preset, guest_vm, setup_platform},
+    scheme::{ZKVMProof, verifier::ZKVMVerifier},
+    structs::ZKVMVerifyingKey,
+};
+use goldilocks::GoldilocksExt2;
+use mpcs::{Basefold, BasefoldRSParams};
+use transcript::BasicTranscript as Transcript;
+
+use crate::CenoStdin;
+
+/// The extension field of the proofs of [`CenoClient`].
+pub type CenoField = GoldilocksExt2;
+/// The polynomial commitment scheme of the proofs of [`CenoClient`].
+pub type CenoPcs = Basefold<GoldilocksExt2, BasefoldRSParams>;
+
+pub type CenoVerifyingKey = ZKVMVerifyingKey<CenoField, CenoPcs>;
+pub type CenoProof = ZKVMProof<CenoField, CenoPcs>;
+
+/// The circuits and the proving key of a guest program, made by [`CenoClient::setup`].
+///
+/// The public input is part of the initial memory of the program, so it is fixed by the key.
+pub struct CenoProvingKey {
+    setup: E2EProvingSetup<CenoField, CenoPcs>,
+    public_input: Vec<u32>,
+}
+
+impl CenoProvingKey {
+    pub fn platform(&self) -> &Platform {
+        self.setup.platform()
+    }
+}
+
+/// The outcome of running a guest with [`CenoClient::execute`], without proving it.
+#[derive(Clone, Debug)]
+pub struct ExecutionReport {
+    /// The exit code of the guest, or `None` if it did not halt within the step limit.
+    pub exit_code: Option<u32>,
+    pub steps: usize,
+    /// What the guest printed with `ceno_rt::println!` and to stdout and stderr.
+    pub messages: Messages,
+    /// The messages the guest revealed with `ceno_rt::commit`, in order.
+    pub public_output: Vec<Vec<u8>>,
+    /// The panic message of the guest, if it panicked.
+    pub panic_message: Option<String>,
+}
+
+/// A proof of an execution of a guest, with the public values it is bound to.
+#[derive(Clone)]
+pub struct ProofWithPublicValues {
+    pub proof: CenoProof,
+    /// The public input of the guest, in the words it was given to the guest.
+    pub public_input: Vec<u32>,
+    /// The exit code of the guest, or `None` if it did not halt within the step limit.
+    pub exit_code: Option<u32>,
+    /// The messages the guest revealed with `ceno_rt::commit`, in order.
+    pub public_output: Vec<Vec<u8>>,
+    /// The panic message of the guest, if it panicked. It is read from the guest memory by the
+    /// prover and is not bound to the proof.
+    pub panic_message: Option<String>,
+}
+
+/// Execute, prove and verify guest programs on the Ceno platform, with Goldilocks and Basefold.
+///
+/// The sizes are in bytes, as for the `e2e` binary of `ceno_zkvm`, and have the same defaults.
+/// The `env` is part of the program, so keys are specific to it.
+#[derive(Clone, Debug)]
+pub struct CenoClient {
+    pub stack_size: u32,
+    pub heap_size: u32,
+    pub public_output_size: u32,
+    pub max_steps: usize,
+    pub env: GuestEnv,
+}
+
+impl Default for CenoClient {
+    fn default() -> Self {
+        Self {
+            stack_size: 32 << 10,
+            heap_size: 2 << 20,
+            public_output_size: 256,
+            max_steps: usize::MAX,
+            env: GuestEnv::default(),
+        }
+    }
+}
+
+impl CenoClient {
+    /// Build the circuits of the guest `elf` with its `public_input`, and its proving and
+    /// verifying keys.
+    pub fn setup(
+        &self,
+        elf: &[u8],
+        public_input: &CenoStdin,
+    ) -> Result<(CenoProvingKey, CenoVerifyingKey)> {
+        let public_input: Vec<u32> = public_input.into();
+        let (program, platform) = self.load(elf, &public_input)?;
+        let setup = E2EProvingSetup::new(program, platform, &public_input);
+        let vk = setup.get_vk();
+        Ok((
+            CenoProvingKey {
+                setup,
+                public_input,
+            },
+            vk,
+        ))
+    }
+
+    /// Run the guest `elf` on the hints in `stdin` and its `public_input`, as it would be proven.
+    pub fn execute(
+        &self,
+        elf: &[u8],
+        stdin: &CenoStdin,
+        public_input: &CenoStdin,
+    ) -> Result<ExecutionReport> {
+        let public_input: Vec<u32> = public_input.into();
+        let (program, platform) = self.load(elf, &public_input)?;
+        let hints = hints_words(&platform, stdin)?;
+
+        let mut vm = guest_vm(&program, &platform, &hints, &public_input);
+        let mut steps = 0;
+        for step in vm.iter_until_halt().take(self.max_steps) {
+            step.map_err(|err| anyhow!("guest execution failed: {}", err.report(&program)))?;
+            steps += 1;
+        }
+        Ok(ExecutionReport {
+            exit_code: vm.halted().then(|| vm.peek_register(Platform::reg_arg0())),
+            steps,
+            messages: read_all_messages(&vm),
+            public_output: read_public_output(&vm, &platform),
+            panic_message: read_panic_message(&vm),
+        })
+    }
+
+    /// Prove the execution of the guest of `pk` on the hints in `stdin`, and the public input
+    /// of `pk`.
+    pub fn prove(&self, pk: &CenoProvingKey, stdin: &CenoStdin) -> Result<ProofWithPublicValues> {
+        let hints = hints_words(pk.platform(), stdin)?;
+        let (proof, exit) = pk.setup.prove(&hints, self.max_steps);
+        Ok(ProofWithPublicValues {
+            public_output: proof.public_output(),
+            proof,
+            public_input: pk.public_input.clone(),
+            exit_code: exit.exit_code,
+            panic_message: exit.panic_message,
+        })
+    }
+
+    /// Verify `proof` against `vk`, along with the public values that come with it.
+    pub fn verify(&self, vk: &CenoVerifyingKey, proof: &ProofWithPublicValues) -> Result<()> {
+        ensure!(
+            proof.public_output == proof.proof.public_output(),
+            "the public output does not match the proof"
+        );
+        if let Some(exit_code) = proof.exit_code {
+            ensure!(
+                exit_code == proof.proof.exit_code(),
+                "the exit code does not match the proof"
+            );
+        }
+        let verifier = ZKVMVerifier::new(vk.clone());
+        let transcript = Transcript::new(b"riscv");
+        let valid = verifier
+            .verify_proof_halt_with_public_io(
+                proof.proof.clone(),
+                transcript,
+                &proof.public_input,
+                proof.exit_code.is_some(),
+            )
+            .map_err(|err| anyhow!("invalid proof: {err:?}"))?;
+        ensure!(valid, "invalid proof");
+        Ok(())
+    }
+
+    /// Load the guest `elf` with the env of the client, and lay out its platform to fit
+    /// `public_input`.
+    fn load(&self, elf: &[u8], public_input: &[u32]) -> Result<(Program, Platform)> {
+        let mut program = Program::load_elf(elf, u32::MAX)?;
+        load_env(&mut program, &CENO_PLATFORM.env, &self.env);
+        let platform = setup_platform(
+            Preset::Ceno,
+            &program,
+            self.stack_size.next_multiple_of(WORD_SIZE as u32),
+            self.heap_size.next_multiple_of(WORD_SIZE as u32),
+            (public_input.len() * WORD_SIZE) as u32,
+            self.public_output_size.next_multiple_of(WORD_SIZE as u32),
+        );
+        Ok((program, platform))
+    }
+}
+
+fn hints_words(platform: &Platform, stdin: &CenoStdin) -> Result<Vec<u32>> {
+    let hints: Vec<u32> = stdin.into();
+    ensure!(
+        hints.len() <= platform.hints.iter_addresses().len(),
+        "hints must fit in {} bytes",
+        platform.hints.len()
+    );
+    Ok(hints)
+}
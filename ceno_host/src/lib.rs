@@ -1,16 +1,30 @@
 use std::{
+    fmt,
     iter::{repeat, zip},
     sync::Arc,
 };
 
 use anyhow::Result;
-use ceno_emul::{IterAddresses, Platform, Program, VMState, host_utils::read_all_messages};
+use ceno_emul::{
+    FastVM, IterAddresses, Platform, Program,
+    host_utils::{
+        GuestEnv, Messages, load_env, read_all_messages, read_panic_message, read_public_output,
+    },
+};
 use itertools::{Itertools, chain};
 use rkyv::{
     Serialize, api::high::HighSerializer, rancor::Error, ser::allocator::ArenaHandle, to_bytes,
     util::AlignedVec,
 };
 
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "client")]
+pub use client::{
+    CenoClient, CenoField, CenoPcs, CenoProof, CenoProvingKey, CenoVerifyingKey, ExecutionReport,
+    ProofWithPublicValues,
+};
+
 // We want to get access to the default value of `AlignedVec::ALIGNMENT`, and using it directly like this
 //   pub const RKVY_ALIGNMENT: usize = rkyv::util::AlignedVec::ALIGNMENT;
 // doesn't work:
@@ -133,26 +147,107 @@ impl CenoStdin {
     }
 }
 
-pub fn run(platform: Platform, elf: &[u8], hints: &CenoStdin) -> Vec<Vec<u8>> {
-    let program = Program::load_elf(elf, u32::MAX).unwrap();
+/// A guest which exited with a code other than [`Platform::code_success`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuestFailure {
+    pub exit_code: u32,
+    /// The panic message of the guest, if it panicked.
+    pub message: Option<String>,
+}
+
+impl fmt::Display for GuestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.message, self.exit_code) {
+            (Some(message), _) => write!(f, "guest panicked: {message}"),
+            (None, code) if code == Platform::code_out_of_memory() => {
+                write!(f, "guest ran out of heap memory")
+            }
+            (None, code) => write!(f, "guest exited with code {code}"),
+        }
+    }
+}
+
+impl std::error::Error for GuestFailure {}
+
+/// Run the guest, and return what it printed with `ceno_rt::println!` and to stdout and stderr.
+///
+/// Return a [`GuestFailure`] instead if the guest panicked or exited with an error code.
+pub fn run(platform: Platform, elf: &[u8], hints: &CenoStdin) -> Result<Messages, GuestFailure> {
+    run_with_env(platform, elf, hints, &GuestEnv::default())
+}
+
+/// Run the guest with program arguments and environment variables, read with `ceno_rt::args` and
+/// `ceno_rt::var`, or with `std::env`.
+pub fn run_with_env(
+    platform: Platform,
+    elf: &[u8],
+    hints: &CenoStdin,
+    env: &GuestEnv,
+) -> Result<Messages, GuestFailure> {
+    let state = execute(platform, elf, hints, &CenoStdin::default(), env)?;
+    Ok(read_all_messages(&state))
+}
+
+/// Run the guest with a public input, read with `ceno_rt::read_public`.
+///
+/// Return its printed messages and the values it revealed with `ceno_rt::commit`, in order.
+pub fn run_with_io(
+    platform: Platform,
+    elf: &[u8],
+    hints: &CenoStdin,
+    public_io: &CenoStdin,
+) -> Result<(Messages, Vec<Vec<u8>>), GuestFailure> {
+    let state = execute(platform, elf, hints, public_io, &GuestEnv::default())?;
+    Ok((
+        read_all_messages(&state),
+        read_public_output(&state, state.platform()),
+    ))
+}
+
+fn execute(
+    platform: Platform,
+    elf: &[u8],
+    hints: &CenoStdin,
+    public_io: &CenoStdin,
+    env: &GuestEnv,
+) -> Result<FastVM, GuestFailure> {
+    let mut program = Program::load_elf(elf, u32::MAX).unwrap();
+    let env = load_env(&mut program, &platform.env, env);
     let platform = Platform {
         prog_data: program.image.keys().copied().collect(),
+        env,
         ..platform
     };
 
     let hints: Vec<u32> = hints.into();
     let hints_range = platform.hints.clone();
+    let public_io: Vec<u32> = public_io.into();
+    let public_io_range = platform.public_io.clone();
+    assert!(
+        public_io.len() <= public_io_range.iter_addresses().len(),
+        "public input must fit in {} bytes",
+        public_io_range.len()
+    );
 
-    let mut state = VMState::new(platform, Arc::new(program));
+    let mut state = FastVM::new(platform, Arc::new(program));
 
     for (addr, value) in zip(hints_range.iter_addresses(), hints) {
         state.init_memory(addr.into(), value);
     }
+    for (addr, value) in zip(public_io_range.iter_addresses(), public_io) {
+        state.init_memory(addr.into(), value);
+    }
 
     let steps = state
-        .iter_until_halt()
-        .collect::<Result<Vec<_>>>()
-        .expect("Failed to run the program");
-    eprintln!("Emulator ran for {} steps.", steps.len());
-    read_all_messages(&state)
+        .run(usize::MAX)
+        .unwrap_or_else(|err| panic!("Failed to run the program: {}", err.report(state.program())));
+    eprintln!("Emulator ran for {steps} steps.");
+    let exit_code = state.exit_code().expect("the guest runs until it halts");
+    if exit_code != Platform::code_success() {
+        return Err(GuestFailure {
+            exit_code,
+            message: read_panic_message(&state),
+        });
+    }
+    Ok(state)
 }
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use ceno_emul::{Platform, Program};
+use ceno_host::{CenoClient, CenoStdin};
+use rkyv::{rancor::Error, util::AlignedVec};
+
+#[test]
+fn test_client_execute() -> Result<()> {
+    let mut client = CenoClient::default();
+    client.env.arg("first").var("MODE", "fast");
+    let report = client.execute(
+        ceno_examples::ceno_rt_env,
+        &CenoStdin::default(),
+        &CenoStdin::default(),
+    )?;
+    assert_eq!(report.exit_code, Some(Platform::code_success()));
+    assert!(report.steps > 0);
+    assert_eq!(
+        String::from_utf8_lossy(&report.messages.stdout),
+        "arg: first\nMODE: Some(\"fast\")\n"
+    );
+
+    let report = client.execute(
+        ceno_examples::ceno_rt_panic,
+        &CenoStdin::default(),
+        &CenoStdin::default(),
+    )?;
+    assert_eq!(report.exit_code, Some(Platform::code_panic()));
+    assert!(
+        report
+            .panic_message
+            .is_some_and(|message| message.contains("This is a panic message!"))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_client_prove_and_verify() -> Result<()> {
+    let client = CenoClient::default();
+    let (pk, vk) = client.setup(
+        ceno_examples::ceno_rt_commit,
+        CenoStdin::default().write(&1997_u32)?,
+    )?;
+    let proof = client.prove(&pk, CenoStdin::default().write(&1999_u32)?)?;
+    assert_eq!(proof.exit_code, Some(Platform::code_success()));
+    assert_eq!(proof.panic_message, None);
+    client.verify(&vk, &proof)?;
+    assert_eq!(committed_u32(&proof.public_output[1])?, 3992003);
+
+    // The public values must be those the proof is bound to.
+    let mut forged = proof.clone();
+    forged.public_output.push(b"forged".to_vec());
+    assert!(client.verify(&vk, &forged).is_err());
+
+    let mut forged = proof.clone();
+    let mut public_input = CenoStdin::default();
+    public_input.write(&1_u32)?;
+    forged.public_input = (&public_input).into();
+    assert!(client.verify(&vk, &forged).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_ceno_rt_compressed() -> Result<()> {
+    let program = Program::load_elf(ceno_examples::ceno_rt_compressed, u32::MAX)?;
+    assert!(program.instructions.iter().any(|insn| insn.compressed));
+
+    // Compressed instructions are proven as the instructions they expand to.
+    let client = CenoClient::default();
+    let (pk, vk) = client.setup(ceno_examples::ceno_rt_compressed, &CenoStdin::default())?;
+    let proof = client.prove(&pk, CenoStdin::default().write(&20_u32)?)?;
+    assert_eq!(proof.exit_code, Some(Platform::code_success()));
+    client.verify(&vk, &proof)?;
+    assert_eq!(committed_u32(&proof.public_output[0])?, 6765);
+    Ok(())
+}
+
+/// Deserialize a `u32` committed by the guest.
+fn committed_u32(message: &[u8]) -> Result<u32> {
+    let mut bytes = AlignedVec::<16>::new();
+    bytes.extend_from_slice(message);
+    Ok(rkyv::from_bytes::<u32, Error>(&bytes)?)
+}
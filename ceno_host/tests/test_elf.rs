@@ -2,12 +2,14 @@ use std::{collections::BTreeSet, iter::from_fn, sync::Arc};
 
 use anyhow::Result;
 use ceno_emul::{
-    CENO_PLATFORM, EmuContext, InsnKind, Platform, Program, StepRecord, VMState, WORD_SIZE,
-    host_utils::read_all_messages,
+    CENO_PLATFORM, EC_POINT_WORDS, EmuContext, EmuError, Platform, Program, SHA_EXTEND_WORDS,
+    StepRecord, VMState, WORD_SIZE,
+    host_utils::{GuestEnv, Messages, read_all_messages},
 };
 use ceno_host::CenoStdin;
 use itertools::{Itertools, enumerate, izip};
 use rand::{Rng, thread_rng};
+use rkyv::{rancor::Error, util::AlignedVec};
 use tiny_keccak::keccakf;
 
 #[test]
@@ -23,25 +25,36 @@ fn test_ceno_rt_mini() -> Result<()> {
     Ok(())
 }
 
-// TODO(Matthias): We are using Rust's standard library's default panic handler now,
-// and they are indicated with a different instruction than our ecall.  (But still work,
-// as you can tell, because this tests panics.)  However, we should adapt this test
-// to properly check for the conventional Rust panic.
 #[test]
-#[should_panic(expected = "Trap IllegalInstruction")]
 fn test_ceno_rt_panic() {
-    let program_elf = ceno_examples::ceno_rt_panic;
-    let program = Program::load_elf(program_elf, u32::MAX).unwrap();
-    let platform = Platform {
-        prog_data: program.image.keys().copied().collect(),
-        ..CENO_PLATFORM
-    };
-    let mut state = VMState::new(platform, Arc::new(program));
-    let steps = run(&mut state).unwrap();
-    let last = steps.last().unwrap();
-    assert_eq!(last.insn().kind, InsnKind::ECALL);
-    assert_eq!(last.rs1().unwrap().value, Platform::ecall_halt());
-    assert_eq!(last.rs2().unwrap().value, 1); // panic / halt(1)
+    let failure = ceno_host::run(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_panic,
+        &CenoStdin::default(),
+    )
+    .expect_err("the guest should panic");
+    assert_eq!(failure.exit_code, Platform::code_panic());
+    let message = failure.message.expect("the panic should be reported");
+    assert!(message.contains("This is a panic message!"), "{message}");
+    assert!(message.contains("ceno_rt_panic.rs"), "{message}");
+}
+
+#[test]
+fn test_ceno_rt_segments() -> Result<()> {
+    // The linker script of ceno_rt must lay out the segments as the platform does.
+    let program = Program::load_elf(ceno_examples::ceno_rt_mini, u32::MAX)?;
+    for (symbol, segment) in [
+        ("_public_io_start", &CENO_PLATFORM.public_io),
+        ("_public_output_start", &CENO_PLATFORM.public_output),
+        ("_env_start", &CENO_PLATFORM.env),
+    ] {
+        assert_eq!(program.addr_of(symbol), Some(segment.start), "{symbol}");
+    }
+    assert_eq!(
+        program.addr_of("_public_output_end"),
+        Some(CENO_PLATFORM.public_output.end)
+    );
+    Ok(())
 }
 
 #[test]
@@ -90,6 +103,19 @@ fn test_ceno_rt_alloc() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ceno_rt_free_list() {
+    // The guest checks that freed blocks are reused, then allocates until the heap runs out.
+    let failure = ceno_host::run(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_free_list,
+        &CenoStdin::default(),
+    )
+    .expect_err("the guest should run out of heap memory");
+    assert_eq!(failure.exit_code, Platform::code_out_of_memory());
+    assert_eq!(failure.message, None);
+}
+
 #[test]
 fn test_ceno_rt_io() -> Result<()> {
     let program_elf = ceno_examples::ceno_rt_io;
@@ -110,6 +136,42 @@ fn test_ceno_rt_io() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ceno_rt_std_io() -> Result<()> {
+    let messages = ceno_host::run(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_std_io,
+        &CenoStdin::default(),
+    )?;
+    assert!(messages.info.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&messages.stdout),
+        "Hello from std!\none, two\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&messages.stderr),
+        "Something went wrong.\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_ceno_rt_env() -> Result<()> {
+    let mut env = GuestEnv::default();
+    env.arg("first").arg("second").var("MODE", "fast");
+    let messages = ceno_host::run_with_env(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_env,
+        &CenoStdin::default(),
+        &env,
+    )?;
+    assert_eq!(
+        String::from_utf8_lossy(&messages.stdout),
+        "arg: first\narg: second\nMODE: Some(\"fast\")\n"
+    );
+    Ok(())
+}
+
 #[test]
 fn test_hints() -> Result<()> {
     let all_messages = messages_to_strings(&ceno_host::run(
@@ -120,7 +182,7 @@ fn test_hints() -> Result<()> {
             .write(&"This is my hint string.".to_string())?
             .write(&1997_u32)?
             .write(&1999_u32)?,
-    ));
+    )?);
     for (i, msg) in enumerate(&all_messages) {
         println!("{i}: {msg}");
     }
@@ -128,6 +190,28 @@ fn test_hints() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ceno_rt_commit() -> Result<()> {
+    let (_, public_output) = ceno_host::run_with_io(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_commit,
+        CenoStdin::default().write(&1999_u32)?,
+        CenoStdin::default().write(&1997_u32)?,
+    )?;
+    assert_eq!(public_output.len(), 2);
+
+    let committed = |msg: &[u8]| {
+        let mut bytes = AlignedVec::<16>::new();
+        bytes.extend_from_slice(msg);
+        bytes
+    };
+    let inputs = rkyv::from_bytes::<[u32; 2], Error>(&committed(&public_output[0]))?;
+    let product = rkyv::from_bytes::<u32, Error>(&committed(&public_output[1]))?;
+    assert_eq!(inputs, [1997, 1999]);
+    assert_eq!(product, 3992003);
+    Ok(())
+}
+
 #[test]
 fn test_bubble_sorting() -> Result<()> {
     let mut rng = thread_rng();
@@ -136,7 +220,7 @@ fn test_bubble_sorting() -> Result<()> {
         ceno_examples::quadratic_sorting,
         // Provide some random numbers to sort.
         CenoStdin::default().write(&(0..1_000).map(|_| rng.gen::<u32>()).collect::<Vec<_>>())?,
-    ));
+    )?);
     for msg in &all_messages {
         print!("{msg}");
     }
@@ -150,7 +234,7 @@ fn test_sorting() -> Result<()> {
         ceno_examples::sorting,
         // Provide some random numbers to sort.
         CenoStdin::default().write(&(0..1000).map(|_| rng.gen::<u32>()).collect::<Vec<_>>())?,
-    ));
+    )?);
     for (i, msg) in enumerate(&all_messages) {
         println!("{i}: {msg}");
     }
@@ -172,7 +256,7 @@ fn test_median() -> Result<()> {
         CENO_PLATFORM,
         ceno_examples::median,
         &hints,
-    ));
+    )?);
     assert!(!all_messages.is_empty());
     for (i, msg) in enumerate(&all_messages) {
         println!("{i}: {msg}");
@@ -181,19 +265,21 @@ fn test_median() -> Result<()> {
 }
 
 #[test]
-#[should_panic(expected = "Trap IllegalInstruction")]
-fn test_hashing_fail() {
+fn test_hashing_fail() -> Result<()> {
     let mut rng = thread_rng();
 
     let mut nums = (0..1_000).map(|_| rng.gen::<u32>()).collect::<Vec<_>>();
     // Add a duplicate number to make uniqueness check fail:
     nums[211] = nums[907];
 
-    let _ = ceno_host::run(
+    let failure = ceno_host::run(
         CENO_PLATFORM,
         ceno_examples::hashing,
-        CenoStdin::default().write(&nums).unwrap(),
-    );
+        CenoStdin::default().write(&nums)?,
+    )
+    .expect_err("the uniqueness check should fail");
+    assert_eq!(failure.exit_code, Platform::code_panic());
+    Ok(())
 }
 
 #[test]
@@ -213,7 +299,7 @@ fn test_hashing() -> Result<()> {
         CENO_PLATFORM,
         ceno_examples::hashing,
         CenoStdin::default().write(&uniques)?,
-    ));
+    )?);
     assert!(!all_messages.is_empty());
     for (i, msg) in enumerate(&all_messages) {
         println!("{i}: {msg}");
@@ -232,7 +318,7 @@ fn test_ceno_rt_keccak() -> Result<()> {
     const ITERATIONS: usize = 3;
     let keccak_outs = sample_keccak_f(ITERATIONS);
 
-    let all_messages = read_all_messages(&state);
+    let all_messages = read_all_messages(&state).info;
     assert_eq!(all_messages.len(), ITERATIONS);
     for (got, expect) in izip!(&all_messages, &keccak_outs) {
         let got = got
@@ -271,6 +357,88 @@ fn test_ceno_rt_keccak() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ceno_rt_sha256() -> Result<()> {
+    let program_elf = ceno_examples::ceno_rt_sha256;
+    let mut state = VMState::new_from_elf(unsafe_platform(), program_elf)?;
+    let steps = run(&mut state)?;
+
+    // Expect the program to have written the hash of "abc".
+    let expect: [u32; 8] = [
+        0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+        0xf20015ad,
+    ];
+    let all_messages = read_all_messages(&state).info;
+    assert_eq!(all_messages.len(), 1);
+    let got = all_messages[0]
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect_vec();
+    assert_eq!(got, expect);
+
+    // Find the syscall records.
+    let syscalls = steps.iter().filter_map(|step| step.syscall()).collect_vec();
+    assert_eq!(syscalls.len(), 2);
+    let (extend, compress) = (syscalls[0], syscalls[1]);
+
+    // The message schedule is read once and written once, word by word.
+    assert_eq!(extend.reg_ops.len(), 1);
+    assert_eq!(extend.mem_ops.len(), SHA_EXTEND_WORDS);
+    for op in &extend.mem_ops[..16] {
+        assert_eq!(op.value.before, op.value.after);
+    }
+
+    // The compression reads the schedule and updates the state.
+    assert_eq!(compress.reg_ops.len(), 2);
+    assert_eq!(compress.reg_ops[1].register_index(), Platform::reg_arg1());
+    assert_eq!(compress.mem_ops.len(), SHA_EXTEND_WORDS + expect.len());
+    for (w, extended) in izip!(&compress.mem_ops, &extend.mem_ops) {
+        assert_eq!(w.value.before, extended.value.after);
+        assert_eq!(w.value.after, extended.value.after);
+    }
+    let got = compress.mem_ops[SHA_EXTEND_WORDS..]
+        .iter()
+        .map(|op| op.value.after)
+        .collect_vec();
+    assert_eq!(got, expect);
+
+    Ok(())
+}
+
+#[test]
+fn test_ceno_rt_ecdsa() -> Result<()> {
+    let program_elf = ceno_examples::ceno_rt_ecdsa;
+    let mut state = VMState::new_from_elf(unsafe_platform(), program_elf)?;
+    let steps = run(&mut state)?;
+
+    // Expect the program to have recovered `r` of the signature.
+    let expect: [u32; 8] = [
+        0xf29dcbcc, 0x8e77ce52, 0xea01d327, 0x9f846e0e, 0x3590a5ef, 0xc89717e6, 0x7a253c3e,
+        0xd0087ee8,
+    ];
+    let all_messages = read_all_messages(&state).info;
+    assert_eq!(all_messages.len(), 1);
+    let got = all_messages[0]
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect_vec();
+    assert_eq!(got, expect);
+
+    // The point operations update the accumulator, and additions read the other point.
+    let syscalls = steps.iter().filter_map(|step| step.syscall()).collect_vec();
+    assert!(syscalls.len() > 256);
+    for syscall in syscalls {
+        let is_add = syscall.reg_ops.len() == 2;
+        let words = if is_add { 2 } else { 1 } * EC_POINT_WORDS;
+        assert_eq!(syscall.mem_ops.len(), words);
+        for op in &syscall.mem_ops[EC_POINT_WORDS..] {
+            assert_eq!(op.value.before, op.value.after);
+        }
+    }
+
+    Ok(())
+}
+
 fn unsafe_platform() -> Platform {
     let mut platform = CENO_PLATFORM;
     platform.unsafe_ecall_nop = true;
@@ -288,15 +456,16 @@ fn sample_keccak_f(count: usize) -> Vec<Vec<u64>> {
         .collect_vec()
 }
 
-fn messages_to_strings(messages: &[Vec<u8>]) -> Vec<String> {
+fn messages_to_strings(messages: &Messages) -> Vec<String> {
     messages
+        .info
         .iter()
         .map(|msg| String::from_utf8_lossy(msg).to_string())
         .collect()
 }
 
-fn run(state: &mut VMState) -> Result<Vec<StepRecord>> {
-    let steps = state.iter_until_halt().collect::<Result<Vec<_>>>()?;
+fn run(state: &mut VMState) -> Result<Vec<StepRecord>, EmuError> {
+    let steps = state.iter_until_halt().collect::<Result<Vec<_>, _>>()?;
     eprintln!("Emulator ran for {} steps.", steps.len());
     Ok(steps)
 }
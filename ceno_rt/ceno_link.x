@@ -1,8 +1,16 @@
 
 _stack_start = ORIGIN(REGION_STACK) + LENGTH(REGION_STACK);
+/* The size of the heap for the `free-list-allocator`, as the default `--heap-size` of e2e.
+   Override it with `-C link-arg=--defsym=_heap_size=<bytes>`. */
+_heap_size = DEFINED(_heap_size) ? _heap_size : 0x200000;
 _hints_start = ORIGIN(REGION_HINTS);
 _hints_length = LENGTH(REGION_HINTS);
 _lengths_of_hints_start = ORIGIN(REGION_HINTS);
+/* The segments of `ceno_emul::CENO_PLATFORM` that the host fills or reads. */
+_public_io_start = ORIGIN(REGION_PUBLIC_IO);
+_public_output_start = ORIGIN(REGION_PUBLIC_OUTPUT);
+_public_output_end = ORIGIN(REGION_PUBLIC_OUTPUT) + LENGTH(REGION_PUBLIC_OUTPUT);
+_env_start = ORIGIN(REGION_ENV);
 
 SECTIONS
 {
@@ -38,6 +46,10 @@ SECTIONS
     _sheap = .;
   } > RAM
 
+  /* The heap of the platform starts after the program data, before `.bss`.
+     Only guests with the `free-list-allocator` reference it, so only they are bounded by it. */
+  PROVIDE(_eheap = ADDR(.bss) + _heap_size);
+
   /* Define a section for runtime-populated EEPROM-like HINTS data */
   .hints (NOLOAD) : ALIGN(4)
   {
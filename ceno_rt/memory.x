@@ -3,6 +3,9 @@ MEMORY
   RAM : ORIGIN = 0x80000000, LENGTH = 1024M
   ROM : ORIGIN = 0x20000000, LENGTH = 16M
   HINTS: ORIGIN = 0x40000000, LENGTH = 1024M
+  PUBLIC_IO : ORIGIN = 0x30001000, LENGTH = 4K
+  PUBLIC_OUTPUT : ORIGIN = 0x30002000, LENGTH = 4K
+  ENV : ORIGIN = 0x30003000, LENGTH = 0x0FFFD000
 }
 
 REGION_ALIAS("REGION_TEXT", ROM);
@@ -13,3 +16,6 @@ REGION_ALIAS("REGION_HEAP", RAM);
 REGION_ALIAS("REGION_STACK", RAM);
 
 REGION_ALIAS("REGION_HINTS", HINTS);
+REGION_ALIAS("REGION_PUBLIC_IO", PUBLIC_IO);
+REGION_ALIAS("REGION_PUBLIC_OUTPUT", PUBLIC_OUTPUT);
+REGION_ALIAS("REGION_ENV", ENV);
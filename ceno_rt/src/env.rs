@@ -0,0 +1,59 @@
+//! The program arguments and environment variables, placed by the host at `_env_start`.
+//!
+//! See `ceno_emul::host_utils::GuestEnv` for the layout.
+
+use core::{slice, str};
+
+use crate::WORD_SIZE;
+
+extern "C" {
+    /// The address of this variable is the start of the env segment.
+    ///
+    /// It is defined in the linker script.  The value of this variable is undefined.
+    static _env_start: u32;
+}
+
+/// The number of arguments and of variables.
+fn counts() -> (usize, usize) {
+    let header = &raw const _env_start;
+    unsafe { (*header as usize, *header.add(1) as usize) }
+}
+
+/// The arguments, followed by the variables as `NAME=value`.
+fn entries() -> impl Iterator<Item = &'static [u8]> {
+    let (argc, varc) = counts();
+    let mut cursor = unsafe { (&raw const _env_start).add(2) };
+    (0..argc + varc).map(move |_| unsafe {
+        let len = *cursor as usize;
+        let entry = slice::from_raw_parts(cursor.add(1) as *const u8, len);
+        cursor = cursor.add(1 + len.div_ceil(WORD_SIZE));
+        entry
+    })
+}
+
+/// The arguments of the program, given by the host.
+pub fn args() -> impl Iterator<Item = &'static str> {
+    let (argc, _) = counts();
+    entries()
+        .take(argc)
+        .map(|arg| str::from_utf8(arg).expect("arguments are UTF-8"))
+}
+
+/// The value of the environment variable `name`, if given by the host.
+pub fn var(name: &str) -> Option<&'static str> {
+    var_bytes(name.as_bytes()).map(|value| str::from_utf8(value).expect("variables are UTF-8"))
+}
+
+pub(crate) fn var_bytes(name: &[u8]) -> Option<&'static [u8]> {
+    let (argc, _) = counts();
+    entries().skip(argc).find_map(|entry| {
+        entry
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(b"="))
+    })
+}
+
+pub(crate) fn arg_bytes(index: usize) -> Option<&'static [u8]> {
+    let (argc, _) = counts();
+    entries().take(argc).nth(index)
+}
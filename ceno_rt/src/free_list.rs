@@ -0,0 +1,137 @@
+//! A bounded allocator that reuses freed blocks, selected with the `free-list-allocator` feature.
+//!
+//! Allocations are rounded up to a power of two, at least `MIN_BLOCK` bytes. Freed blocks are kept
+//! in a list per size, and reused by later allocations of the same size. New blocks are taken
+//! from the heap between `_sheap` and `_eheap`, and the guest halts with `OUT_OF_MEMORY_EXIT_CODE`
+//! once it is exhausted.
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::UnsafeCell,
+    ptr::{self, null_mut},
+};
+
+use crate::{OUT_OF_MEMORY_EXIT_CODE, halt};
+
+/// The smallest block, which can hold the link of a free list. All blocks are aligned to it.
+const MIN_BLOCK: usize = 8;
+const MIN_CLASS: u32 = MIN_BLOCK.trailing_zeros();
+/// The number of block sizes, from `MIN_BLOCK` to half of the address space.
+const CLASSES: usize = (usize::BITS - 1 - MIN_CLASS) as usize;
+
+/// A freed block, linked to the next one of the same size.
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+struct FreeListAllocator(UnsafeCell<FreeLists>);
+
+// SAFETY: Single threaded.
+unsafe impl Sync for FreeListAllocator {}
+
+struct FreeLists {
+    /// The start of the part of the heap that was never allocated.
+    next_alloc: *mut u8,
+    /// The first freed block of each size.
+    free: [*mut FreeBlock; CLASSES],
+}
+
+/// The size class of the blocks that fit `layout`, or `None` if no block is large enough.
+fn class_of(layout: &Layout) -> Option<usize> {
+    let size = layout.size().max(layout.align()).max(MIN_BLOCK);
+    let class = size.checked_next_power_of_two()?.trailing_zeros() - MIN_CLASS;
+    ((class as usize) < CLASSES).then_some(class as usize)
+}
+
+fn block_size(class: usize) -> usize {
+    MIN_BLOCK << class
+}
+
+impl FreeLists {
+    /// Take a block for `layout`, and whether it was never used before, so it is still zeroed.
+    unsafe fn allocate(&mut self, layout: Layout) -> (*mut u8, bool) {
+        let Some(class) = class_of(&layout) else {
+            out_of_memory();
+        };
+
+        // Reuse a freed block, if it is aligned enough. Only blocks from allocations with an
+        // alignment over `MIN_BLOCK` may not be.
+        let block = self.free[class];
+        if !block.is_null() && (block as usize) % layout.align() == 0 {
+            self.free[class] = (*block).next;
+            return (block as *mut u8, false);
+        }
+
+        let heap_pos = self.next_alloc;
+        let ptr = heap_pos.add(heap_pos.align_offset(layout.align().max(MIN_BLOCK)));
+        let end = (ptr as usize)
+            .checked_add(block_size(class))
+            .filter(|&end| end <= &raw const _eheap as usize);
+        match end {
+            Some(end) => {
+                self.next_alloc = end as *mut u8;
+                (ptr, true)
+            }
+            None => out_of_memory(),
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for FreeListAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: Single threaded, so nothing else can touch this while we're working.
+        (*self.0.get()).allocate(layout).0
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let (ptr, fresh) = (*self.0.get()).allocate(layout);
+        // The heap starts out zeroed, but freed blocks do not.
+        if !fresh {
+            ptr::write_bytes(ptr, 0, layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // `layout` fits the block, so `class_of` succeeded when allocating it.
+        let class = class_of(&layout).unwrap_unchecked();
+        let lists = &mut *self.0.get();
+        let block = ptr as *mut FreeBlock;
+        (*block).next = lists.free[class];
+        lists.free[class] = block;
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        // Keep the block if the new size still rounds up to it.
+        if class_of(&layout) == class_of(&new_layout) {
+            return ptr;
+        }
+        let new_ptr = self.alloc(new_layout);
+        ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+        self.dealloc(ptr, layout);
+        new_ptr
+    }
+}
+
+fn out_of_memory() -> ! {
+    halt(OUT_OF_MEMORY_EXIT_CODE)
+}
+
+extern "C" {
+    /// The address of this variable is the start of the heap (growing upwards).
+    ///
+    /// It is defined in the linker script.
+    static mut _sheap: u8;
+
+    /// The address of this variable is the end of the heap, see `ceno_emul::Platform::heap`.
+    ///
+    /// It is defined in the linker script, from `_heap_size`.
+    static _eheap: u8;
+}
+
+#[global_allocator]
+static HEAP: FreeListAllocator = FreeListAllocator(UnsafeCell::new(FreeLists {
+    next_alloc: &raw mut _sheap,
+    free: [null_mut(); CLASSES],
+}));
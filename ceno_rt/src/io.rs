@@ -1,14 +1,63 @@
 use crate::{INFO_OUT_ADDR, WORD_SIZE};
 use core::{cell::Cell, fmt, mem::size_of, slice};
+use rkyv::{
+    Serialize, api::high::HighSerializer, rancor::Failure, ser::allocator::ArenaHandle, to_bytes,
+    util::AlignedVec,
+};
 
-static INFO_OUT: IOWriter = IOWriter::new(INFO_OUT_ADDR);
+static INFO_OUT: IOWriter = IOWriter::new(INFO_OUT_ADDR as *mut u32, u32::MAX as *const u32);
+
+/// The frames of `info_out` carry the stream that they belong to in the top byte of their length
+/// word: 0 for `print!`, or the file descriptor of `sys_write`.
+/// See `ceno_emul::host_utils::read_all_messages`.
+const STREAM_SHIFT: u32 = 24;
+const INFO_STREAM: u32 = 0;
+/// The length of the longest frame, in bytes.
+pub(crate) const MAX_STREAM_FRAME: usize = (1 << STREAM_SHIFT) - 1;
 
 pub fn info_out() -> &'static IOWriter {
     &INFO_OUT
 }
 
+extern "C" {
+    /// The address of this variable is the start of the public output segment.
+    ///
+    /// It is defined in the linker script.  The value of this variable is undefined.
+    static _public_output_start: u32;
+
+    /// The address of this variable is the end of the public output segment.
+    static _public_output_end: u32;
+}
+
+static PUBLIC_OUTPUT: IOWriter = IOWriter::new(
+    &raw const _public_output_start as *mut u32,
+    &raw const _public_output_end,
+);
+
+/// Reveal raw bytes to the verifier.
+///
+/// Unlike `info_out`, the public output segment is part of the proof: the verifier sees exactly
+/// the messages committed here, in order.
+///
+/// Panics, and so halts with an error, if the message does not fit in the public output segment.
+pub fn commit_slice(msg: &[u8]) {
+    PUBLIC_OUTPUT.write_frame(msg);
+}
+
+/// Reveal a value to the verifier, serialized with rkyv.
+///
+/// The host reads it back with `ceno_emul::host_utils::read_public_output`.
+pub fn commit<T>(value: &T)
+where
+    T: for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Failure>>,
+{
+    let bytes = to_bytes::<Failure>(value).expect("serialization of committed value failed");
+    commit_slice(&bytes);
+}
+
 pub struct IOWriter {
     cursor: Cell<*mut u32>,
+    end: *const u32,
 }
 
 // Safety: Only single-threaded programs are supported.
@@ -16,10 +65,11 @@ pub struct IOWriter {
 unsafe impl Sync for IOWriter {}
 
 impl IOWriter {
-    const fn new(addr: u32) -> Self {
-        assert!(addr % WORD_SIZE as u32 == 0);
+    /// A writer from `start` to `end`, which must be word-aligned.
+    const fn new(start: *mut u32, end: *const u32) -> Self {
         IOWriter {
-            cursor: Cell::new(addr as *mut u32),
+            cursor: Cell::new(start),
+            end,
         }
     }
 
@@ -30,6 +80,10 @@ impl IOWriter {
         let byte_len = count * size_of::<T>();
         let word_len = byte_len.div_ceil(WORD_SIZE);
         let cursor = self.cursor.get();
+        assert!(
+            word_len <= (self.end as usize - cursor as usize) / WORD_SIZE,
+            "out of space in the output segment"
+        );
 
         // Bump the cursor to the next word-aligned address.
         self.cursor.set(unsafe { cursor.add(word_len) });
@@ -44,9 +98,17 @@ impl IOWriter {
     }
 
     pub fn write_frame(&self, msg: &[u8]) {
+        self.write_stream_frame(INFO_STREAM, msg);
+    }
+
+    /// Write a frame of `stream`, which goes in the top byte of the length word.
+    ///
+    /// `msg` must not be longer than `MAX_STREAM_FRAME`.
+    pub(crate) fn write_stream_frame(&self, stream: u32, msg: &[u8]) {
+        debug_assert!(msg.len() <= MAX_STREAM_FRAME);
         let word_len = msg.len().div_ceil(WORD_SIZE);
         let words: &mut [u32] = self.alloc(1 + word_len);
-        words[0] = msg.len() as u32;
+        words[0] = (stream << STREAM_SHIFT) | msg.len() as u32;
         let bytes =
             unsafe { slice::from_raw_parts_mut(words[1..].as_mut_ptr() as *mut u8, msg.len()) };
         bytes.copy_from_slice(msg);
@@ -7,27 +7,47 @@ use getrandom::{Error, register_custom_getrandom};
 use core::arch::{asm, global_asm};
 use std::{
     alloc::{Layout, alloc_zeroed},
-    ptr::null,
+    ptr, slice,
 };
 
-#[cfg(target_arch = "riscv32")]
+#[cfg(all(target_arch = "riscv32", not(feature = "free-list-allocator")))]
 mod allocator;
 
+#[cfg(all(target_arch = "riscv32", feature = "free-list-allocator"))]
+mod free_list;
+
+#[cfg(target_arch = "riscv32")]
+mod panic;
+
 mod mmio;
-pub use mmio::{read, read_slice};
+pub use mmio::{read, read_public, read_public_slice, read_slice};
+
+mod env;
+pub use env::{args, var};
 
 mod io;
-pub use io::info_out;
+pub use io::{commit, commit_slice, info_out};
 
 mod params;
 pub use params::*;
 
 pub mod syscalls;
 
+/// Write to stdout (fd 1) or stderr (fd 2), as the `std` library does for `println!` and
+/// `eprintln!`. The bytes are written to `info_out` with the fd as their stream, and other fds
+/// are ignored.
+///
+/// # Safety
+///
+/// Make sure that `buf` has at least `count` bytes.
 #[no_mangle]
 #[linkage = "weak"]
-pub extern "C" fn sys_write(_fd: i32, _buf: *const u8, _count: usize) -> isize {
-    0
+pub unsafe extern "C" fn sys_write(fd: u32, buf: *const u8, count: usize) {
+    if fd == STDOUT_FILENO || fd == STDERR_FILENO {
+        for chunk in slice::from_raw_parts(buf, count).chunks(io::MAX_STREAM_FRAME) {
+            info_out().write_stream_frame(fd, chunk);
+        }
+    }
 }
 
 #[no_mangle]
@@ -36,10 +56,57 @@ pub extern "C" fn sys_alloc_words(nwords: usize) -> *mut u32 {
     unsafe { alloc_zeroed(Layout::from_size_align(4 * nwords, 4).unwrap()) as *mut u32 }
 }
 
+/// Copy the value of the environment variable `varname` to `recv_buf`, up to `words` words, as
+/// the `std` library does for `std::env::var`. Return the length of the value in bytes, or
+/// `usize::MAX` if the variable is not set.
+///
+/// # Safety
+///
+/// Make sure that `recv_buf` has at least `words` words, and `varname` has `varname_len` bytes.
 #[no_mangle]
 #[linkage = "weak"]
-pub extern "C" fn sys_getenv(_name: *const u8) -> *const u8 {
-    null()
+pub unsafe extern "C" fn sys_getenv(
+    recv_buf: *mut u32,
+    words: usize,
+    varname: *const u8,
+    varname_len: usize,
+) -> usize {
+    match env::var_bytes(slice::from_raw_parts(varname, varname_len)) {
+        Some(value) => copy_to_words(value, recv_buf, words),
+        None => usize::MAX,
+    }
+}
+
+/// The number of arguments of the program, for `std::env::args`.
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn sys_argc() -> usize {
+    env::args().count()
+}
+
+/// Copy the argument `arg_index` to `out_words`, up to `out_nwords` words, as the `std` library
+/// does for `std::env::args`. Return the length of the argument in bytes.
+///
+/// # Safety
+///
+/// Make sure that `out_words` has at least `out_nwords` words, and `arg_index` is less than
+/// `sys_argc()`.
+#[no_mangle]
+#[linkage = "weak"]
+pub unsafe extern "C" fn sys_argv(
+    out_words: *mut u32,
+    out_nwords: usize,
+    arg_index: usize,
+) -> usize {
+    let arg = env::arg_bytes(arg_index).expect("argument index out of range");
+    copy_to_words(arg, out_words, out_nwords)
+}
+
+/// Copy as much of `bytes` as fits in `nwords` words to `buf`, and return the length of `bytes`.
+unsafe fn copy_to_words(bytes: &[u8], buf: *mut u32, nwords: usize) -> usize {
+    let len = bytes.len().min(nwords * WORD_SIZE);
+    ptr::copy_nonoverlapping(bytes.as_ptr(), buf as *mut u8, len);
+    bytes.len()
 }
 
 /// Generates random bytes.
@@ -117,6 +184,9 @@ _start:
     la sp, _stack_start
     mv fp, sp
 
+    // Report panics to the host.
+    call {panic_init}
+
     // Call Rust's main function.
     call main
 
@@ -128,6 +198,7 @@ _start:
     li a0, 0
     ecall
     ",
+    panic_init = sym panic::init,
 );
 
 extern "C" {
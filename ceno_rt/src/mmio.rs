@@ -51,3 +51,37 @@ where
 {
     rkyv::access::<T, Failure>(read_slice()).expect("Deserialised access failed.")
 }
+
+extern "C" {
+    /// The address of this variable is the start of the public input segment.
+    ///
+    /// It is defined in the linker script.  The value of this variable is undefined.
+    static _public_io_start: usize;
+}
+
+/// Get the length of the next public input.
+///
+/// The public input has the same layout as the hints, see `ceno_host::CenoStdin`.
+fn public_input_len() -> usize {
+    static mut NEXT_PUBLIC_INPUT_LEN_AT: *const usize = &raw const _public_io_start;
+    unsafe {
+        let len: usize = core::ptr::read(NEXT_PUBLIC_INPUT_LEN_AT);
+        NEXT_PUBLIC_INPUT_LEN_AT = NEXT_PUBLIC_INPUT_LEN_AT.add(1);
+        len
+    }
+}
+
+/// Read the next public input as raw bytes.
+///
+/// Unlike hints, the public input is known to the verifier.
+pub fn read_public_slice<'a>() -> &'a [u8] {
+    unsafe { from_raw_parts(&raw const _public_io_start as *const u8, public_input_len()) }
+}
+
+/// Read the next public input.
+pub fn read_public<'a, T>() -> &'a T
+where
+    T: Portable + for<'c> CheckBytes<HighValidator<'c, Failure>>,
+{
+    rkyv::access::<T, Failure>(read_public_slice()).expect("Deserialised access failed.")
+}
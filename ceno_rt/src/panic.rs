@@ -0,0 +1,35 @@
+use core::{arch::asm, ptr};
+use std::panic::{self, PanicHookInfo};
+
+use crate::{PANIC_EXIT_CODE, PANIC_REPORT_MAGIC, WORD_SIZE};
+
+/// Report panics to the host. Called by `_start` before `main`.
+pub(crate) extern "C" fn init() {
+    panic::set_hook(Box::new(report_panic));
+}
+
+/// Halt with `PANIC_EXIT_CODE`, leaving the message and location of the panic to the host.
+///
+/// The report is a frame in the heap: `PANIC_REPORT_MAGIC`, the length in bytes, then the bytes.
+/// Its address is passed to the halt ecall in `a1`. Unlike `info_out`, the heap is part of the
+/// proven memory, so that a panicking guest can still be proven.
+fn report_panic(info: &PanicHookInfo) {
+    let report = info.to_string();
+    let mut frame = vec![0u32; 2 + report.len().div_ceil(WORD_SIZE)];
+    frame[0] = PANIC_REPORT_MAGIC;
+    frame[1] = report.len() as u32;
+    unsafe {
+        ptr::copy_nonoverlapping(
+            report.as_ptr(),
+            frame[2..].as_mut_ptr() as *mut u8,
+            report.len(),
+        );
+        asm!(
+            "ecall",
+            in ("a0") PANIC_EXIT_CODE,
+            in ("a1") frame.as_ptr(),
+            in ("t0") 0,
+        );
+    }
+    unreachable!();
+}
@@ -1,3 +1,18 @@
 pub const WORD_SIZE: usize = 4;
 
 pub const INFO_OUT_ADDR: u32 = 0xC000_0000;
+
+/// The file descriptors of `sys_write`, which are also their streams on `info_out`.
+pub const STDOUT_FILENO: u32 = 1;
+pub const STDERR_FILENO: u32 = 2;
+
+/// The exit code of a guest that panicked, see `ceno_emul::Platform::code_panic`.
+pub const PANIC_EXIT_CODE: u32 = 1;
+
+/// The first word of a panic report, which tells a panic from a guest that halts with
+/// `PANIC_EXIT_CODE` itself. See `ceno_emul::Platform::panic_report_magic`.
+pub const PANIC_REPORT_MAGIC: u32 = u32::from_le_bytes(*b"PNC!");
+
+/// The exit code of a guest that ran out of heap memory, see
+/// `ceno_emul::Platform::code_out_of_memory`.
+pub const OUT_OF_MEMORY_EXIT_CODE: u32 = 2;
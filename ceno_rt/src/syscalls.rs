@@ -24,3 +24,131 @@ pub fn keccak_permute(state: &mut [u64; 25]) {
     #[cfg(not(target_os = "zkvm"))]
     unreachable!()
 }
+
+// Based on https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/zkvm/entrypoint/src/syscalls/sha_extend.rs
+pub const SHA_EXTEND: u32 = 0x00_30_01_05;
+
+// Based on https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/zkvm/entrypoint/src/syscalls/sha_compress.rs
+pub const SHA_COMPRESS: u32 = 0x00_01_01_06;
+
+/// Executes the SHA-256 message schedule: computes `w[16..64]` from the message block in
+/// `w[0..16]`.
+///
+/// ### Safety
+///
+/// The caller must ensure that `w` is valid pointer to data that is aligned along a four byte
+/// boundary.
+#[allow(unused_variables)]
+pub fn sha_extend(w: &mut [u32; 64]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") SHA_EXTEND,
+            in("a0") w as *mut [u32; 64],
+            in("a1") 0
+        );
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Executes the SHA-256 compression function on the hash `state`, with the message schedule `w`
+/// from `sha_extend`.
+///
+/// ### Safety
+///
+/// The caller must ensure that `w` and `state` are valid pointers to data that is aligned along
+/// a four byte boundary, and that they do not overlap.
+#[allow(unused_variables)]
+pub fn sha_compress(w: &[u32; 64], state: &mut [u32; 8]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") SHA_COMPRESS,
+            in("a0") w as *const [u32; 64],
+            in("a1") state as *mut [u32; 8]
+        );
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+// Based on https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/zkvm/entrypoint/src/syscalls/secp256k1.rs
+pub const SECP256K1_ADD: u32 = 0x00_01_01_0A;
+pub const SECP256K1_DOUBLE: u32 = 0x00_00_01_0B;
+
+// Based on https://github.com/succinctlabs/sp1/blob/013c24ea2fa15a0e7ed94f7d11a7ada4baa39ab9/crates/zkvm/entrypoint/src/syscalls/ed25519.rs
+pub const ED25519_ADD: u32 = 0x00_01_01_07;
+
+/// Adds two secp256k1 points: `p = p + q`.
+///
+/// Points are in affine coordinates, `x` then `y`, each as 8 little-endian words. The points must
+/// be on the curve with different `x` coordinates.
+///
+/// ### Safety
+///
+/// The caller must ensure that `p` and `q` are valid pointers to data that is aligned along a
+/// four byte boundary, and that they do not overlap.
+#[allow(unused_variables)]
+pub fn secp256k1_add(p: &mut [u32; 16], q: &[u32; 16]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") SECP256K1_ADD,
+            in("a0") p as *mut [u32; 16],
+            in("a1") q as *const [u32; 16]
+        );
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Doubles a secp256k1 point: `p = p + p`.
+///
+/// The point is in affine coordinates as in `secp256k1_add`, and must not have `y = 0`.
+///
+/// ### Safety
+///
+/// The caller must ensure that `p` is valid pointer to data that is aligned along a four byte
+/// boundary.
+#[allow(unused_variables)]
+pub fn secp256k1_double(p: &mut [u32; 16]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") SECP256K1_DOUBLE,
+            in("a0") p as *mut [u32; 16],
+            in("a1") 0
+        );
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Adds two ed25519 points: `p = p + q`.
+///
+/// Points are in affine coordinates as in `secp256k1_add`, and must be on the curve. The addition
+/// is complete: `q` may be a copy of `p`, or the identity `(0, 1)`.
+///
+/// ### Safety
+///
+/// The caller must ensure that `p` and `q` are valid pointers to data that is aligned along a
+/// four byte boundary, and that they do not overlap.
+#[allow(unused_variables)]
+pub fn ed25519_add(p: &mut [u32; 16], q: &[u32; 16]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") ED25519_ADD,
+            in("a0") p as *mut [u32; 16],
+            in("a1") q as *const [u32; 16]
+        );
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
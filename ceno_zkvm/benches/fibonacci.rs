@@ -31,9 +31,17 @@ fn setup() -> (Program, Platform) {
     let stack_size = 32768;
     let heap_size = 2097152;
     let pub_io_size = 16;
+    let pub_output_size = 16;
     let elf_bytes = fs::read(&file_path).expect("read elf file");
     let program = Program::load_elf(&elf_bytes, u32::MAX).unwrap();
-    let platform = setup_platform(Preset::Sp1, &program, stack_size, heap_size, pub_io_size);
+    let platform = setup_platform(
+        Preset::Sp1,
+        &program,
+        stack_size,
+        heap_size,
+        pub_io_size,
+        pub_output_size,
+    );
     (program, platform)
 }
 
@@ -41,10 +49,11 @@ fn fibonacci_prove(c: &mut Criterion) {
     let (program, platform) = setup();
     for max_steps in [1usize << 20, 1usize << 21, 1usize << 22] {
         // estimate proof size data first
-        let (proof, verifier) = run_e2e_with_checkpoint::<E, Pcs>(
+        let (proof, verifier, _) = run_e2e_with_checkpoint::<E, Pcs>(
             program.clone(),
             platform.clone(),
             vec![],
+            vec![],
             max_steps,
             Checkpoint::PrepSanityCheck,
         )
@@ -85,6 +94,7 @@ fn fibonacci_prove(c: &mut Criterion) {
                             program.clone(),
                             platform.clone(),
                             vec![],
+                            vec![],
                             max_steps,
                             Checkpoint::PrepE2EProving,
                         );
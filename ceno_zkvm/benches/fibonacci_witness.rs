@@ -29,9 +29,17 @@ fn setup() -> (Program, Platform) {
     let stack_size = 32768;
     let heap_size = 2097152;
     let pub_io_size = 16;
+    let pub_output_size = 16;
     let elf_bytes = fs::read(&file_path).expect("read elf file");
     let program = Program::load_elf(&elf_bytes, u32::MAX).unwrap();
-    let platform = setup_platform(Preset::Sp1, &program, stack_size, heap_size, pub_io_size);
+    let platform = setup_platform(
+        Preset::Sp1,
+        &program,
+        stack_size,
+        heap_size,
+        pub_io_size,
+        pub_output_size,
+    );
     (program, platform)
 }
 
@@ -56,6 +64,7 @@ fn fibonacci_witness(c: &mut Criterion) {
                         program.clone(),
                         platform.clone(),
                         vec![],
+                        vec![],
                         max_steps,
                         Checkpoint::PrepWitnessGen,
                     );
@@ -135,6 +135,16 @@ fn main() {
 
     let io_addrs = init_public_io(&[]).iter().map(|v| v.addr).collect_vec();
 
+    // The program does not commit any public output.
+    let output_addrs = MemPadder::init_mem(
+        CENO_PLATFORM.public_output.clone(),
+        mmu_config.public_output_len(),
+        &[],
+    )
+    .iter()
+    .map(|v| v.addr)
+    .collect_vec();
+
     config.generate_fixed_traces(&zkvm_cs, &mut zkvm_fixed_traces);
     mmu_config.generate_fixed_traces(
         &zkvm_cs,
@@ -142,6 +152,7 @@ fn main() {
         &reg_init,
         &mem_init,
         &io_addrs,
+        &output_addrs,
     );
 
     let pk = zkvm_cs
@@ -195,6 +206,7 @@ fn main() {
             EXIT_PC as u32,
             end_cycle,
             public_io_init.iter().map(|v| v.value).collect(),
+            vec![0; output_addrs.len()],
         );
 
         let mut zkvm_witness = ZKVMWitnesses::default();
@@ -213,6 +225,7 @@ fn main() {
                     let vma: WordAddr = Platform::register_vma(index).into();
                     MemFinalRecord {
                         addr: rec.addr,
+                        init_value: rec.value,
                         value: vm.peek_register(index),
                         cycle: *final_access.get(&vma).unwrap_or(&0),
                     }
@@ -220,6 +233,7 @@ fn main() {
                     // The table is padded beyond the number of registers.
                     MemFinalRecord {
                         addr: rec.addr,
+                        init_value: 0,
                         value: 0,
                         cycle: 0,
                     }
@@ -234,6 +248,7 @@ fn main() {
                 let vma: WordAddr = rec.addr.into();
                 MemFinalRecord {
                     addr: rec.addr,
+                    init_value: rec.value,
                     value: vm.peek_memory(vma),
                     cycle: *final_access.get(&vma).unwrap_or(&0),
                 }
@@ -257,6 +272,7 @@ fn main() {
                 &reg_final,
                 &mem_final,
                 &public_io_final,
+                &vec![0; output_addrs.len()],
                 &[],
             )
             .unwrap();
@@ -1,6 +1,11 @@
-use ceno_emul::{IterAddresses, Program, WORD_SIZE, Word};
+use ceno_emul::{
+    CENO_PLATFORM, IterAddresses, Program, WORD_SIZE, Word,
+    disassemble::write_listing,
+    gdb::GdbStub,
+    host_utils::{GuestEnv, load_env},
+};
 use ceno_zkvm::{
-    e2e::{Checkpoint, Preset, run_e2e_with_checkpoint, setup_platform},
+    e2e::{Checkpoint, Preset, guest_vm, profile_guest, run_e2e_with_checkpoint, setup_platform},
     with_panic_hook,
 };
 use clap::Parser;
@@ -8,7 +13,7 @@ use ff_ext::ff::Field;
 use goldilocks::{Goldilocks, GoldilocksExt2};
 use itertools::Itertools;
 use mpcs::{Basefold, BasefoldRSParams};
-use std::{fs, panic};
+use std::{fs, io, panic, process};
 use tracing::level_filters::LevelFilter;
 use tracing_forest::ForestLayer;
 use tracing_subscriber::{
@@ -25,6 +30,12 @@ fn parse_size(s: &str) -> Result<u32, parse_size::Error> {
         .map(|size| size as u32)
 }
 
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=value, got {s}"))
+}
+
 /// Prove the execution of a fixed RISC-V program.
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -51,6 +62,23 @@ struct Args {
     #[arg(long)]
     hints: Option<String>,
 
+    /// Public input, known to the verifier.
+    /// The file holds the bytes of a `ceno_host::CenoStdin`, as `ceno_rt::read_public` reads them:
+    /// the lengths of the inputs, then the inputs.
+    /// The public input table is sized to fit it.
+    #[arg(long)]
+    public_io: Option<String>,
+
+    /// An argument of the guest program, read with `ceno_rt::args` or `std::env::args`.
+    /// The arguments are part of the proven initial memory.
+    #[arg(long = "arg")]
+    guest_args: Vec<String>,
+
+    /// An environment variable of the guest as `NAME=value`, read with `ceno_rt::var` or
+    /// `std::env::var`. The variables are part of the proven initial memory.
+    #[arg(long = "env", value_parser = parse_env_var)]
+    guest_env: Vec<(String, String)>,
+
     /// Stack size in bytes.
     #[arg(long, default_value = "32k", value_parser = parse_size)]
     stack_size: u32,
@@ -58,6 +86,23 @@ struct Args {
     /// Heap size in bytes.
     #[arg(long, default_value = "2M", value_parser = parse_size)]
     heap_size: u32,
+
+    /// Size in bytes of the public output the guest can commit to.
+    #[arg(long, default_value = "256", value_parser = parse_size)]
+    public_output_size: u32,
+
+    /// Write the steps spent in each guest function to this file before proving.
+    /// The output is in the folded-stack format of flamegraph tools.
+    #[arg(long)]
+    profile_guest: Option<String>,
+
+    /// Instead of proving, serve the guest to `gdb` on this address, e.g. `localhost:9001`.
+    #[arg(long)]
+    gdb: Option<String>,
+
+    /// Instead of proving, list the instructions of the ELF with their addresses and symbols.
+    #[arg(long)]
+    objdump: bool,
 }
 
 fn main() {
@@ -65,9 +110,11 @@ fn main() {
         let mut args = Args::parse();
         args.stack_size = args.stack_size.next_multiple_of(WORD_SIZE as u32);
         args.heap_size = args.heap_size.next_multiple_of(WORD_SIZE as u32);
+        args.public_output_size = args.public_output_size.next_multiple_of(WORD_SIZE as u32);
         args
     };
-    let pub_io_size = 16; // TODO: configure.
+    let public_io = memory_from_file(&args.public_io);
+    let pub_io_size = (public_io.len() * WORD_SIZE) as u32;
 
     // default filter
     let default_filter = EnvFilter::builder()
@@ -105,13 +152,23 @@ fn main() {
 
     tracing::info!("Loading ELF file: {}", &args.elf);
     let elf_bytes = fs::read(&args.elf).expect("read elf file");
-    let program = Program::load_elf(&elf_bytes, u32::MAX).unwrap();
+    let mut program = Program::load_elf(&elf_bytes, u32::MAX).unwrap();
+    if args.objdump {
+        write_listing(&program, io::stdout().lock()).expect("write listing");
+        return;
+    }
+    let guest_env = GuestEnv {
+        args: args.guest_args.clone(),
+        vars: args.guest_env.iter().cloned().collect(),
+    };
+    load_env(&mut program, &CENO_PLATFORM.env, &guest_env);
     let platform = setup_platform(
         args.platform,
         &program,
         args.stack_size,
         args.heap_size,
         pub_io_size,
+        args.public_output_size,
     );
     tracing::info!("Running on platform {:?} {}", args.platform, platform);
     tracing::info!(
@@ -120,6 +177,8 @@ fn main() {
         args.heap_size
     );
 
+    tracing::info!("Public input: {} words", public_io.len());
+
     tracing::info!("Loading hints file: {:?}", args.hints);
     let hints = memory_from_file(&args.hints);
     assert!(
@@ -128,8 +187,28 @@ fn main() {
         platform.hints.len()
     );
 
+    if let Some(addr) = &args.gdb {
+        let vm = guest_vm(&program, &platform, &hints, &public_io);
+        GdbStub::new(vm).serve(addr).expect("gdb session failed");
+        return;
+    }
+
     let max_steps = args.max_steps.unwrap_or(usize::MAX);
 
+    if let Some(path) = &args.profile_guest {
+        let file = fs::File::create(path).expect("create guest profile file");
+        profile_guest(
+            &program,
+            &platform,
+            &hints,
+            &public_io,
+            max_steps,
+            io::BufWriter::new(file),
+        )
+        .expect("write guest profile");
+        tracing::info!("Guest profile written to {path}");
+    }
+
     type E = GoldilocksExt2;
     type B = Goldilocks;
     type Pcs = Basefold<GoldilocksExt2, BasefoldRSParams>;
@@ -138,17 +217,31 @@ fn main() {
         program,
         platform,
         hints,
+        public_io.clone(),
         max_steps,
         Checkpoint::PrepSanityCheck,
     );
 
-    let (mut zkvm_proof, verifier) = state.expect("PrepSanityCheck should yield state.");
+    let (mut zkvm_proof, verifier, exit) = state.expect("PrepSanityCheck should yield state.");
+
+    for (i, msg) in zkvm_proof.public_output().iter().enumerate() {
+        tracing::info!("public output {i}: {msg:02x?}");
+    }
 
     // do statistics
     let serialize_size = bincode::serialize(&zkvm_proof).unwrap().len();
     let stat_recorder = StatisticRecorder::default();
     let transcript = TranscriptWithStat::new(&stat_recorder, b"riscv");
-    verifier.verify_proof(zkvm_proof.clone(), transcript).ok();
+    let verified = verifier.verify_proof_halt_with_public_io(
+        zkvm_proof.clone(),
+        transcript,
+        &public_io,
+        exit.exit_code.is_some(),
+    );
+    if !matches!(verified, Ok(true)) {
+        tracing::error!("the proof does not verify on the public input: {verified:?}");
+        process::exit(1);
+    }
     println!(
         "e2e proof stat: proof size = {}, hashes count = {}",
         serialize_size,
@@ -5,8 +5,8 @@ use crate::{
     error::ZKVMError,
     expression::{Expression, Fixed, Instance, StructuralWitIn, ToExpr, WitIn},
     instructions::riscv::constants::{
-        END_CYCLE_IDX, END_PC_IDX, EXIT_CODE_IDX, INIT_CYCLE_IDX, INIT_PC_IDX, PUBLIC_IO_IDX,
-        UINT_LIMBS,
+        END_CYCLE_IDX, END_PC_IDX, EXIT_CODE_IDX, INIT_CYCLE_IDX, INIT_PC_IDX,
+        INIT_PUBLIC_OUTPUT_IDX, PUBLIC_IO_IDX, PUBLIC_OUTPUT_IDX, UINT_LIMBS,
     },
     structs::{ProgramParams, RAMType, ROMType},
     tables::InsnRecord,
@@ -28,6 +28,22 @@ impl<'a, E: ExtensionField> CircuitBuilder<'a, E> {
         self.cs.create_witin(name_fn)
     }
 
+    pub fn create_init_state_witin<NR, N>(&mut self, name_fn: N) -> WitIn
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.create_init_state_witin(name_fn)
+    }
+
+    pub fn create_final_state_witin<NR, N>(&mut self, name_fn: N) -> WitIn
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.create_final_state_witin(name_fn)
+    }
+
     pub fn create_structural_witin<NR, N>(
         &mut self,
         name_fn: N,
@@ -79,6 +95,36 @@ impl<'a, E: ExtensionField> CircuitBuilder<'a, E> {
         self.cs.query_instance(|| "public_io", PUBLIC_IO_IDX)
     }
 
+    pub fn query_public_output(&mut self) -> Result<Instance, ZKVMError> {
+        self.cs
+            .query_instance(|| "public_output", PUBLIC_OUTPUT_IDX)
+    }
+
+    pub fn query_init_public_output(&mut self) -> Result<Instance, ZKVMError> {
+        self.cs
+            .query_instance(|| "init_public_output", INIT_PUBLIC_OUTPUT_IDX)
+    }
+
+    /// Query the `limbs` consecutive public values starting at `idx`.
+    pub fn query_instance_limbs<NR, N>(
+        &mut self,
+        name_fn: N,
+        idx: usize,
+        limbs: usize,
+    ) -> Result<Vec<Instance>, ZKVMError>
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name: String = name_fn().into();
+        (0..limbs)
+            .map(|l| {
+                self.cs
+                    .query_instance(|| format!("{name}_limb_{l}"), idx + l)
+            })
+            .collect()
+    }
+
     pub fn lk_record<NR, N>(
         &mut self,
         name_fn: N,
@@ -424,6 +470,16 @@ impl<'a, E: ExtensionField> CircuitBuilder<'a, E> {
         self.logic_u8(ROMType::Ltu, a, b, c)
     }
 
+    /// Assert `(a << b) & 0xff = c`, that `a` is a byte and that `b < 8`.
+    pub fn lookup_shl_byte(
+        &mut self,
+        a: Expression<E>,
+        b: Expression<E>,
+        c: Expression<E>,
+    ) -> Result<(), ZKVMError> {
+        self.logic_u8(ROMType::Shl, a, b, c)
+    }
+
     // Assert that `2^b = c` and that `b` is a 5-bit unsigned integer.
     pub fn lookup_pow2(&mut self, b: Expression<E>, c: Expression<E>) -> Result<(), ZKVMError> {
         self.logic_u8(ROMType::Pow, 2.into(), b, c)
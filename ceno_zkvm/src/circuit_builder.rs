@@ -1,5 +1,11 @@
 use itertools::{Itertools, chain};
-use std::{collections::HashMap, iter::once, marker::PhantomData};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter::once,
+    marker::PhantomData,
+    ops::Range,
+};
 
 use ff_ext::ExtensionField;
 use mpcs::PolynomialCommitmentScheme;
@@ -14,7 +20,7 @@ use crate::{
 };
 
 /// namespace used for annotation, preserve meta info during circuit construction
-#[derive(Clone, Debug, Default, serde::Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct NameSpace {
     namespace: Vec<String>,
 }
@@ -51,20 +57,28 @@ impl NameSpace {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct LogupTableExpression<E: ExtensionField> {
     pub multiplicity: Expression<E>,
     pub values: Expression<E>,
     pub table_spec: SetTableSpec,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SetTableSpec {
     pub len: Option<usize>,
     pub structural_witins: Vec<StructuralWitIn>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct SetTableExpression<E: ExtensionField> {
     /// table expression
     pub expr: Expression<E>,
@@ -74,20 +88,30 @@ pub struct SetTableExpression<E: ExtensionField> {
     pub table_spec: SetTableSpec,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct ConstraintSystem<E: ExtensionField> {
     pub(crate) ns: NameSpace,
 
     pub num_witin: WitnessId,
     pub witin_namespace_map: Vec<String>,
 
+    /// In continuation mode, the last witnesses of a table hold its content at the start, then
+    /// at the end of a segment. Each group is committed apart from the other witnesses, so that
+    /// the verifier links segments by these commitments.
+    pub num_init_state_witin: WitnessId,
+    pub num_final_state_witin: WitnessId,
+
     pub num_structural_witin: WitnessId,
     pub structural_witin_namespace_map: Vec<String>,
 
     pub num_fixed: usize,
     pub fixed_namespace_map: Vec<String>,
 
-    pub instance_name_map: HashMap<Instance, String>,
+    pub instance_name_map: BTreeMap<Instance, String>,
 
     pub r_expressions: Vec<Expression<E>>,
     pub r_expressions_namespace_map: Vec<String>,
@@ -128,6 +152,7 @@ pub struct ConstraintSystem<E: ExtensionField> {
     pub chip_record_alpha: Expression<E>,
     pub chip_record_beta: Expression<E>,
 
+    #[serde(skip)]
     pub debug_map: HashMap<usize, Vec<Expression<E>>>,
 
     pub(crate) phantom: PhantomData<E>,
@@ -139,12 +164,14 @@ impl<E: ExtensionField> ConstraintSystem<E> {
             num_witin: 0,
             // platform,
             witin_namespace_map: vec![],
+            num_init_state_witin: 0,
+            num_final_state_witin: 0,
             num_structural_witin: 0,
             structural_witin_namespace_map: vec![],
             num_fixed: 0,
             fixed_namespace_map: vec![],
             ns: NameSpace::new(root_name_fn),
-            instance_name_map: HashMap::new(),
+            instance_name_map: BTreeMap::new(),
             r_expressions: vec![],
             r_expressions_namespace_map: vec![],
             r_ram_types: vec![],
@@ -192,11 +219,46 @@ impl<E: ExtensionField> ConstraintSystem<E> {
             vk: VerifyingKey {
                 cs: self,
                 fixed_commit,
+                init_state_commit: None,
             },
         }
     }
 
+    /// The ranges of witnesses which are committed together: the main witnesses, then the
+    /// initial and the final segment state, which are empty outside of continuation mode.
+    pub fn witin_groups(&self) -> [Range<usize>; 3] {
+        let end = self.num_witin as usize;
+        let final_start = end - self.num_final_state_witin as usize;
+        let init_start = final_start - self.num_init_state_witin as usize;
+        [0..init_start, init_start..final_start, final_start..end]
+    }
+
     pub fn create_witin<NR: Into<String>, N: FnOnce() -> NR>(&mut self, n: N) -> WitIn {
+        assert_eq!(
+            self.num_init_state_witin + self.num_final_state_witin,
+            0,
+            "segment state witnesses must come last"
+        );
+        self.push_witin(n)
+    }
+
+    /// Create a witness of the content at the start of a segment, after the main witnesses.
+    pub fn create_init_state_witin<NR: Into<String>, N: FnOnce() -> NR>(&mut self, n: N) -> WitIn {
+        assert_eq!(
+            self.num_final_state_witin, 0,
+            "initial state witnesses must come before the final state"
+        );
+        self.num_init_state_witin = self.num_init_state_witin.strict_add(1);
+        self.push_witin(n)
+    }
+
+    /// Create a witness of the content at the end of a segment, after all other witnesses.
+    pub fn create_final_state_witin<NR: Into<String>, N: FnOnce() -> NR>(&mut self, n: N) -> WitIn {
+        self.num_final_state_witin = self.num_final_state_witin.strict_add(1);
+        self.push_witin(n)
+    }
+
+    fn push_witin<NR: Into<String>, N: FnOnce() -> NR>(&mut self, n: N) -> WitIn {
         let wit_in = WitIn { id: self.num_witin };
         self.num_witin = self.num_witin.strict_add(1);
 
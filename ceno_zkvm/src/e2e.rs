@@ -1,7 +1,7 @@
 use crate::{
     instructions::riscv::{DummyExtraConfig, MemPadder, MmuConfig, Rv32imConfig},
     scheme::{
-        PublicValues, ZKVMProof,
+        PublicValues, SegmentState, ZKVMProof,
         constants::MAX_NUM_VARIABLES,
         mock_prover::{LkMultiplicityKey, MockProver},
         prover::ZKVMProver,
@@ -9,13 +9,15 @@ use crate::{
     },
     state::GlobalState,
     structs::{
-        ProgramParams, ZKVMConstraintSystem, ZKVMFixedTraces, ZKVMProvingKey, ZKVMWitnesses,
+        ProgramParams, ZKVMConstraintSystem, ZKVMFixedTraces, ZKVMProvingKey, ZKVMVerifyingKey,
+        ZKVMWitnesses,
     },
     tables::{MemFinalRecord, MemInitRecord, ProgramTableCircuit, ProgramTableConfig},
+    utils::next_pow2_instance_padding,
 };
 use ceno_emul::{
-    ByteAddr, CENO_PLATFORM, EmuContext, InsnKind, IterAddresses, Platform, Program, StepRecord,
-    Tracer, VMState, WORD_SIZE, WordAddr,
+    ByteAddr, CENO_PLATFORM, EmuContext, IterAddresses, Platform, Profiler, Program, StepRecord,
+    Tracer, VMSnapshot, VMState, WORD_SIZE, WordAddr, host_utils::read_panic_message,
 };
 use clap::ValueEnum;
 use ff_ext::ExtensionField;
@@ -23,7 +25,9 @@ use itertools::{Itertools, MinMaxResult, chain};
 use mpcs::PolynomialCommitmentScheme;
 use std::{
     collections::{BTreeSet, HashMap, HashSet},
+    io,
     iter::zip,
+    ops::Range,
     sync::Arc,
 };
 use transcript::BasicTranscript as Transcript;
@@ -31,6 +35,7 @@ use transcript::BasicTranscript as Transcript;
 pub struct FullMemState<Record> {
     mem: Vec<Record>,
     io: Vec<Record>,
+    output: Vec<Record>,
     reg: Vec<Record>,
     priv_io: Vec<Record>,
 }
@@ -38,61 +43,209 @@ pub struct FullMemState<Record> {
 type InitMemState = FullMemState<MemInitRecord>;
 type FinalMemState = FullMemState<MemFinalRecord>;
 
+/// How the guest ended, as found by the emulator.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuestExit {
+    /// The exit code of the guest, or `None` if it did not halt within the step limit.
+    pub exit_code: Option<u32>,
+    /// The panic message of the guest, if it panicked.
+    pub panic_message: Option<String>,
+}
+
 pub struct EmulationResult {
-    exit_code: Option<u32>,
-    all_records: Vec<StepRecord>,
+    exit: GuestExit,
     final_mem_state: FinalMemState,
     pi: PublicValues<u32>,
 }
 
+/// The number of steps that the emulator hands over to witness generation at a time.
+pub const WITNESS_CHUNK_STEPS: usize = 1 << 16;
+
+/// Emulate `program` as a single segment, passing its steps to `on_steps` in chunks.
 fn emulate_program(
     program: Arc<Program>,
     max_steps: usize,
-    init_mem_state: InitMemState,
+    init_mem_state: &InitMemState,
     platform: &Platform,
-    hints: Vec<u32>,
+    hints: &[u32],
+    on_steps: impl FnMut(Vec<StepRecord>),
+) -> EmulationResult {
+    SegmentEmulator::new(program, max_steps, None, init_mem_state, platform, hints)
+        .next_segment(WITNESS_CHUNK_STEPS, on_steps)
+        .expect("emulation yields at least one segment")
+}
+
+/// Emulate a program, cutting the trace in segments of at most `segment_steps` steps.
+///
+/// Without `segment_steps`, the whole trace is one segment with the public values of a full
+/// execution. Otherwise, each segment has the public values of continuation mode.
+struct SegmentEmulator<'a> {
+    vm: VMState,
+    init_mem_state: &'a InitMemState,
+    hints: &'a [u32],
+    continuation: bool,
+    segment_steps: usize,
+    steps_left: usize,
+    done: bool,
+}
+
+impl<'a> SegmentEmulator<'a> {
+    fn new(
+        program: Arc<Program>,
+        max_steps: usize,
+        segment_steps: Option<usize>,
+        init_mem_state: &'a InitMemState,
+        platform: &Platform,
+        hints: &'a [u32],
+    ) -> Self {
+        let mut vm: VMState = VMState::new(platform.clone(), program);
+
+        for record in &init_mem_state.io {
+            vm.init_memory(record.addr.into(), record.value);
+        }
+
+        for (addr, value) in zip(platform.hints.iter_addresses(), hints) {
+            vm.init_memory(addr.into(), *value);
+        }
+
+        let continuation = segment_steps.is_some();
+        let segment_steps = match segment_steps {
+            Some(steps) => {
+                assert!(steps > 0, "segments must have at least one step");
+                steps
+            }
+            None => max_steps,
+        };
+
+        Self {
+            vm,
+            init_mem_state,
+            hints,
+            continuation,
+            segment_steps,
+            steps_left: max_steps,
+            done: false,
+        }
+    }
+
+    /// Run the next segment and pass its steps to `on_steps`, in chunks of at most
+    /// `chunk_steps`. Return `None` once the program halted or ran for `max_steps`.
+    fn next_segment(
+        &mut self,
+        chunk_steps: usize,
+        mut on_steps: impl FnMut(Vec<StepRecord>),
+    ) -> Option<EmulationResult> {
+        if self.done {
+            return None;
+        }
+
+        let start = self.vm.start_segment();
+        let mut segment_left = self.segment_steps.min(self.steps_left);
+        while segment_left > 0 && !self.vm.halted() {
+            let steps = self
+                .vm
+                .iter_until_halt()
+                .take(chunk_steps.min(segment_left))
+                .collect::<Result<Vec<StepRecord>, _>>()
+                .expect("vm exec failed");
+            segment_left -= steps.len();
+            self.steps_left -= steps.len();
+            on_steps(steps);
+        }
+
+        self.done = self.vm.halted() || self.steps_left == 0;
+        Some(finish_segment(
+            &self.vm,
+            &start,
+            self.init_mem_state,
+            self.hints,
+            self.continuation,
+        ))
+    }
+}
+
+/// Set up a VM to run `program` with its inputs, outside of the proving pipeline.
+pub fn guest_vm(
+    program: &Program,
+    platform: &Platform,
+    hints: &[u32],
+    public_io: &[u32],
+) -> VMState {
+    let mut vm = VMState::new(platform.clone(), Arc::new(program.clone()));
+    for (addr, value) in zip(platform.public_io.iter_addresses(), public_io) {
+        vm.init_memory(addr.into(), *value);
+    }
+    for (addr, value) in zip(platform.hints.iter_addresses(), hints) {
+        vm.init_memory(addr.into(), *value);
+    }
+    vm
+}
+
+/// Run `program` and write the number of steps spent in each call stack of guest functions to
+/// `out`, in the folded-stack format of flamegraph tools.
+pub fn profile_guest(
+    program: &Program,
+    platform: &Platform,
+    hints: &[u32],
+    public_io: &[u32],
+    max_steps: usize,
+    out: impl io::Write,
+) -> io::Result<()> {
+    let mut vm = guest_vm(program, platform, hints, public_io);
+    let mut profiler = Profiler::new(program);
+    for step in vm.iter_until_halt().take(max_steps) {
+        let step = step.unwrap_or_else(|err| panic!("vm exec failed: {}", err.report(program)));
+        profiler.observe(&step);
+    }
+    tracing::info!("profiled {} guest steps", profiler.total());
+    profiler.write_folded(out)
+}
+
+/// Find the final memory state and the public values of a segment which started from `start`.
+fn finish_segment(
+    vm: &VMState,
+    start: &VMSnapshot,
+    init_mem_state: &InitMemState,
+    hints: &[u32],
+    continuation: bool,
 ) -> EmulationResult {
     let InitMemState {
         mem: mem_init,
         io: io_init,
+        output: output_init,
         reg: reg_init,
         priv_io: _,
     } = init_mem_state;
+    let platform = vm.platform();
 
-    let mut vm: VMState = VMState::new(platform.clone(), program);
-
-    for (addr, value) in zip(platform.hints.iter_addresses(), &hints) {
-        vm.init_memory(addr.into(), *value);
+    // The HALT step leaves the exit code in its argument register, if halting at all.
+    let exit_code = vm.halted().then(|| vm.peek_register(Platform::reg_arg0()));
+    let panic_message = read_panic_message(vm);
+    if let Some(message) = &panic_message {
+        tracing::error!("guest panicked: {message}");
+    }
+    if exit_code == Some(Platform::code_out_of_memory()) {
+        tracing::error!("guest ran out of heap memory");
     }
-
-    let all_records = vm
-        .iter_until_halt()
-        .take(max_steps)
-        .collect::<Result<Vec<StepRecord>, _>>()
-        .expect("vm exec failed");
-
-    // Find the exit code from the HALT step, if halting at all.
-    let exit_code = all_records
-        .iter()
-        .rev()
-        .find(|record| {
-            record.insn().kind == InsnKind::ECALL
-                && record.rs1().unwrap().value == Platform::ecall_halt()
-        })
-        .and_then(|halt_record| halt_record.rs2())
-        .map(|rs2| rs2.value);
 
     let final_access = vm.tracer().final_accesses();
     let end_cycle: u32 = vm.tracer().cycle().try_into().unwrap();
+    // The memory at the start of the segment.
+    let init_value = |vma: WordAddr| *start.memory.get(&vma).unwrap_or(&0);
 
-    let pi = PublicValues::new(
-        exit_code.unwrap_or(0),
-        vm.program().entry,
-        Tracer::SUBCYCLES_PER_INSN as u32,
-        vm.get_pc().into(),
-        end_cycle,
-        io_init.iter().map(|rec| rec.value).collect_vec(),
-    );
+    // Find the final public output values and cycles.
+    let output_final = output_init
+        .iter()
+        .map(|rec| {
+            let vma: WordAddr = rec.addr.into();
+            MemFinalRecord {
+                addr: rec.addr,
+                value: vm.peek_memory(vma),
+                cycle: *final_access.get(&vma).unwrap_or(&0),
+                init_value: init_value(vma),
+            }
+        })
+        .collect_vec();
 
     // Find the final register values and cycles.
     let reg_final = reg_init
@@ -105,6 +258,7 @@ fn emulate_program(
                     addr: rec.addr,
                     value: vm.peek_register(index),
                     cycle: *final_access.get(&vma).unwrap_or(&0),
+                    init_value: start.registers[index],
                 }
             } else {
                 // The table is padded beyond the number of registers.
@@ -112,6 +266,7 @@ fn emulate_program(
                     addr: rec.addr,
                     value: 0,
                     cycle: 0,
+                    init_value: 0,
                 }
             }
         })
@@ -126,10 +281,10 @@ fn emulate_program(
                 addr: rec.addr,
                 value: vm.peek_memory(vma),
                 cycle: *final_access.get(&vma).unwrap_or(&0),
+                init_value: init_value(vma),
             }
         })
         .collect_vec();
-    debug_memory_ranges(&vm, &mem_final);
 
     // Find the final public IO cycles.
     let io_final = io_init
@@ -138,30 +293,79 @@ fn emulate_program(
             addr: rec.addr,
             value: rec.value,
             cycle: *final_access.get(&rec.addr.into()).unwrap_or(&0),
+            init_value: rec.value,
         })
         .collect_vec();
 
-    let priv_io_final = zip(platform.hints.iter_addresses(), &hints)
+    let priv_io_final = zip(platform.hints.iter_addresses(), hints)
         .map(|(addr, &value)| MemFinalRecord {
             addr,
             value,
             cycle: *final_access.get(&addr.into()).unwrap_or(&0),
+            init_value: value,
         })
         .collect_vec();
 
+    let public_io = io_init.iter().map(|rec| rec.value).collect_vec();
+    let pi = if continuation {
+        PublicValues::new_segment(
+            exit_code.unwrap_or(0),
+            SegmentState {
+                pc: start.pc,
+                cycle: start.cycle.try_into().unwrap(),
+                public_output: output_final.iter().map(|rec| rec.init_value).collect_vec(),
+            },
+            SegmentState {
+                pc: vm.get_pc().into(),
+                cycle: end_cycle,
+                public_output: output_final.iter().map(|rec| rec.value).collect_vec(),
+            },
+            public_io,
+        )
+    } else {
+        PublicValues::new(
+            exit_code.unwrap_or(0),
+            vm.program().entry,
+            Tracer::SUBCYCLES_PER_INSN as u32,
+            vm.get_pc().into(),
+            end_cycle,
+            public_io,
+            output_final.iter().map(|rec| rec.value).collect_vec(),
+        )
+    };
+
+    debug_memory_ranges(
+        vm,
+        chain!(&mem_final, &io_final, &output_final, &priv_io_final),
+    );
+
     EmulationResult {
         pi,
-        exit_code,
-        all_records,
+        exit: GuestExit {
+            exit_code,
+            panic_message,
+        },
         final_mem_state: FinalMemState {
             reg: reg_final,
             io: io_final,
+            output: output_final,
             mem: mem_final,
             priv_io: priv_io_final,
         },
     }
 }
 
+/// The public state at the entry of `program`, where the first segment of an execution starts.
+///
+/// Registers and memory start as committed in the verifying key.
+pub fn initial_segment_state(program: &Program, platform: &Platform) -> SegmentState {
+    SegmentState {
+        pc: program.entry,
+        cycle: Tracer::SUBCYCLES_PER_INSN as u32,
+        public_output: vec![0; platform.public_output.iter_addresses().len()],
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum Preset {
     Ceno,
@@ -174,6 +378,7 @@ pub fn setup_platform(
     stack_size: u32,
     heap_size: u32,
     pub_io_size: u32,
+    pub_output_size: u32,
 ) -> Platform {
     let preset = match preset {
         Preset::Ceno => CENO_PLATFORM,
@@ -202,17 +407,49 @@ pub fn setup_platform(
         heap.start..heap_end as u32
     };
 
+    // The free-list allocator of ceno_rt halts at the heap end of its linker script, so the
+    // platform heap must extend to it.
+    if let Some(guest_heap_end) = program.addr_of("_eheap") {
+        assert!(
+            guest_heap_end <= heap.end,
+            "the guest heap ends at {guest_heap_end:#x}, after the platform heap at {:#x}; increase the heap size",
+            heap.end
+        );
+    }
+
+    // Detect the env as the program data in its segment, see `host_utils::load_env`.
+    let env_end = program
+        .image
+        .range(preset.env.clone())
+        .next_back()
+        .map_or(preset.env.start, |(&addr, _)| addr + WORD_SIZE as u32);
+
     Platform {
-        rom: program.base_address
-            ..program.base_address + (program.instructions.len() * WORD_SIZE) as u32,
+        rom: program.base_address..program.code_end(),
         prog_data,
         stack,
         heap,
-        public_io: preset.public_io.start..preset.public_io.start + pub_io_size.next_power_of_two(),
+        env: preset.env.start..env_end,
+        public_io: public_segment(&preset.public_io, pub_io_size),
+        public_output: public_segment(&preset.public_output, pub_output_size),
         ..preset
     }
 }
 
+/// The start of `segment`, sized to at least `size` bytes, which must fit in `segment`.
+///
+/// The tables of the public segments have a power-of-two number of words, and at least the
+/// minimal number of instances of a table, even without public input or output.
+fn public_segment(segment: &Range<u32>, size: u32) -> Range<u32> {
+    let min_size = (next_pow2_instance_padding(1) * WORD_SIZE) as u32;
+    let size = size.max(min_size).next_power_of_two();
+    assert!(
+        size <= segment.len() as u32,
+        "{size} bytes do not fit in the public segment {segment:#x?}"
+    );
+    segment.start..segment.start + size
+}
+
 fn init_mem(program: &Program, platform: &Platform) -> Vec<MemInitRecord> {
     let program_addrs = program.image.iter().map(|(addr, value)| MemInitRecord {
         addr: *addr,
@@ -286,6 +523,11 @@ fn generate_fixed_traces<E: ExtensionField>(
         &init_mem_state.reg,
         &init_mem_state.mem,
         &init_mem_state.io.iter().map(|rec| rec.addr).collect_vec(),
+        &init_mem_state
+            .output
+            .iter()
+            .map(|rec| rec.addr)
+            .collect_vec(),
     );
     system_config
         .dummy_config
@@ -294,59 +536,109 @@ fn generate_fixed_traces<E: ExtensionField>(
     zkvm_fixed_traces
 }
 
+/// The witness of a segment, generated from its steps as the emulator produces them.
+///
+/// The steps are assigned to the opcode circuits chunk by chunk, so that the trace of a segment
+/// is never held in memory as a whole.
+pub struct WitnessBuilder<'a, E: ExtensionField> {
+    system_config: &'a ConstraintSystemConfig<E>,
+    zkvm_witness: ZKVMWitnesses<E>,
+}
+
+impl<'a, E: ExtensionField> WitnessBuilder<'a, E> {
+    pub fn new(system_config: &'a ConstraintSystemConfig<E>) -> Self {
+        let mut builder = Self {
+            system_config,
+            zkvm_witness: ZKVMWitnesses::default(),
+        };
+        // Every opcode circuit has a witness, even without steps.
+        builder.push_steps(vec![]);
+        builder
+    }
+
+    /// Assign the next chunk of steps to the opcode circuits.
+    pub fn push_steps(&mut self, steps: Vec<StepRecord>) {
+        let system_config = self.system_config;
+        let dummy_records = system_config
+            .config
+            .assign_opcode_circuit(&system_config.zkvm_cs, &mut self.zkvm_witness, steps)
+            .unwrap();
+        system_config
+            .dummy_config
+            .assign_opcode_circuit(
+                &system_config.zkvm_cs,
+                &mut self.zkvm_witness,
+                dummy_records,
+            )
+            .unwrap();
+    }
+
+    /// Assign the table circuits, after all the steps of the segment.
+    pub fn finish(
+        self,
+        emul_result: &EmulationResult,
+        program: &Program,
+        is_mock_proving: bool,
+    ) -> ZKVMWitnesses<E> {
+        let Self {
+            system_config,
+            mut zkvm_witness,
+        } = self;
+        zkvm_witness.finalize_lk_multiplicities(is_mock_proving);
+
+        // assign table circuits
+        system_config
+            .config
+            .assign_table_circuit(&system_config.zkvm_cs, &mut zkvm_witness)
+            .unwrap();
+        system_config
+            .mmu_config
+            .assign_table_circuit(
+                &system_config.zkvm_cs,
+                &mut zkvm_witness,
+                &emul_result.final_mem_state.reg,
+                &emul_result.final_mem_state.mem,
+                &emul_result
+                    .final_mem_state
+                    .io
+                    .iter()
+                    .map(|rec| rec.cycle)
+                    .collect_vec(),
+                &emul_result
+                    .final_mem_state
+                    .output
+                    .iter()
+                    .map(|rec| rec.cycle)
+                    .collect_vec(),
+                &emul_result.final_mem_state.priv_io,
+            )
+            .unwrap();
+        // assign program circuit
+        zkvm_witness
+            .assign_table_circuit::<ProgramTableCircuit<E>>(
+                &system_config.zkvm_cs,
+                &system_config.prog_config,
+                program,
+            )
+            .unwrap();
+
+        zkvm_witness
+    }
+}
+
+/// Generate the witness of a segment from its steps, in chunks.
 pub fn generate_witness<E: ExtensionField>(
     system_config: &ConstraintSystemConfig<E>,
-    emul_result: EmulationResult,
+    steps: impl IntoIterator<Item = Vec<StepRecord>>,
+    emul_result: &EmulationResult,
     program: &Program,
     is_mock_proving: bool,
 ) -> ZKVMWitnesses<E> {
-    let mut zkvm_witness = ZKVMWitnesses::default();
-    // assign opcode circuits
-    let dummy_records = system_config
-        .config
-        .assign_opcode_circuit(
-            &system_config.zkvm_cs,
-            &mut zkvm_witness,
-            emul_result.all_records,
-        )
-        .unwrap();
-    system_config
-        .dummy_config
-        .assign_opcode_circuit(&system_config.zkvm_cs, &mut zkvm_witness, dummy_records)
-        .unwrap();
-    zkvm_witness.finalize_lk_multiplicities(is_mock_proving);
-
-    // assign table circuits
-    system_config
-        .config
-        .assign_table_circuit(&system_config.zkvm_cs, &mut zkvm_witness)
-        .unwrap();
-    system_config
-        .mmu_config
-        .assign_table_circuit(
-            &system_config.zkvm_cs,
-            &mut zkvm_witness,
-            &emul_result.final_mem_state.reg,
-            &emul_result.final_mem_state.mem,
-            &emul_result
-                .final_mem_state
-                .io
-                .iter()
-                .map(|rec| rec.cycle)
-                .collect_vec(),
-            &emul_result.final_mem_state.priv_io,
-        )
-        .unwrap();
-    // assign program circuit
-    zkvm_witness
-        .assign_table_circuit::<ProgramTableCircuit<E>>(
-            &system_config.zkvm_cs,
-            &system_config.prog_config,
-            program,
-        )
-        .unwrap();
-
-    zkvm_witness
+    let mut builder = WitnessBuilder::new(system_config);
+    for chunk in steps {
+        builder.push_steps(chunk);
+    }
+    builder.finish(emul_result, program, is_mock_proving)
 }
 
 // Encodes useful early return points of the e2e pipeline
@@ -357,9 +649,10 @@ pub enum Checkpoint {
     Complete,
 }
 
-// Currently handles state required by the sanity check in `bin/e2e.rs`
+// Currently handles state required by the sanity check in `bin/e2e.rs`:
+// the proof, its verifier, and how the program ended.
 // Future cases would require this to be an enum
-pub type IntermediateState<E, PCS> = (ZKVMProof<E, PCS>, ZKVMVerifier<E, PCS>);
+pub type IntermediateState<E, PCS> = (ZKVMProof<E, PCS>, ZKVMVerifier<E, PCS>, GuestExit);
 
 // Runs end-to-end pipeline, stopping at a certain checkpoint and yielding useful state.
 //
@@ -380,45 +673,14 @@ pub fn run_e2e_with_checkpoint<
     program: Program,
     platform: Platform,
     hints: Vec<u32>,
+    public_io: Vec<u32>,
     max_steps: usize,
     checkpoint: Checkpoint,
 ) -> (Option<IntermediateState<E, PCS>>, Box<dyn FnOnce()>) {
-    let mem_init = init_mem(&program, &platform);
-
-    let pub_io_len = platform.public_io.iter_addresses().len();
-    let program_params = ProgramParams {
-        platform: platform.clone(),
-        program_size: program.instructions.len(),
-        static_memory_len: mem_init.len(),
-        pub_io_len,
-    };
-
-    let program = Arc::new(program);
-    let system_config = construct_configs::<E>(program_params);
-    let reg_init = system_config.mmu_config.initial_registers();
-
-    // IO is not used in this program, but it must have a particular size at the moment.
-    let io_init = MemPadder::init_mem(platform.public_io.clone(), pub_io_len, &[]);
-
-    let init_full_mem = InitMemState {
-        mem: mem_init,
-        reg: reg_init,
-        io: io_init,
-        priv_io: vec![],
-    };
-
-    // Generate fixed traces
-    let zkvm_fixed_traces = generate_fixed_traces(&system_config, &init_full_mem, &program);
-
-    // Keygen
-    let pcs_param = PCS::setup(1 << MAX_NUM_VARIABLES).expect("Basefold PCS setup");
-    let (pp, vp) = PCS::trim(pcs_param, 1 << MAX_NUM_VARIABLES).expect("Basefold trim");
-    let pk = system_config
-        .zkvm_cs
-        .clone()
-        .key_gen::<PCS>(pp.clone(), vp.clone(), zkvm_fixed_traces.clone())
-        .expect("keygen failed");
+    let (system_config, init_full_mem, zkvm_fixed_traces, pk) =
+        setup_e2e::<E, PCS>(&program, &platform, &public_io, false);
     let vk = pk.get_vk();
+    let program = Arc::new(program);
 
     // Generate witness
     let is_mock_proving = std::env::var("MOCK_PROVING").is_ok();
@@ -441,21 +703,39 @@ pub fn run_e2e_with_checkpoint<
         );
     }
 
-    // Emulate program
-    let emul_result = emulate_program(program.clone(), max_steps, init_full_mem, &platform, hints);
-
-    // Clone some emul_result fields before consuming
-    let pi = emul_result.pi.clone();
-    let exit_code = emul_result.exit_code;
-
     if let Checkpoint::PrepWitnessGen = checkpoint {
+        // Emulate program, keeping the steps for the witness generation.
+        let mut steps = vec![];
+        let emul_result = emulate_program(
+            program.clone(),
+            max_steps,
+            &init_full_mem,
+            &platform,
+            &hints,
+            |chunk| steps.push(chunk),
+        );
         return (
             None,
-            Box::new(move || _ = generate_witness(&system_config, emul_result, &program, false)),
+            Box::new(move || {
+                _ = generate_witness(&system_config, steps, &emul_result, &program, false)
+            }),
         );
     }
 
-    let zkvm_witness = generate_witness(&system_config, emul_result, &program, is_mock_proving);
+    // Emulate program, generating the witness along the way
+    let mut witness_builder = WitnessBuilder::new(&system_config);
+    let emul_result = emulate_program(
+        program.clone(),
+        max_steps,
+        &init_full_mem,
+        &platform,
+        &hints,
+        |chunk| witness_builder.push_steps(chunk),
+    );
+    let pi = emul_result.pi.clone();
+    let exit = emul_result.exit.clone();
+
+    let zkvm_witness = witness_builder.finish(&emul_result, &program, is_mock_proving);
 
     // proving
     let prover = ZKVMProver::new(pk);
@@ -479,15 +759,139 @@ pub fn run_e2e_with_checkpoint<
 
     let verifier = ZKVMVerifier::new(vk);
 
-    run_e2e_verify(&verifier, zkvm_proof.clone(), exit_code, max_steps);
+    run_e2e_verify(&verifier, zkvm_proof.clone(), exit.exit_code, max_steps);
 
     if let Checkpoint::PrepSanityCheck = checkpoint {
-        return (Some((zkvm_proof, verifier)), Box::new(|| ()));
+        return (Some((zkvm_proof, verifier, exit)), Box::new(|| ()));
     }
 
     (None, Box::new(|| ()))
 }
 
+/// Prove the execution of `program` in segments of at most `segment_steps` steps each.
+///
+/// Return the proofs of the segments in order, and their verifier. The chain of proofs verifies
+/// with [`ZKVMVerifier::verify_segments`] from the [`initial_segment_state`] of the program.
+pub fn run_e2e_segments<
+    E: ExtensionField + LkMultiplicityKey,
+    PCS: PolynomialCommitmentScheme<E> + 'static,
+>(
+    program: Program,
+    platform: Platform,
+    hints: Vec<u32>,
+    public_io: Vec<u32>,
+    max_steps: usize,
+    segment_steps: usize,
+) -> (Vec<ZKVMProof<E, PCS>>, ZKVMVerifier<E, PCS>) {
+    let (system_config, init_full_mem, zkvm_fixed_traces, pk) =
+        setup_e2e::<E, PCS>(&program, &platform, &public_io, true);
+    let verifier = ZKVMVerifier::new(pk.get_vk());
+    let prover = ZKVMProver::new(pk);
+    let program = Arc::new(program);
+    let is_mock_proving = std::env::var("MOCK_PROVING").is_ok();
+
+    let mut emulator = SegmentEmulator::new(
+        program.clone(),
+        max_steps,
+        Some(segment_steps),
+        &init_full_mem,
+        &platform,
+        &hints,
+    );
+    let mut proofs = vec![];
+    loop {
+        // Emulate the next segment, generating its witness along the way
+        let mut witness_builder = WitnessBuilder::new(&system_config);
+        let Some(emul_result) = emulator.next_segment(WITNESS_CHUNK_STEPS, |chunk| {
+            witness_builder.push_steps(chunk)
+        }) else {
+            break;
+        };
+        let pi = emul_result.pi.clone();
+        let zkvm_witness = witness_builder.finish(&emul_result, &program, is_mock_proving);
+
+        if is_mock_proving {
+            MockProver::assert_satisfied_full(
+                &system_config.zkvm_cs,
+                zkvm_fixed_traces.clone(),
+                &zkvm_witness,
+                &pi,
+                &program,
+            );
+        }
+
+        let transcript = Transcript::new(b"riscv");
+        proofs.push(
+            prover
+                .create_proof(zkvm_witness, pi, transcript)
+                .expect("create_proof failed"),
+        );
+    }
+    tracing::info!("proved {} segments", proofs.len());
+
+    (proofs, verifier)
+}
+
+/// Build the circuits, the initial memory, and the keys to prove `program`.
+fn setup_e2e<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
+    program: &Program,
+    platform: &Platform,
+    public_io: &[u32],
+    continuation: bool,
+) -> (
+    ConstraintSystemConfig<E>,
+    InitMemState,
+    ZKVMFixedTraces<E>,
+    ZKVMProvingKey<E, PCS>,
+) {
+    let mem_init = init_mem(program, platform);
+
+    let pub_io_len = platform.public_io.iter_addresses().len();
+    let pub_output_len = platform.public_output.iter_addresses().len();
+    let program_params = ProgramParams {
+        platform: platform.clone(),
+        program_size: program.instructions.len(),
+        static_memory_len: mem_init.len(),
+        pub_io_len,
+        pub_output_len,
+        continuation,
+    };
+
+    let system_config = construct_configs::<E>(program_params);
+    let reg_init = system_config.mmu_config.initial_registers();
+
+    assert!(
+        public_io.len() <= pub_io_len,
+        "public input must fit in {} words",
+        pub_io_len
+    );
+    let io_init = MemPadder::init_mem(platform.public_io.clone(), pub_io_len, public_io);
+    // The public output starts zeroed; the guest fills it with `ceno_rt::commit`.
+    let output_init = MemPadder::init_mem(platform.public_output.clone(), pub_output_len, &[]);
+
+    let init_full_mem = InitMemState {
+        mem: mem_init,
+        reg: reg_init,
+        io: io_init,
+        output: output_init,
+        priv_io: vec![],
+    };
+
+    // Generate fixed traces
+    let zkvm_fixed_traces = generate_fixed_traces(&system_config, &init_full_mem, program);
+
+    // Keygen
+    let pcs_param = PCS::setup(1 << MAX_NUM_VARIABLES).expect("Basefold PCS setup");
+    let (pp, vp) = PCS::trim(pcs_param, 1 << MAX_NUM_VARIABLES).expect("Basefold trim");
+    let pk = system_config
+        .zkvm_cs
+        .clone()
+        .key_gen::<PCS>(pp, vp, zkvm_fixed_traces.clone())
+        .expect("keygen failed");
+
+    (system_config, init_full_mem, zkvm_fixed_traces, pk)
+}
+
 // Runs program emulation + witness generation + proving
 #[allow(clippy::too_many_arguments)]
 pub fn run_e2e_proof<E: ExtensionField + LkMultiplicityKey, PCS: PolynomialCommitmentScheme<E>>(
@@ -501,17 +905,47 @@ pub fn run_e2e_proof<E: ExtensionField + LkMultiplicityKey, PCS: PolynomialCommi
     zkvm_fixed_traces: ZKVMFixedTraces<E>,
     is_mock_proving: bool,
 ) -> ZKVMProof<E, PCS> {
-    // Emulate program
-    let emul_result = emulate_program(program.clone(), max_steps, init_full_mem, &platform, hints);
+    let (zkvm_proof, _) = prove_program(
+        &ZKVMProver::new(pk),
+        program,
+        max_steps,
+        &init_full_mem,
+        &platform,
+        &hints,
+        system_config,
+        &zkvm_fixed_traces,
+        is_mock_proving,
+    );
+    zkvm_proof
+}
 
-    // clone pi before consuming
+/// Emulate `program`, generate its witness and prove it. Return the proof and how the program
+/// ended.
+#[allow(clippy::too_many_arguments)]
+fn prove_program<E: ExtensionField + LkMultiplicityKey, PCS: PolynomialCommitmentScheme<E>>(
+    prover: &ZKVMProver<E, PCS>,
+    program: Arc<Program>,
+    max_steps: usize,
+    init_full_mem: &InitMemState,
+    platform: &Platform,
+    hints: &[u32],
+    system_config: &ConstraintSystemConfig<E>,
+    zkvm_fixed_traces: &ZKVMFixedTraces<E>,
+    is_mock_proving: bool,
+) -> (ZKVMProof<E, PCS>, GuestExit) {
+    // Emulate program, generating the witness along the way
+    let mut witness_builder = WitnessBuilder::new(system_config);
+    let emul_result = emulate_program(
+        program.clone(),
+        max_steps,
+        init_full_mem,
+        platform,
+        hints,
+        |chunk| witness_builder.push_steps(chunk),
+    );
     let pi = emul_result.pi.clone();
 
-    // Generate witness
-    let zkvm_witness = generate_witness(system_config, emul_result, &program, is_mock_proving);
-
-    // proving
-    let prover = ZKVMProver::new(pk);
+    let zkvm_witness = witness_builder.finish(&emul_result, &program, is_mock_proving);
 
     if is_mock_proving {
         MockProver::assert_satisfied_full(
@@ -525,9 +959,70 @@ pub fn run_e2e_proof<E: ExtensionField + LkMultiplicityKey, PCS: PolynomialCommi
     }
 
     let transcript = Transcript::new(b"riscv");
-    prover
+    let zkvm_proof = prover
         .create_proof(zkvm_witness, pi, transcript)
-        .expect("create_proof failed")
+        .expect("create_proof failed");
+    (zkvm_proof, emul_result.exit)
+}
+
+/// The circuits, the initial memory and the proving key of a program, set up once to prove
+/// any number of its executions.
+///
+/// The public input is part of the initial memory: it is fixed at setup, while the hints may
+/// change from one proof to the next.
+pub struct E2EProvingSetup<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
+    program: Arc<Program>,
+    platform: Platform,
+    system_config: ConstraintSystemConfig<E>,
+    init_full_mem: InitMemState,
+    zkvm_fixed_traces: ZKVMFixedTraces<E>,
+    prover: ZKVMProver<E, PCS>,
+}
+
+impl<E: ExtensionField + LkMultiplicityKey, PCS: PolynomialCommitmentScheme<E>>
+    E2EProvingSetup<E, PCS>
+{
+    pub fn new(program: Program, platform: Platform, public_io: &[u32]) -> Self {
+        let (system_config, init_full_mem, zkvm_fixed_traces, pk) =
+            setup_e2e::<E, PCS>(&program, &platform, public_io, false);
+        Self {
+            program: Arc::new(program),
+            platform,
+            system_config,
+            init_full_mem,
+            zkvm_fixed_traces,
+            prover: ZKVMProver::new(pk),
+        }
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn platform(&self) -> &Platform {
+        &self.platform
+    }
+
+    pub fn get_vk(&self) -> ZKVMVerifyingKey<E, PCS> {
+        self.prover.pk.get_vk()
+    }
+
+    /// Prove the execution of the program on `hints`, for at most `max_steps` steps.
+    ///
+    /// Return the proof, and the exit code and panic message of the program.
+    pub fn prove(&self, hints: &[u32], max_steps: usize) -> (ZKVMProof<E, PCS>, GuestExit) {
+        prove_program(
+            &self.prover,
+            self.program.clone(),
+            max_steps,
+            &self.init_full_mem,
+            &self.platform,
+            hints,
+            &self.system_config,
+            &self.zkvm_fixed_traces,
+            std::env::var("MOCK_PROVING").is_ok(),
+        )
+    }
 }
 
 pub fn run_e2e_verify<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
@@ -549,7 +1044,7 @@ pub fn run_e2e_verify<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
     }
 }
 
-fn debug_memory_ranges(vm: &VMState, mem_final: &[MemFinalRecord]) {
+fn debug_memory_ranges<'a>(vm: &VMState, mem_final: impl Iterator<Item = &'a MemFinalRecord>) {
     let accessed_addrs = vm
         .tracer()
         .final_accesses()
@@ -560,7 +1055,6 @@ fn debug_memory_ranges(vm: &VMState, mem_final: &[MemFinalRecord]) {
         .collect_vec();
 
     let handled_addrs = mem_final
-        .iter()
         .filter(|rec| rec.cycle != 0)
         .map(|rec| ByteAddr(rec.addr))
         .collect::<HashSet<_>>();
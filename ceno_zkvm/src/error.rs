@@ -1,4 +1,5 @@
 use mpcs::Error;
+use sumcheck::structs::SumCheckError;
 
 #[derive(Debug)]
 pub enum UtilError {
@@ -15,6 +16,8 @@ pub enum ZKVMError {
     FixedTraceNotFound(String),
     VerifyError(String),
     PCSError(Error),
+    SerializationError(String),
+    IOError(std::io::Error),
 }
 
 impl From<UtilError> for ZKVMError {
@@ -22,3 +25,9 @@ impl From<UtilError> for ZKVMError {
         Self::UtilError(error)
     }
 }
+
+impl From<SumCheckError> for ZKVMError {
+    fn from(error: SumCheckError) -> Self {
+        Self::VerifyError(format!("sumcheck: {error:?}"))
+    }
+}
@@ -13,6 +13,7 @@ use ff_ext::ExtensionField;
 use goldilocks::SmallField;
 
 use multilinear_extensions::virtual_poly::ArcMultilinearExtension;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
     circuit_builder::CircuitBuilder,
@@ -20,7 +21,11 @@ use crate::{
     structs::{ChallengeId, RAMType, WitnessId},
 };
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub enum Expression<E: ExtensionField> {
     /// WitIn(Id)
     WitIn(WitnessId),
@@ -785,17 +790,17 @@ pub struct WitIn {
     pub id: WitnessId,
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, Serialize, Deserialize)]
 pub struct StructuralWitIn {
     pub id: WitnessId,
     pub max_len: usize,
     pub offset: u32,
     pub multi_factor: usize,
 }
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Fixed(pub usize);
 
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Instance(pub usize);
 
 impl WitIn {
@@ -55,6 +55,8 @@ impl<E: ExtensionField> Expression<E> {
             .map(|Term { coeff, vars }| (vars, coeff))
             .into_group_map()
             .into_iter()
+            // sorted, so that the constraint system and its fingerprint are the same in every run
+            .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
             .map(|(vars, coeffs)| Term {
                 coeff: coeffs.into_iter().sum(),
                 vars,
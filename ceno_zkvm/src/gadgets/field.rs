@@ -0,0 +1,352 @@
+//! Arithmetic modulo a prime `p < 2^256`, on values held as 16 limbs of 16 bits.
+//!
+//! A congruence `a * b + Σ c ≡ Σ d (mod p)` holds iff `a * b + Σ c + k * p = q * p + Σ d` for
+//! some `q`, where `k` is the number of terms of `Σ d`. The offset `k * p` makes the quotient
+//! non-negative for reduced `d`, and both sides are evaluated exactly on 512 bits with the
+//! multiplication and addition of `UIntLimbs`.
+
+use std::fmt::Display;
+
+use ff_ext::ExtensionField;
+use itertools::{Itertools, izip};
+use num_bigint::BigUint;
+
+use crate::{
+    circuit_builder::CircuitBuilder,
+    error::ZKVMError,
+    expression::ToExpr,
+    instructions::riscv::constants::LIMB_BITS,
+    uint::{UIntLimbs, ValueAdd, ValueMul},
+    witness::LkMultiplicity,
+};
+
+pub type UInt256<E> = UIntLimbs<256, LIMB_BITS, E>;
+pub type UInt512<E> = UIntLimbs<512, LIMB_BITS, E>;
+
+/// The limbs of a value of `n` limbs, little-endian.
+pub fn to_limbs(x: &BigUint, n: usize) -> Vec<u16> {
+    let mut limbs = x
+        .iter_u32_digits()
+        .flat_map(|d| [d as u16, (d >> 16) as u16])
+        .collect_vec();
+    assert!(
+        limbs.iter().skip(n).all(|&limb| limb == 0),
+        "value exceeds {n} limbs"
+    );
+    limbs.resize(n, 0);
+    limbs
+}
+
+fn assert_limbs(lkm: &mut LkMultiplicity, limbs: &[u16]) {
+    limbs
+        .iter()
+        .for_each(|&limb| lkm.assert_ux::<16>(limb as u64));
+}
+
+/// Assign a value held in range-checked witnesses.
+pub fn assign_uint256<E: ExtensionField>(
+    instance: &mut [E::BaseField],
+    lkm: &mut LkMultiplicity,
+    x: &UInt256<E>,
+    value: &BigUint,
+) {
+    let limbs = to_limbs(value, UInt256::<E>::NUM_LIMBS);
+    assert_limbs(lkm, &limbs);
+    x.assign_limbs(instance, &limbs);
+}
+
+/// `a + b` on `n` limbs without overflow.
+fn add_outcome(a: &BigUint, b: &BigUint, n: usize, lkm: &mut LkMultiplicity) -> ValueAdd {
+    let (a, b) = (to_limbs(a, n), to_limbs(b, n));
+    let mut limbs = Vec::with_capacity(n);
+    let mut carries = Vec::with_capacity(n);
+    let mut carry = 0;
+    for (a, b) in izip!(a, b) {
+        let sum = a as u32 + b as u32 + carry;
+        limbs.push(sum as u16);
+        carry = sum >> LIMB_BITS;
+        carries.push(carry as u16);
+    }
+    assert_eq!(carries.pop(), Some(0), "addition overflows {n} limbs");
+    assert_limbs(lkm, &limbs);
+    ValueAdd { limbs, carries }
+}
+
+/// `a * b` of two 256-bit values on 512 bits.
+fn mul_outcome<E: ExtensionField>(a: &BigUint, b: &BigUint, lkm: &mut LkMultiplicity) -> ValueMul {
+    let n = UInt256::<E>::NUM_LIMBS;
+    let (a, b) = (to_limbs(a, n), to_limbs(b, n));
+    let mut columns = vec![0u64; 2 * n];
+    for (i, &a) in a.iter().enumerate() {
+        for (j, &b) in b.iter().enumerate() {
+            columns[i + j] += a as u64 * b as u64;
+        }
+    }
+    let mut limbs = Vec::with_capacity(2 * n);
+    let mut carries = Vec::with_capacity(2 * n);
+    let mut carry = 0;
+    for column in columns {
+        let sum = column + carry;
+        limbs.push(sum as u16);
+        carry = sum >> LIMB_BITS;
+        carries.push(carry);
+    }
+    assert_eq!(carries.pop(), Some(0), "product overflows 512 bits");
+    assert_limbs(lkm, &limbs);
+    ValueMul {
+        limbs,
+        carries,
+        max_carry_value: UInt256::<E>::MAX_DEGREE_2_MUL_CARRY_VALUE,
+    }
+}
+
+/// `a * b` on 512 bits, where constant operands are moved into witnesses.
+#[derive(Debug, Clone)]
+struct MulConfig<E: ExtensionField> {
+    /// The operands that were constants.
+    swapped: [Option<UInt256<E>>; 2],
+    product: UInt512<E>,
+}
+
+impl<E: ExtensionField> MulConfig<E> {
+    fn construct_circuit(
+        cb: &mut CircuitBuilder<E>,
+        a: &UInt256<E>,
+        b: &UInt256<E>,
+    ) -> Result<Self, ZKVMError> {
+        let (mut a, mut b) = (a.clone(), b.clone());
+        let (a_is_const, b_is_const) = (a.is_expr(), b.is_expr());
+        let product = a.mul::<512, _, _>(|| "mul", cb, &mut b, false)?;
+        Ok(MulConfig {
+            swapped: [a_is_const.then_some(a), b_is_const.then_some(b)],
+            product,
+        })
+    }
+
+    fn assign_instance(
+        &self,
+        instance: &mut [E::BaseField],
+        lkm: &mut LkMultiplicity,
+        a: &BigUint,
+        b: &BigUint,
+    ) -> Result<BigUint, ZKVMError> {
+        for (swapped, value) in izip!(&self.swapped, [a, b]) {
+            if let Some(x) = swapped {
+                assign_uint256(instance, lkm, x, value);
+            }
+        }
+        let product = mul_outcome::<E>(a, b, lkm);
+        self.product.assign_mul_outcome(instance, lkm, &product)?;
+        Ok(a * b)
+    }
+}
+
+/// A running sum of 512-bit values.
+#[derive(Debug, Clone)]
+struct SumConfig<E: ExtensionField> {
+    first: UInt512<E>,
+    /// The partial sums after each further term.
+    sums: Vec<UInt512<E>>,
+}
+
+impl<E: ExtensionField> SumConfig<E> {
+    fn construct_circuit(
+        cb: &mut CircuitBuilder<E>,
+        terms: Vec<UInt512<E>>,
+    ) -> Result<Self, ZKVMError> {
+        let mut terms = terms.into_iter();
+        let first = terms.next().expect("at least one term");
+        let mut sums: Vec<UInt512<E>> = vec![];
+        for (i, term) in terms.enumerate() {
+            let sum = sums
+                .last()
+                .unwrap_or(&first)
+                .add(|| format!("add_{i}"), cb, &term, false)?;
+            sums.push(sum);
+        }
+        Ok(SumConfig { first, sums })
+    }
+
+    fn output(&self) -> &UInt512<E> {
+        self.sums.last().unwrap_or(&self.first)
+    }
+
+    fn assign_instance(
+        &self,
+        instance: &mut [E::BaseField],
+        lkm: &mut LkMultiplicity,
+        terms: &[BigUint],
+    ) {
+        let n = UInt512::<E>::NUM_LIMBS;
+        let mut sum = terms[0].clone();
+        for (config, term) in izip!(&self.sums, &terms[1..]) {
+            config.assign_add_outcome(instance, &add_outcome(&sum, term, n, lkm));
+            sum += term;
+        }
+    }
+}
+
+/// Constrains `a * b + Σ c ≡ Σ d (mod p)` for values of 256 bits, where the product is optional.
+///
+/// Constants may be given as `UIntLimbs::from_const_unchecked`.
+#[derive(Debug, Clone)]
+pub struct FieldOpConfig<E: ExtensionField> {
+    modulus: BigUint,
+    num_d: usize,
+    product: Option<MulConfig<E>>,
+    quotient: UInt256<E>,
+    quotient_mul: MulConfig<E>,
+    lhs: SumConfig<E>,
+    rhs: SumConfig<E>,
+}
+
+impl<E: ExtensionField> FieldOpConfig<E> {
+    pub fn construct_circuit<NR: Into<String> + Display + Clone, N: FnOnce() -> NR>(
+        circuit_builder: &mut CircuitBuilder<E>,
+        name_fn: N,
+        modulus: &BigUint,
+        product: Option<(&UInt256<E>, &UInt256<E>)>,
+        c: &[&UInt256<E>],
+        d: &[&UInt256<E>],
+    ) -> Result<Self, ZKVMError> {
+        circuit_builder.namespace(name_fn, |cb| {
+            let widen = |x: &UInt256<E>| UInt512::from_exprs_unchecked(x.expr());
+            let n = UInt512::<E>::NUM_LIMBS;
+
+            let product = product
+                .map(|(a, b)| {
+                    cb.namespace(|| "product", |cb| MulConfig::construct_circuit(cb, a, b))
+                })
+                .transpose()?;
+            let quotient = UInt256::new(|| "quotient", cb)?;
+            let modulus_uint = UInt256::from_const_unchecked(to_limbs(modulus, n / 2));
+            let quotient_mul = cb.namespace(
+                || "quotient_mul",
+                |cb| MulConfig::construct_circuit(cb, &quotient, &modulus_uint),
+            )?;
+
+            let offset = modulus * d.len();
+            let lhs_terms = product
+                .iter()
+                .map(|product| product.product.clone())
+                .chain(c.iter().map(|&c| widen(c)))
+                .chain(
+                    (!d.is_empty()).then(|| UIntLimbs::from_const_unchecked(to_limbs(&offset, n))),
+                )
+                .collect_vec();
+            let rhs_terms = [quotient_mul.product.clone()]
+                .into_iter()
+                .chain(d.iter().map(|&d| widen(d)))
+                .collect_vec();
+            let lhs = cb.namespace(|| "lhs", |cb| SumConfig::construct_circuit(cb, lhs_terms))?;
+            let rhs = cb.namespace(|| "rhs", |cb| SumConfig::construct_circuit(cb, rhs_terms))?;
+
+            // All limbs are range checked, so the sides are equal as integers.
+            for (i, (l, r)) in izip!(lhs.output().expr(), rhs.output().expr()).enumerate() {
+                cb.require_equal(|| format!("limb_{i}"), l, r)?;
+            }
+
+            Ok(FieldOpConfig {
+                modulus: modulus.clone(),
+                num_d: d.len(),
+                product,
+                quotient,
+                quotient_mul,
+                lhs,
+                rhs,
+            })
+        })
+    }
+
+    pub fn assign_instance(
+        &self,
+        instance: &mut [E::BaseField],
+        lkm: &mut LkMultiplicity,
+        product: Option<(&BigUint, &BigUint)>,
+        c: &[&BigUint],
+        d: &[&BigUint],
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(d.len(), self.num_d);
+        let product = match (&self.product, product) {
+            (Some(config), Some((a, b))) => Some(config.assign_instance(instance, lkm, a, b)?),
+            (None, None) => None,
+            _ => panic!("mismatched product"),
+        };
+        let offset = &self.modulus * self.num_d;
+        let lhs_terms = product
+            .into_iter()
+            .chain(c.iter().map(|&c| c.clone()))
+            .chain((self.num_d > 0).then_some(offset))
+            .collect_vec();
+        let lhs: BigUint = lhs_terms.iter().sum();
+        let rhs: BigUint = d.iter().copied().sum();
+        assert!(lhs >= rhs, "unreduced terms");
+        let quotient = (&lhs - &rhs) / &self.modulus;
+        assert_eq!(
+            &quotient * &self.modulus + &rhs,
+            lhs,
+            "field operation does not hold"
+        );
+
+        assign_uint256(instance, lkm, &self.quotient, &quotient);
+        let modulus = self.modulus.clone();
+        let qp = self
+            .quotient_mul
+            .assign_instance(instance, lkm, &quotient, &modulus)?;
+        let rhs_terms = [qp]
+            .into_iter()
+            .chain(d.iter().map(|&d| d.clone()))
+            .collect_vec();
+
+        self.lhs.assign_instance(instance, lkm, &lhs_terms);
+        self.rhs.assign_instance(instance, lkm, &rhs_terms);
+        Ok(())
+    }
+}
+
+/// Constrains `x < p` by adding `2^256 - p` without overflow.
+#[derive(Debug, Clone)]
+pub struct AssertReducedConfig<E: ExtensionField> {
+    complement: BigUint,
+    sum: UInt256<E>,
+}
+
+impl<E: ExtensionField> AssertReducedConfig<E> {
+    pub fn construct_circuit<NR: Into<String> + Display + Clone, N: FnOnce() -> NR>(
+        circuit_builder: &mut CircuitBuilder<E>,
+        name_fn: N,
+        modulus: &BigUint,
+        x: &UInt256<E>,
+    ) -> Result<Self, ZKVMError> {
+        let complement = (BigUint::from(1u32) << 256) - modulus;
+        let complement_uint =
+            UInt256::from_const_unchecked(to_limbs(&complement, UInt256::<E>::NUM_LIMBS));
+        let sum = x.add(name_fn, circuit_builder, &complement_uint, false)?;
+        Ok(AssertReducedConfig { complement, sum })
+    }
+
+    pub fn assign_instance(
+        &self,
+        instance: &mut [E::BaseField],
+        lkm: &mut LkMultiplicity,
+        x: &BigUint,
+    ) {
+        let sum = add_outcome(x, &self.complement, UInt256::<E>::NUM_LIMBS, lkm);
+        self.sum.assign_add_outcome(instance, &sum);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_limbs;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_limbs() {
+        let x = (BigUint::from(1u32) << 255) + 0x1234_5678u32;
+        let limbs = to_limbs(&x, 16);
+        assert_eq!(&limbs[..3], &[0x5678, 0x1234, 0]);
+        assert_eq!(limbs[15], 0x8000);
+        assert_eq!(&limbs[3..15], &[0; 12]);
+    }
+}
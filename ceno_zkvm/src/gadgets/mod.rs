@@ -1,10 +1,12 @@
 mod div;
+mod field;
 mod is_lt;
 mod is_zero;
 mod signed;
 mod signed_ext;
 
 pub use div::DivConfig;
+pub use field::{AssertReducedConfig, FieldOpConfig, UInt256, UInt512, assign_uint256, to_limbs};
 pub use is_lt::{
     AssertLtConfig, AssertSignedLtConfig, InnerLtConfig, IsLtConfig, SignedLtConfig, cal_lt_diff,
 };
@@ -3,7 +3,7 @@ use ff_ext::ExtensionField;
 use multilinear_extensions::util::max_usable_threads;
 use rayon::{
     iter::{IndexedParallelIterator, ParallelIterator},
-    slice::ParallelSlice,
+    slice::{ParallelSlice, ParallelSliceMut},
 };
 use std::sync::Arc;
 
@@ -53,6 +53,21 @@ pub trait Instruction<E: ExtensionField> {
         num_witin: usize,
         steps: Vec<StepRecord>,
     ) -> Result<(RowMajorMatrix<E::BaseField>, LkMultiplicity), ZKVMError> {
+        let lk_multiplicity = LkMultiplicity::default();
+        let mut raw_witin =
+            RowMajorMatrix::<E::BaseField>::new(0, num_witin, Self::padding_strategy());
+        Self::append_instances(config, &mut raw_witin, &lk_multiplicity, &steps)?;
+
+        Ok((raw_witin, lk_multiplicity))
+    }
+
+    // assign instances giving a chunk of steps, as rows after those already in `raw_witin`
+    fn append_instances(
+        config: &Self::InstructionConfig,
+        raw_witin: &mut RowMajorMatrix<E::BaseField>,
+        lk_multiplicity: &LkMultiplicity,
+        steps: &[StepRecord],
+    ) -> Result<(), ZKVMError> {
         let nthreads = max_usable_threads();
         let num_instance_per_batch = if steps.len() > 256 {
             steps.len().div_ceil(nthreads)
@@ -60,10 +75,10 @@ pub trait Instruction<E: ExtensionField> {
             steps.len()
         }
         .max(1);
-        let lk_multiplicity = LkMultiplicity::default();
-        let mut raw_witin =
-            RowMajorMatrix::<E::BaseField>::new(steps.len(), num_witin, Self::padding_strategy());
-        let raw_witin_iter = raw_witin.par_batch_iter_mut(num_instance_per_batch);
+        let num_witin = raw_witin.num_col();
+        let raw_witin_iter = raw_witin
+            .append_rows(steps.len())
+            .par_chunks_mut(num_instance_per_batch * num_witin);
 
         raw_witin_iter
             .zip(steps.par_chunks(num_instance_per_batch))
@@ -77,8 +92,6 @@ pub trait Instruction<E: ExtensionField> {
                     })
                     .collect::<Vec<_>>()
             })
-            .collect::<Result<(), ZKVMError>>()?;
-
-        Ok((raw_witin, lk_multiplicity))
+            .collect::<Result<(), ZKVMError>>()
     }
 }
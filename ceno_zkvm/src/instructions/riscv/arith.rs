@@ -130,8 +130,9 @@ impl<E: ExtensionField, I: RIVInstruction> Instruction<E> for ArithInstruction<E
 
 #[cfg(test)]
 mod test {
-    use ceno_emul::{Change, StepRecord, encode_rv32};
+    use ceno_emul::{Change, StepRecord, disassemble::expand_compressed, encode_rv32};
     use goldilocks::GoldilocksExt2;
+    use itertools::Itertools;
 
     use super::*;
     use crate::{
@@ -160,6 +161,81 @@ mod test {
         verify::<SubOp>("underflow", 3, 11);
     }
 
+    #[test]
+    fn test_opcode_add_in_chunks() {
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = AddInstruction::<GoldilocksExt2>::construct_circuit(&mut cb).unwrap();
+        let num_witin = cb.cs.num_witin as usize;
+
+        let insn_code = encode_rv32(InsnKind::ADD, 2, 3, 4, 0);
+        let steps = (0..5)
+            .map(|i| {
+                StepRecord::new_r_instruction(
+                    4 * (i + 1) as u64,
+                    MOCK_PC_START + 4 * i,
+                    insn_code,
+                    i * 1000,
+                    i,
+                    Change::new(0, i * 1001),
+                    0,
+                )
+            })
+            .collect_vec();
+
+        let (expected_witin, expected_lkm) =
+            AddInstruction::<GoldilocksExt2>::assign_instances(&config, num_witin, steps.clone())
+                .unwrap();
+
+        // The same steps, assigned in chunks.
+        let (mut raw_witin, lkm) =
+            AddInstruction::<GoldilocksExt2>::assign_instances(&config, num_witin, vec![]).unwrap();
+        for chunk in steps.chunks(2) {
+            AddInstruction::<GoldilocksExt2>::append_instances(
+                &config,
+                &mut raw_witin,
+                &lkm,
+                chunk,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            raw_witin.iter_rows().collect_vec(),
+            expected_witin.iter_rows().collect_vec()
+        );
+        assert_eq!(
+            lkm.into_finalize_result(),
+            expected_lkm.into_finalize_result()
+        );
+    }
+
+    #[test]
+    fn test_opcode_add_compressed() {
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = AddInstruction::<GoldilocksExt2>::construct_circuit(&mut cb).unwrap();
+
+        // c.add a0, a1
+        let insn_code = expand_compressed(0x952e);
+        let (raw_witin, lkm) = AddInstruction::<GoldilocksExt2>::assign_instances(
+            &config,
+            cb.cs.num_witin as usize,
+            vec![StepRecord::new_r_instruction(
+                3,
+                MOCK_PC_START,
+                insn_code,
+                11,
+                2,
+                Change::new(11, 13),
+                0,
+            )],
+        )
+        .unwrap();
+
+        MockProver::assert_satisfied_raw(&cb, raw_witin, &[insn_code], None, Some(lkm));
+    }
+
     fn verify<I: RIVInstruction>(name: &'static str, rs1: u32, rs2: u32) {
         let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
         let mut cb = CircuitBuilder::new(&mut cs);
@@ -1,7 +1,6 @@
 use ceno_emul::{InsnKind, StepRecord};
 use ff_ext::ExtensionField;
 
-use super::constants::PC_STEP_SIZE;
 use crate::{
     chip_handler::RegisterExpr,
     circuit_builder::CircuitBuilder,
@@ -65,11 +64,13 @@ impl<E: ExtensionField> BInstructionConfig<E> {
             rs1.id.expr(),
             rs2.id.expr(),
             imm.expr(),
+            vm_state.compressed.expr(),
         ))?;
 
         // Branch program counter
-        let pc_offset =
-            branch_taken_bit.clone() * imm.expr() - branch_taken_bit * PC_STEP_SIZE + PC_STEP_SIZE;
+        let pc_offset = branch_taken_bit.clone() * imm.expr()
+            - branch_taken_bit * vm_state.pc_step()
+            + vm_state.pc_step();
         let next_pc = vm_state.next_pc.unwrap();
         circuit_builder.require_equal(
             || "pc_branch",
@@ -10,6 +10,9 @@ pub const INIT_CYCLE_IDX: usize = 3;
 pub const END_PC_IDX: usize = 4;
 pub const END_CYCLE_IDX: usize = 5;
 pub const PUBLIC_IO_IDX: usize = 6;
+pub const PUBLIC_OUTPUT_IDX: usize = 7;
+// The public output at the start of a segment, used in continuation mode only.
+pub const INIT_PUBLIC_OUTPUT_IDX: usize = 8;
 
 pub const LIMB_BITS: usize = 16;
 pub const LIMB_MASK: u32 = 0xFFFF;
@@ -199,6 +199,7 @@ impl<E: ExtensionField> DummyConfig<E> {
             rs1_id,
             rs2_id,
             imm.expr(),
+            vm_state.compressed.expr(),
         ))?;
 
         Ok(DummyConfig {
@@ -1,7 +1,14 @@
+mod bytes;
+mod ec;
 mod halt;
+mod keccak;
+mod sha256;
 
 use ceno_emul::InsnKind;
+pub use ec::{Ed25519AddInstruction, Secp256k1AddInstruction, Secp256k1DoubleInstruction};
 pub use halt::HaltInstruction;
+pub use keccak::KeccakInstruction;
+pub use sha256::{ShaCompressInstruction, ShaExtendInstruction};
 
 use super::{RIVInstruction, dummy::DummyInstruction};
 
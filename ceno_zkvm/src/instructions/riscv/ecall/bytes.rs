@@ -0,0 +1,186 @@
+//! Bitwise operations on values held as little-endian bytes, with one lookup per byte.
+//!
+//! Rotating a value of `N` bytes left by `8 * q + s` bits gives byte `j` from the low bits of
+//! byte `j - q` and the high `s` bits of byte `j - q - 1`, and both parts follow linearly from the
+//! lookups `shl[i] = (a[i] << s) & 0xff`. Shifts right are the same without the wrap around.
+
+use std::array;
+
+use ff::Field;
+use ff_ext::ExtensionField;
+use goldilocks::SmallField;
+use itertools::izip;
+
+use crate::{
+    circuit_builder::CircuitBuilder,
+    error::ZKVMError,
+    expression::{Expression, ToExpr, WitIn},
+    set_val,
+    witness::LkMultiplicity,
+};
+
+pub(super) type Bytes<const N: usize> = [WitIn; N];
+pub(super) type BytesExpr<E, const N: usize> = [Expression<E>; N];
+pub(super) type BytesValue<const N: usize> = [u8; N];
+
+pub(super) fn new_bytes<E: ExtensionField, const N: usize>(
+    cb: &mut CircuitBuilder<E>,
+    name: &str,
+) -> Bytes<N> {
+    array::from_fn(|i| cb.create_witin(|| format!("{name}_{i}")))
+}
+
+pub(super) fn bytes_expr<E: ExtensionField, const N: usize>(a: &Bytes<N>) -> BytesExpr<E, N> {
+    array::from_fn(|i| a[i].expr())
+}
+
+pub(super) fn assign_bytes<F: SmallField, const N: usize>(
+    instance: &mut [F],
+    a: &Bytes<N>,
+    value: &BytesValue<N>,
+) {
+    for (byte, v) in izip!(a, value) {
+        set_val!(instance, byte, *v as u64);
+    }
+}
+
+/// The little-endian value of the bytes.
+pub(super) fn compose<E: ExtensionField>(a: &[Expression<E>]) -> Expression<E> {
+    a.iter()
+        .rev()
+        .fold(Expression::ZERO, |acc, byte| acc * 256u64 + byte.clone())
+}
+
+/// `a ^ b`, byte by byte.
+pub(super) fn xor_bytes<E: ExtensionField, const N: usize>(
+    cb: &mut CircuitBuilder<E>,
+    name: &str,
+    a: &BytesExpr<E, N>,
+    b: &BytesExpr<E, N>,
+) -> Result<Bytes<N>, ZKVMError> {
+    let c = new_bytes(cb, name);
+    for (a, b, c) in izip!(a, b, &c) {
+        cb.lookup_xor_byte(a.clone(), b.clone(), c.expr())?;
+    }
+    Ok(c)
+}
+
+pub(super) fn assign_xor_bytes<F: SmallField, const N: usize>(
+    instance: &mut [F],
+    lk_multiplicity: &mut LkMultiplicity,
+    c: &Bytes<N>,
+    a: &BytesValue<N>,
+    b: &BytesValue<N>,
+) -> BytesValue<N> {
+    let value = array::from_fn(|i| {
+        lk_multiplicity.lookup_xor_byte(a[i] as u64, b[i] as u64);
+        a[i] ^ b[i]
+    });
+    assign_bytes(instance, c, &value);
+    value
+}
+
+/// `a & b`, byte by byte.
+pub(super) fn and_bytes<E: ExtensionField, const N: usize>(
+    cb: &mut CircuitBuilder<E>,
+    name: &str,
+    a: &BytesExpr<E, N>,
+    b: &BytesExpr<E, N>,
+) -> Result<Bytes<N>, ZKVMError> {
+    let c = new_bytes(cb, name);
+    for (a, b, c) in izip!(a, b, &c) {
+        cb.lookup_and_byte(a.clone(), b.clone(), c.expr())?;
+    }
+    Ok(c)
+}
+
+pub(super) fn assign_and_bytes<F: SmallField, const N: usize>(
+    instance: &mut [F],
+    lk_multiplicity: &mut LkMultiplicity,
+    c: &Bytes<N>,
+    a: &BytesValue<N>,
+    b: &BytesValue<N>,
+) -> BytesValue<N> {
+    let value = array::from_fn(|i| {
+        lk_multiplicity.lookup_and_byte(a[i] as u64, b[i] as u64);
+        a[i] & b[i]
+    });
+    assign_bytes(instance, c, &value);
+    value
+}
+
+/// `!a`, byte by byte.
+pub(super) fn not_bytes<E: ExtensionField, const N: usize>(a: &BytesExpr<E, N>) -> BytesExpr<E, N> {
+    a.clone().map(|a| Expression::from(0xffu64) - a)
+}
+
+/// `(a << s) & 0xff`, byte by byte.
+pub(super) fn shl_bytes<E: ExtensionField, const N: usize>(
+    cb: &mut CircuitBuilder<E>,
+    name: &str,
+    a: &BytesExpr<E, N>,
+    s: u32,
+) -> Result<Bytes<N>, ZKVMError> {
+    let c = new_bytes(cb, name);
+    for (a, c) in izip!(a, &c) {
+        cb.lookup_shl_byte(a.clone(), s.into(), c.expr())?;
+    }
+    Ok(c)
+}
+
+pub(super) fn assign_shl_bytes<F: SmallField, const N: usize>(
+    instance: &mut [F],
+    lk_multiplicity: &mut LkMultiplicity,
+    c: &Bytes<N>,
+    a: &BytesValue<N>,
+    s: u32,
+) {
+    let value: BytesValue<N> = array::from_fn(|i| {
+        lk_multiplicity.lookup_shl_byte(a[i] as u64, s as u64);
+        a[i] << s
+    });
+    assign_bytes(instance, c, &value);
+}
+
+/// `a` rotated left by `r` bits, where `shl` is `(a << (r % 8)) & 0xff` if the rotation is not
+/// by whole bytes.
+pub(super) fn rotate_bytes<E: ExtensionField, const N: usize>(
+    a: &BytesExpr<E, N>,
+    shl: Option<&Bytes<N>>,
+    r: u32,
+) -> BytesExpr<E, N> {
+    let q = (r / 8) as usize;
+    array::from_fn(|j| {
+        let lo = (j + N - q) % N;
+        let hi = (lo + N - 1) % N;
+        match shl {
+            None => a[lo].clone(),
+            Some(shl) => shl[lo].expr() + shifted_out(&a[hi], &shl[hi], r % 8),
+        }
+    })
+}
+
+/// `a` shifted right by `r` bits, where `shl` is `(a << (8 - r % 8)) & 0xff` if the shift is not
+/// by whole bytes.
+pub(super) fn shr_bytes<E: ExtensionField, const N: usize>(
+    a: &BytesExpr<E, N>,
+    shl: Option<&Bytes<N>>,
+    r: u32,
+) -> BytesExpr<E, N> {
+    let q = (r / 8) as usize;
+    array::from_fn(|j| match shl {
+        None if j + q < N => a[j + q].clone(),
+        Some(shl) if j + q < N => {
+            // The high bits come from the next byte, the low bits are shifted out of this one.
+            let hi = shl.get(j + q + 1).map_or(Expression::ZERO, |hi| hi.expr());
+            hi + shifted_out(&a[j + q], &shl[j + q], 8 - r % 8)
+        }
+        _ => Expression::ZERO,
+    })
+}
+
+/// The high `s` bits of `a`, given `shl = (a << s) & 0xff`.
+fn shifted_out<E: ExtensionField>(a: &Expression<E>, shl: &WitIn, s: u32) -> Expression<E> {
+    let inv_256 = Expression::Constant(E::BaseField::from(256).invert().unwrap());
+    inv_256 * (a.clone() * (1u64 << s) - shl.expr())
+}
@@ -0,0 +1,1120 @@
+//! The elliptic curve syscalls `SECP256K1_ADD`, `SECP256K1_DOUBLE` and `ED25519_ADD`.
+//!
+//! Coordinates are held as 256-bit `UIntLimbs`, and the formulas of the group laws are
+//! constrained as congruences modulo the base field, with divisions turned into
+//! multiplications: `λ * (x2 - x1) ≡ y2 - y1` rather than `λ = (y2 - y1) / (x2 - x1)`. Divisors
+//! are shown to be invertible where this fixes the quotient, and the output coordinates are
+//! shown to be reduced. The inputs of ed25519 are shown to be on the curve.
+
+use std::marker::PhantomData;
+
+use ceno_emul::{
+    EC_FIELD_WORDS, EC_POINT_WORDS, ED25519_ADD, ED25519_D, ED25519_P, Platform, SECP256K1_ADD,
+    SECP256K1_DOUBLE, SECP256K1_P, StepRecord, WORD_SIZE, WriteOp,
+};
+use ff_ext::ExtensionField;
+use itertools::{Itertools, izip};
+use num_bigint::BigUint;
+
+use super::sha256::{ArgConfig, ecall_config};
+use crate::{
+    circuit_builder::CircuitBuilder,
+    error::ZKVMError,
+    expression::{Expression, ToExpr},
+    gadgets::{AssertReducedConfig, FieldOpConfig, UInt256, assign_uint256, to_limbs},
+    instructions::{
+        Instruction,
+        riscv::{ecall_insn::EcallInstructionConfig, insn_base::WriteMEM},
+    },
+    witness::LkMultiplicity,
+};
+
+type Point<E> = [UInt256<E>; 2];
+type PointValue = [BigUint; 2];
+
+fn new_point<E: ExtensionField>(
+    cb: &mut CircuitBuilder<E>,
+    name: &str,
+) -> Result<Point<E>, ZKVMError> {
+    Ok([
+        UInt256::new(|| format!("{name}_x"), cb)?,
+        UInt256::new(|| format!("{name}_y"), cb)?,
+    ])
+}
+
+fn assign_point<E: ExtensionField>(
+    instance: &mut [E::BaseField],
+    lk_multiplicity: &mut LkMultiplicity,
+    point: &Point<E>,
+    value: &PointValue,
+) {
+    for (coordinate, value) in izip!(point, value) {
+        assign_uint256(instance, lk_multiplicity, coordinate, value);
+    }
+}
+
+fn constant<E: ExtensionField>(value: &BigUint) -> UInt256<E> {
+    UInt256::from_const_unchecked(to_limbs(value, UInt256::<E>::NUM_LIMBS))
+}
+
+/// The memory words of a point, `x` then `y`, with two limbs per word.
+fn point_words<E: ExtensionField>(point: &Point<E>) -> Vec<Expression<E>> {
+    point
+        .iter()
+        .flat_map(|coordinate| {
+            coordinate
+                .expr()
+                .into_iter()
+                .tuples()
+                .map(|(lo, hi)| lo + hi * (1u64 << 16))
+        })
+        .collect_vec()
+}
+
+/// The point held in the words before or after the memory operations.
+fn point_value(ops: &[WriteOp], after: bool) -> PointValue {
+    let words = ops
+        .iter()
+        .map(|op| {
+            if after {
+                op.value.after
+            } else {
+                op.value.before
+            }
+        })
+        .collect_vec();
+    assert_eq!(words.len(), EC_POINT_WORDS);
+    let (x, y) = words.split_at(EC_FIELD_WORDS);
+    [BigUint::from_slice(x), BigUint::from_slice(y)]
+}
+
+/// Update the point at `ptr` from `before` to `after`.
+fn point_writes<E: ExtensionField>(
+    cb: &mut CircuitBuilder<E>,
+    ecall_cfg: &EcallInstructionConfig,
+    ptr: &ArgConfig<E>,
+    before: &Point<E>,
+    after: &Point<E>,
+) -> Result<Vec<WriteMEM>, ZKVMError> {
+    izip!(point_words(before), point_words(after))
+        .enumerate()
+        .map(|(i, (before, after))| {
+            WriteMEM::construct_circuit(
+                cb,
+                ptr.ptr.address_expr() + (i * WORD_SIZE) as u64,
+                before,
+                after,
+                ecall_cfg.ts,
+            )
+        })
+        .collect()
+}
+
+fn array_of<T: std::fmt::Debug, const N: usize>(
+    values: impl Iterator<Item = Result<T, ZKVMError>>,
+) -> Result<[T; N], ZKVMError> {
+    Ok(values.collect::<Result<Vec<_>, _>>()?.try_into().unwrap())
+}
+
+fn sub(a: &BigUint, b: &BigUint, modulus: &BigUint) -> BigUint {
+    (a + modulus - b) % modulus
+}
+
+fn inv(a: &BigUint, modulus: &BigUint) -> BigUint {
+    a.modpow(&(modulus - 2u32), modulus)
+}
+
+/// The sum of the points of a short Weierstrass curve on a line of slope `λ` through `(x1, y1)`
+/// and a point with x coordinate `x2`: `x3 = λ^2 - x1 - x2` and `y3 = λ * (x1 - x3) - y1`.
+struct LineSumConfig<E: ExtensionField> {
+    output: Point<E>,
+    /// `x1 - x3`.
+    e: UInt256<E>,
+    x3_op: FieldOpConfig<E>,
+    e_op: FieldOpConfig<E>,
+    y3_op: FieldOpConfig<E>,
+    reduced: [AssertReducedConfig<E>; 2],
+}
+
+impl<E: ExtensionField> LineSumConfig<E> {
+    fn construct_circuit(
+        cb: &mut CircuitBuilder<E>,
+        modulus: &BigUint,
+        lambda: &UInt256<E>,
+        [x1, y1]: [&UInt256<E>; 2],
+        x2: &UInt256<E>,
+    ) -> Result<Self, ZKVMError> {
+        let output = new_point(cb, "output")?;
+        let [x3, y3] = &output;
+        let e = UInt256::new(|| "e", cb)?;
+
+        // λ^2 ≡ x3 + x1 + x2
+        let x3_op = FieldOpConfig::construct_circuit(
+            cb,
+            || "x3",
+            modulus,
+            Some((lambda, lambda)),
+            &[],
+            &[x3, x1, x2],
+        )?;
+        // e + x3 ≡ x1
+        let e_op = FieldOpConfig::construct_circuit(cb, || "e", modulus, None, &[&e, x3], &[x1])?;
+        // λ * e ≡ y3 + y1
+        let y3_op =
+            FieldOpConfig::construct_circuit(cb, || "y3", modulus, Some((lambda, &e)), &[], &[
+                y3, y1,
+            ])?;
+        let reduced = [
+            AssertReducedConfig::construct_circuit(cb, || "x3_reduced", modulus, x3)?,
+            AssertReducedConfig::construct_circuit(cb, || "y3_reduced", modulus, y3)?,
+        ];
+
+        Ok(LineSumConfig {
+            output,
+            e,
+            x3_op,
+            e_op,
+            y3_op,
+            reduced,
+        })
+    }
+
+    fn assign_instance(
+        &self,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        modulus: &BigUint,
+        lambda: &BigUint,
+        [x1, y1]: [&BigUint; 2],
+        x2: &BigUint,
+    ) -> Result<PointValue, ZKVMError> {
+        let x3 = sub(&sub(&(lambda * lambda), x1, modulus), x2, modulus);
+        let e = sub(x1, &x3, modulus);
+        let y3 = sub(&(lambda * &e % modulus), y1, modulus);
+
+        let output = [x3, y3];
+        assign_point(instance, lk_multiplicity, &self.output, &output);
+        assign_uint256(instance, lk_multiplicity, &self.e, &e);
+        let [x3, y3] = &output;
+
+        self.x3_op
+            .assign_instance(instance, lk_multiplicity, Some((lambda, lambda)), &[], &[
+                x3, x1, x2,
+            ])?;
+        self.e_op
+            .assign_instance(instance, lk_multiplicity, None, &[&e, x3], &[x1])?;
+        self.y3_op
+            .assign_instance(instance, lk_multiplicity, Some((lambda, &e)), &[], &[
+                y3, y1,
+            ])?;
+        for (reduced, value) in izip!(&self.reduced, &output) {
+            reduced.assign_instance(instance, lk_multiplicity, value);
+        }
+        Ok(output)
+    }
+}
+
+pub struct Secp256k1AddConfig<E: ExtensionField> {
+    ecall_cfg: EcallInstructionConfig,
+    p_ptr: ArgConfig<E>,
+    q_ptr: ArgConfig<E>,
+
+    p: Point<E>,
+    q: Point<E>,
+    /// `x2 - x1`, and its inverse.
+    dx: UInt256<E>,
+    dx_inv: UInt256<E>,
+    lambda: UInt256<E>,
+    dx_op: FieldOpConfig<E>,
+    dx_inv_op: FieldOpConfig<E>,
+    lambda_op: FieldOpConfig<E>,
+    line_sum: LineSumConfig<E>,
+    mem_writes: Vec<WriteMEM>,
+}
+
+/// The `SECP256K1_ADD` syscall, which adds the point pointed to by `x11` to the point pointed to
+/// by `x10`. The points must have different x coordinates.
+pub struct Secp256k1AddInstruction<E>(PhantomData<E>);
+
+impl<E: ExtensionField> Instruction<E> for Secp256k1AddInstruction<E> {
+    type InstructionConfig = Secp256k1AddConfig<E>;
+
+    fn name() -> String {
+        "ECALL_SECP256K1_ADD".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let modulus = BigUint::from_slice(&SECP256K1_P);
+        let ecall_cfg = ecall_config(cb, SECP256K1_ADD)?;
+        let p_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg0(), ecall_cfg.ts)?;
+        let q_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg1(), ecall_cfg.ts)?;
+
+        let p = new_point(cb, "p")?;
+        let q = new_point(cb, "q")?;
+        let [x1, y1] = &p;
+        let [x2, y2] = &q;
+        let dx = UInt256::new(|| "dx", cb)?;
+        let dx_inv = UInt256::new(|| "dx_inv", cb)?;
+        let lambda = UInt256::new(|| "lambda", cb)?;
+        let one = constant(&BigUint::from(1u32));
+
+        // dx + x1 ≡ x2
+        let dx_op =
+            FieldOpConfig::construct_circuit(cb, || "dx", &modulus, None, &[&dx, x1], &[x2])?;
+        // dx * dx_inv ≡ 1
+        let dx_inv_op = FieldOpConfig::construct_circuit(
+            cb,
+            || "dx_inv",
+            &modulus,
+            Some((&dx, &dx_inv)),
+            &[],
+            &[&one],
+        )?;
+        // λ * dx + y1 ≡ y2
+        let lambda_op = FieldOpConfig::construct_circuit(
+            cb,
+            || "lambda",
+            &modulus,
+            Some((&lambda, &dx)),
+            &[y1],
+            &[y2],
+        )?;
+        let line_sum = cb.namespace(
+            || "line_sum",
+            |cb| LineSumConfig::construct_circuit(cb, &modulus, &lambda, [x1, y1], x2),
+        )?;
+
+        // Update p, and read q.
+        let mut mem_writes = point_writes(cb, &ecall_cfg, &p_ptr, &p, &line_sum.output)?;
+        mem_writes.extend(point_writes(cb, &ecall_cfg, &q_ptr, &q, &q)?);
+
+        Ok(Secp256k1AddConfig {
+            ecall_cfg,
+            p_ptr,
+            q_ptr,
+            p,
+            q,
+            dx,
+            dx_inv,
+            lambda,
+            dx_op,
+            dx_inv_op,
+            lambda_op,
+            line_sum,
+            mem_writes,
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(step.rs1().unwrap().value, SECP256K1_ADD);
+        let ops = step.syscall().expect("syscall step");
+        let modulus = BigUint::from_slice(&SECP256K1_P);
+
+        config
+            .ecall_cfg
+            .assign_instance::<E>(instance, lk_multiplicity, step)?;
+        config
+            .p_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[0])?;
+        config
+            .q_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[1])?;
+
+        let (p_ops, q_ops) = ops.mem_ops.split_at(EC_POINT_WORDS);
+        let p = point_value(p_ops, false);
+        let q = point_value(q_ops, false);
+        assign_point(instance, lk_multiplicity, &config.p, &p);
+        assign_point(instance, lk_multiplicity, &config.q, &q);
+        let [x1, y1] = &p;
+        let [x2, y2] = &q;
+
+        let dx = sub(x2, x1, &modulus);
+        let dx_inv = inv(&dx, &modulus);
+        let lambda = sub(y2, y1, &modulus) * &dx_inv % &modulus;
+        assign_uint256(instance, lk_multiplicity, &config.dx, &dx);
+        assign_uint256(instance, lk_multiplicity, &config.dx_inv, &dx_inv);
+        assign_uint256(instance, lk_multiplicity, &config.lambda, &lambda);
+
+        config
+            .dx_op
+            .assign_instance(instance, lk_multiplicity, None, &[&dx, x1], &[x2])?;
+        config.dx_inv_op.assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((&dx, &dx_inv)),
+            &[],
+            &[&BigUint::from(1u32)],
+        )?;
+        config.lambda_op.assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((&lambda, &dx)),
+            &[y1],
+            &[y2],
+        )?;
+        let output = config.line_sum.assign_instance(
+            instance,
+            lk_multiplicity,
+            &modulus,
+            &lambda,
+            [x1, y1],
+            x2,
+        )?;
+        assert_eq!(output, point_value(p_ops, true), "secp256k1 add mismatch");
+
+        for (writer, op) in izip!(&config.mem_writes, &ops.mem_ops) {
+            writer.assign_op(instance, lk_multiplicity, step.cycle(), op)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Secp256k1DoubleConfig<E: ExtensionField> {
+    ecall_cfg: EcallInstructionConfig,
+    p_ptr: ArgConfig<E>,
+
+    p: Point<E>,
+    /// `2 * y`, and its inverse.
+    two_y: UInt256<E>,
+    two_y_inv: UInt256<E>,
+    /// `x^2`.
+    xx: UInt256<E>,
+    lambda: UInt256<E>,
+    two_y_op: FieldOpConfig<E>,
+    two_y_inv_op: FieldOpConfig<E>,
+    xx_op: FieldOpConfig<E>,
+    lambda_op: FieldOpConfig<E>,
+    line_sum: LineSumConfig<E>,
+    mem_writes: Vec<WriteMEM>,
+}
+
+/// The `SECP256K1_DOUBLE` syscall, which doubles the point pointed to by `x10`. The point must
+/// not have `y = 0`.
+pub struct Secp256k1DoubleInstruction<E>(PhantomData<E>);
+
+impl<E: ExtensionField> Instruction<E> for Secp256k1DoubleInstruction<E> {
+    type InstructionConfig = Secp256k1DoubleConfig<E>;
+
+    fn name() -> String {
+        "ECALL_SECP256K1_DOUBLE".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let modulus = BigUint::from_slice(&SECP256K1_P);
+        let ecall_cfg = ecall_config(cb, SECP256K1_DOUBLE)?;
+        let p_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg0(), ecall_cfg.ts)?;
+
+        let p = new_point(cb, "p")?;
+        let [x, y] = &p;
+        let two_y = UInt256::new(|| "two_y", cb)?;
+        let two_y_inv = UInt256::new(|| "two_y_inv", cb)?;
+        let xx = UInt256::new(|| "xx", cb)?;
+        let lambda = UInt256::new(|| "lambda", cb)?;
+        let one = constant(&BigUint::from(1u32));
+
+        // two_y ≡ y + y
+        let two_y_op =
+            FieldOpConfig::construct_circuit(cb, || "two_y", &modulus, None, &[&two_y], &[y, y])?;
+        // two_y * two_y_inv ≡ 1
+        let two_y_inv_op = FieldOpConfig::construct_circuit(
+            cb,
+            || "two_y_inv",
+            &modulus,
+            Some((&two_y, &two_y_inv)),
+            &[],
+            &[&one],
+        )?;
+        // x * x ≡ xx
+        let xx_op =
+            FieldOpConfig::construct_circuit(cb, || "xx", &modulus, Some((x, x)), &[], &[&xx])?;
+        // λ * two_y ≡ 3 * xx
+        let lambda_op = FieldOpConfig::construct_circuit(
+            cb,
+            || "lambda",
+            &modulus,
+            Some((&lambda, &two_y)),
+            &[],
+            &[&xx, &xx, &xx],
+        )?;
+        let line_sum = cb.namespace(
+            || "line_sum",
+            |cb| LineSumConfig::construct_circuit(cb, &modulus, &lambda, [x, y], x),
+        )?;
+
+        let mem_writes = point_writes(cb, &ecall_cfg, &p_ptr, &p, &line_sum.output)?;
+
+        Ok(Secp256k1DoubleConfig {
+            ecall_cfg,
+            p_ptr,
+            p,
+            two_y,
+            two_y_inv,
+            xx,
+            lambda,
+            two_y_op,
+            two_y_inv_op,
+            xx_op,
+            lambda_op,
+            line_sum,
+            mem_writes,
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(step.rs1().unwrap().value, SECP256K1_DOUBLE);
+        let ops = step.syscall().expect("syscall step");
+        let modulus = BigUint::from_slice(&SECP256K1_P);
+
+        config
+            .ecall_cfg
+            .assign_instance::<E>(instance, lk_multiplicity, step)?;
+        config
+            .p_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[0])?;
+
+        let p = point_value(&ops.mem_ops, false);
+        assign_point(instance, lk_multiplicity, &config.p, &p);
+        let [x, y] = &p;
+
+        let two_y = (y + y) % &modulus;
+        let two_y_inv = inv(&two_y, &modulus);
+        let xx = x * x % &modulus;
+        let lambda = BigUint::from(3u32) * &xx * &two_y_inv % &modulus;
+        assign_uint256(instance, lk_multiplicity, &config.two_y, &two_y);
+        assign_uint256(instance, lk_multiplicity, &config.two_y_inv, &two_y_inv);
+        assign_uint256(instance, lk_multiplicity, &config.xx, &xx);
+        assign_uint256(instance, lk_multiplicity, &config.lambda, &lambda);
+
+        config
+            .two_y_op
+            .assign_instance(instance, lk_multiplicity, None, &[&two_y], &[y, y])?;
+        config.two_y_inv_op.assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((&two_y, &two_y_inv)),
+            &[],
+            &[&BigUint::from(1u32)],
+        )?;
+        config
+            .xx_op
+            .assign_instance(instance, lk_multiplicity, Some((x, x)), &[], &[&xx])?;
+        config.lambda_op.assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((&lambda, &two_y)),
+            &[],
+            &[&xx, &xx, &xx],
+        )?;
+        let output = config.line_sum.assign_instance(
+            instance,
+            lk_multiplicity,
+            &modulus,
+            &lambda,
+            [x, y],
+            x,
+        )?;
+        assert_eq!(
+            output,
+            point_value(&ops.mem_ops, true),
+            "secp256k1 double mismatch"
+        );
+
+        for (writer, op) in izip!(&config.mem_writes, &ops.mem_ops) {
+            writer.assign_op(instance, lk_multiplicity, step.cycle(), op)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Shows that a point is on ed25519: `-x^2 + y^2 = 1 + d * x^2 * y^2`.
+struct Ed25519OnCurveConfig<E: ExtensionField> {
+    /// `x^2`, `y^2` and `x^2 * y^2`.
+    squares: [UInt256<E>; 3],
+    square_ops: [FieldOpConfig<E>; 3],
+    curve_op: FieldOpConfig<E>,
+}
+
+impl<E: ExtensionField> Ed25519OnCurveConfig<E> {
+    fn construct_circuit(
+        cb: &mut CircuitBuilder<E>,
+        modulus: &BigUint,
+        d: &UInt256<E>,
+        [x, y]: [&UInt256<E>; 2],
+    ) -> Result<Self, ZKVMError> {
+        let squares = array_of((0..3).map(|i| UInt256::new(|| format!("square_{i}"), cb)))?;
+        let [xx, yy, xxyy] = &squares;
+        let factors = [(x, x), (y, y), (xx, yy)];
+        let square_ops = array_of(izip!(&factors, &squares).enumerate().map(
+            |(i, (&factors, square))| {
+                FieldOpConfig::construct_circuit(
+                    cb,
+                    || format!("square_{i}"),
+                    modulus,
+                    Some(factors),
+                    &[],
+                    &[square],
+                )
+            },
+        ))?;
+        let one = constant(&BigUint::from(1u32));
+        // d * xxyy + xx + 1 ≡ yy
+        let curve_op = FieldOpConfig::construct_circuit(
+            cb,
+            || "curve",
+            modulus,
+            Some((d, xxyy)),
+            &[xx, &one],
+            &[yy],
+        )?;
+
+        Ok(Ed25519OnCurveConfig {
+            squares,
+            square_ops,
+            curve_op,
+        })
+    }
+
+    fn assign_instance(
+        &self,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        modulus: &BigUint,
+        d: &BigUint,
+        [x, y]: [&BigUint; 2],
+    ) -> Result<(), ZKVMError> {
+        let xx = x * x % modulus;
+        let yy = y * y % modulus;
+        let xxyy = &xx * &yy % modulus;
+        let factors = [(x, x), (y, y), (&xx, &yy)];
+        let squares = [&xx, &yy, &xxyy];
+        for (config, op, &value, &(a, b)) in
+            izip!(&self.squares, &self.square_ops, &squares, &factors)
+        {
+            assign_uint256(instance, lk_multiplicity, config, value);
+            op.assign_instance(instance, lk_multiplicity, Some((a, b)), &[], &[value])?;
+        }
+        self.curve_op.assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((d, &xxyy)),
+            &[&xx, &BigUint::from(1u32)],
+            &[&yy],
+        )
+    }
+}
+
+pub struct Ed25519AddConfig<E: ExtensionField> {
+    ecall_cfg: EcallInstructionConfig,
+    p_ptr: ArgConfig<E>,
+    q_ptr: ArgConfig<E>,
+
+    p: Point<E>,
+    q: Point<E>,
+    output: Point<E>,
+    /// The products `x1 * x2`, `y1 * y2`, `x1 * y2` and `y1 * x2`.
+    products: [UInt256<E>; 4],
+    /// `x1 * x2 * y1 * y2`.
+    tu: UInt256<E>,
+    /// `d * x1 * x2 * y1 * y2`.
+    k: UInt256<E>,
+    /// The inverses of `1 + k` and `1 - k`.
+    den_invs: [UInt256<E>; 2],
+    on_curve: [Ed25519OnCurveConfig<E>; 2],
+    product_ops: [FieldOpConfig<E>; 4],
+    tu_op: FieldOpConfig<E>,
+    k_op: FieldOpConfig<E>,
+    den_inv_ops: [FieldOpConfig<E>; 2],
+    x3_op: FieldOpConfig<E>,
+    y3_op: FieldOpConfig<E>,
+    reduced: [AssertReducedConfig<E>; 2],
+    mem_writes: Vec<WriteMEM>,
+}
+
+impl<E: ExtensionField> Ed25519AddConfig<E> {
+    /// Assign the points `p` and `q` with their sum, and return the sum.
+    fn assign_sum(
+        &self,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        p: &PointValue,
+        q: &PointValue,
+    ) -> Result<PointValue, ZKVMError> {
+        let modulus = BigUint::from_slice(&ED25519_P);
+        let d = BigUint::from_slice(&ED25519_D);
+        let one = BigUint::from(1u32);
+        assign_point(instance, lk_multiplicity, &self.p, p);
+        assign_point(instance, lk_multiplicity, &self.q, q);
+        for (on_curve, [x, y]) in izip!(&self.on_curve, [p, q]) {
+            on_curve.assign_instance(instance, lk_multiplicity, &modulus, &d, [x, y])?;
+        }
+        let [x1, y1] = p;
+        let [x2, y2] = q;
+
+        let factors = [(x1, x2), (y1, y2), (x1, y2), (y1, x2)];
+        let products = factors.map(|(a, b)| a * b % &modulus);
+        for (config, op, value, &(a, b)) in
+            izip!(&self.products, &self.product_ops, &products, &factors)
+        {
+            assign_uint256(instance, lk_multiplicity, config, value);
+            op.assign_instance(instance, lk_multiplicity, Some((a, b)), &[], &[value])?;
+        }
+        let [t, u, v, w] = &products;
+
+        let tu = t * u % &modulus;
+        assign_uint256(instance, lk_multiplicity, &self.tu, &tu);
+        self.tu_op
+            .assign_instance(instance, lk_multiplicity, Some((t, u)), &[], &[&tu])?;
+        let k = &d * &tu % &modulus;
+        assign_uint256(instance, lk_multiplicity, &self.k, &k);
+        self.k_op
+            .assign_instance(instance, lk_multiplicity, Some((&d, &tu)), &[], &[&k])?;
+
+        let x_den_inv = inv(&((&one + &k) % &modulus), &modulus);
+        let y_den_inv = inv(&sub(&one, &k, &modulus), &modulus);
+        assign_uint256(instance, lk_multiplicity, &self.den_invs[0], &x_den_inv);
+        assign_uint256(instance, lk_multiplicity, &self.den_invs[1], &y_den_inv);
+        self.den_inv_ops[0].assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((&x_den_inv, &k)),
+            &[&x_den_inv],
+            &[&one],
+        )?;
+        self.den_inv_ops[1].assign_instance(
+            instance,
+            lk_multiplicity,
+            Some((&y_den_inv, &k)),
+            &[&one],
+            &[&y_den_inv],
+        )?;
+
+        let x3 = (v + w) * &x_den_inv % &modulus;
+        let y3 = (u + t) * &y_den_inv % &modulus;
+        self.x3_op
+            .assign_instance(instance, lk_multiplicity, Some((&x3, &k)), &[&x3], &[v, w])?;
+        self.y3_op
+            .assign_instance(instance, lk_multiplicity, Some((&y3, &k)), &[u, t], &[&y3])?;
+        let output = [x3, y3];
+        assign_point(instance, lk_multiplicity, &self.output, &output);
+        for (reduced, value) in izip!(&self.reduced, &output) {
+            reduced.assign_instance(instance, lk_multiplicity, value);
+        }
+        Ok(output)
+    }
+}
+
+/// The `ED25519_ADD` syscall, which adds the point pointed to by `x11` to the point pointed to
+/// by `x10`, with the complete formulas `x3 = (x1 * y2 + y1 * x2) / (1 + k)` and
+/// `y3 = (y1 * y2 + x1 * x2) / (1 - k)` where `k = d * x1 * x2 * y1 * y2`. The points must be on
+/// the curve, which makes the divisors invertible.
+pub struct Ed25519AddInstruction<E>(PhantomData<E>);
+
+impl<E: ExtensionField> Instruction<E> for Ed25519AddInstruction<E> {
+    type InstructionConfig = Ed25519AddConfig<E>;
+
+    fn name() -> String {
+        "ECALL_ED25519_ADD".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let modulus = BigUint::from_slice(&ED25519_P);
+        let ecall_cfg = ecall_config(cb, ED25519_ADD)?;
+        let p_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg0(), ecall_cfg.ts)?;
+        let q_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg1(), ecall_cfg.ts)?;
+
+        let p = new_point(cb, "p")?;
+        let q = new_point(cb, "q")?;
+        let output = new_point(cb, "output")?;
+        let [x1, y1] = &p;
+        let [x2, y2] = &q;
+        let [x3, y3] = &output;
+        let d = constant(&BigUint::from_slice(&ED25519_D));
+        let one = constant(&BigUint::from(1u32));
+
+        let on_curve = [
+            cb.namespace(
+                || "p_on_curve",
+                |cb| Ed25519OnCurveConfig::construct_circuit(cb, &modulus, &d, [x1, y1]),
+            )?,
+            cb.namespace(
+                || "q_on_curve",
+                |cb| Ed25519OnCurveConfig::construct_circuit(cb, &modulus, &d, [x2, y2]),
+            )?,
+        ];
+
+        let factors = [(x1, x2), (y1, y2), (x1, y2), (y1, x2)];
+        let products =
+            array_of((0..factors.len()).map(|i| UInt256::new(|| format!("product_{i}"), cb)))?;
+        let product_ops = array_of(izip!(&factors, &products).enumerate().map(
+            |(i, (&factors, product))| {
+                FieldOpConfig::construct_circuit(
+                    cb,
+                    || format!("product_{i}"),
+                    &modulus,
+                    Some(factors),
+                    &[],
+                    &[product],
+                )
+            },
+        ))?;
+        let [t, u, v, w] = &products;
+
+        let tu = UInt256::new(|| "tu", cb)?;
+        let tu_op =
+            FieldOpConfig::construct_circuit(cb, || "tu", &modulus, Some((t, u)), &[], &[&tu])?;
+        let k = UInt256::new(|| "k", cb)?;
+        let k_op =
+            FieldOpConfig::construct_circuit(cb, || "k", &modulus, Some((&d, &tu)), &[], &[&k])?;
+
+        let den_invs = array_of((0..2).map(|i| UInt256::new(|| format!("den_inv_{i}"), cb)))?;
+        let [x_den_inv, y_den_inv] = &den_invs;
+        let den_inv_ops = [
+            // x_den_inv * k + x_den_inv ≡ 1
+            FieldOpConfig::construct_circuit(
+                cb,
+                || "x_den_inv",
+                &modulus,
+                Some((x_den_inv, &k)),
+                &[x_den_inv],
+                &[&one],
+            )?,
+            // y_den_inv * k + 1 ≡ y_den_inv
+            FieldOpConfig::construct_circuit(
+                cb,
+                || "y_den_inv",
+                &modulus,
+                Some((y_den_inv, &k)),
+                &[&one],
+                &[y_den_inv],
+            )?,
+        ];
+
+        // x3 * k + x3 ≡ v + w
+        let x3_op =
+            FieldOpConfig::construct_circuit(cb, || "x3", &modulus, Some((x3, &k)), &[x3], &[
+                v, w,
+            ])?;
+        // y3 * k + u + t ≡ y3
+        let y3_op =
+            FieldOpConfig::construct_circuit(cb, || "y3", &modulus, Some((y3, &k)), &[u, t], &[
+                y3,
+            ])?;
+        let reduced = [
+            AssertReducedConfig::construct_circuit(cb, || "x3_reduced", &modulus, x3)?,
+            AssertReducedConfig::construct_circuit(cb, || "y3_reduced", &modulus, y3)?,
+        ];
+
+        // Update p, and read q.
+        let mut mem_writes = point_writes(cb, &ecall_cfg, &p_ptr, &p, &output)?;
+        mem_writes.extend(point_writes(cb, &ecall_cfg, &q_ptr, &q, &q)?);
+
+        Ok(Ed25519AddConfig {
+            ecall_cfg,
+            p_ptr,
+            q_ptr,
+            p,
+            q,
+            output,
+            products,
+            tu,
+            k,
+            den_invs,
+            on_curve,
+            product_ops,
+            tu_op,
+            k_op,
+            den_inv_ops,
+            x3_op,
+            y3_op,
+            reduced,
+            mem_writes,
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(step.rs1().unwrap().value, ED25519_ADD);
+        let ops = step.syscall().expect("syscall step");
+
+        config
+            .ecall_cfg
+            .assign_instance::<E>(instance, lk_multiplicity, step)?;
+        config
+            .p_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[0])?;
+        config
+            .q_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[1])?;
+
+        let (p_ops, q_ops) = ops.mem_ops.split_at(EC_POINT_WORDS);
+        let output = config.assign_sum(
+            instance,
+            lk_multiplicity,
+            &point_value(p_ops, false),
+            &point_value(q_ops, false),
+        )?;
+        assert_eq!(output, point_value(p_ops, true), "ed25519 add mismatch");
+
+        for (writer, op) in izip!(&config.mem_writes, &ops.mem_ops) {
+            writer.assign_op(instance, lk_multiplicity, step.cycle(), op)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ceno_emul::{
+        CENO_PLATFORM, EC_POINT_WORDS, ED25519_ADD, InsnKind, Platform, Program, SECP256K1_ADD,
+        SECP256K1_DOUBLE, StepRecord, VMState, WORD_SIZE, encode_rv32, encode_rv32u,
+    };
+    use ff_ext::ff::Field;
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+    use itertools::Itertools;
+    use num_bigint::BigUint;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::{
+        Ed25519AddInstruction, PointValue, Secp256k1AddInstruction, Secp256k1DoubleInstruction,
+        point_value,
+    };
+    use crate::{
+        circuit_builder::{CircuitBuilder, ConstraintSystem},
+        instructions::Instruction,
+        scheme::mock_prover::MockProver,
+        witness::LkMultiplicity,
+    };
+
+    fn point(x: &str, y: &str) -> PointValue {
+        [x, y].map(|c| BigUint::parse_bytes(c.as_bytes(), 16).unwrap())
+    }
+
+    fn secp256k1_g() -> PointValue {
+        point(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        )
+    }
+
+    fn ed25519_b() -> PointValue {
+        point(
+            "216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a",
+            "6666666666666666666666666666666666666666666666666666666666666658",
+        )
+    }
+
+    /// Run `p = first(p, q); p = second(p, q)` with the given syscalls, with `q` at `q_offset`
+    /// bytes from `p`. Return the steps, or `None` if the emulator traps.
+    fn run_ec(
+        p: &PointValue,
+        q: &PointValue,
+        q_offset: u32,
+        [first, second]: [u32; 2],
+    ) -> (Option<Vec<StepRecord>>, Vec<ceno_emul::Instruction>) {
+        let p_ptr = CENO_PLATFORM.heap.start;
+        let q_ptr = p_ptr + q_offset;
+        let load_immediate =
+            |rd: usize, imm: u32| encode_rv32u(InsnKind::ADDI, 0, 0, rd as u32, imm);
+        let instructions = vec![
+            load_immediate(Platform::reg_arg0(), p_ptr),
+            load_immediate(Platform::reg_arg1(), q_ptr),
+            load_immediate(Platform::reg_ecall(), first),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            load_immediate(Platform::reg_ecall(), second),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            load_immediate(Platform::reg_ecall(), Platform::ecall_halt()),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+        ];
+        let pc = CENO_PLATFORM.pc_base();
+        let program = Program::new(pc, pc, instructions.clone(), Default::default());
+        let mut vm = VMState::new(CENO_PLATFORM, program.into());
+
+        for (ptr, point) in [(p_ptr, p), (q_ptr, q)] {
+            let words = point.iter().flat_map(|c| {
+                let mut digits = c.to_u32_digits();
+                digits.resize(EC_POINT_WORDS / 2, 0);
+                digits
+            });
+            for (i, word) in words.enumerate() {
+                vm.init_memory((ptr + (i * WORD_SIZE) as u32).into(), word);
+            }
+        }
+
+        (
+            vm.iter_until_halt().collect::<Result<_, _>>().ok(),
+            instructions,
+        )
+    }
+
+    /// Run `p = first(p, g); p = second(p, g)` from `p = g`, and return the steps of the syscalls.
+    fn ec_steps(
+        g: &PointValue,
+        first: u32,
+        second: u32,
+    ) -> (StepRecord, StepRecord, Vec<ceno_emul::Instruction>) {
+        let (steps, program) = run_ec(g, g, (EC_POINT_WORDS * WORD_SIZE) as u32, [first, second]);
+        let steps = steps.unwrap();
+        (steps[3].clone(), steps[5].clone(), program)
+    }
+
+    fn output(step: &StepRecord) -> PointValue {
+        point_value(&step.syscall().unwrap().mem_ops[..EC_POINT_WORDS], true)
+    }
+
+    fn assert_satisfied<I: Instruction<GoldilocksExt2>>(
+        step: StepRecord,
+        program: &[ceno_emul::Instruction],
+    ) {
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = cb.namespace(|| "ec", I::construct_circuit).unwrap();
+
+        let (raw_witin, lkm) =
+            I::assign_instances(&config, cb.cs.num_witin as usize, vec![step]).unwrap();
+        MockProver::assert_satisfied_raw(&cb, raw_witin, program, None, Some(lkm));
+    }
+
+    #[test]
+    fn test_secp256k1_double() {
+        let (step, _, program) = ec_steps(&secp256k1_g(), SECP256K1_DOUBLE, SECP256K1_ADD);
+        assert_eq!(
+            output(&step),
+            point(
+                "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+                "1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a",
+            )
+        );
+        assert_satisfied::<Secp256k1DoubleInstruction<_>>(step, &program);
+    }
+
+    #[test]
+    fn test_secp256k1_add() {
+        let (_, step, program) = ec_steps(&secp256k1_g(), SECP256K1_DOUBLE, SECP256K1_ADD);
+        assert_eq!(
+            output(&step),
+            point(
+                "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+                "388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672",
+            )
+        );
+
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = cb
+            .namespace(
+                || "secp256k1_add",
+                Secp256k1AddInstruction::construct_circuit,
+            )
+            .unwrap();
+
+        let (raw_witin, lkm) =
+            Secp256k1AddInstruction::assign_instances(&config, cb.cs.num_witin as usize, vec![
+                step,
+            ])
+            .unwrap();
+        MockProver::assert_satisfied_raw(
+            &cb,
+            raw_witin.clone(),
+            &program,
+            None,
+            Some(lkm.deep_clone()),
+        );
+
+        // A wrong slope is rejected.
+        let mut raw_witin = raw_witin;
+        let limb = config.lambda.wits_in().unwrap()[0];
+        for row in raw_witin.iter_mut() {
+            row[limb.id as usize] += Goldilocks::from(1);
+        }
+        let wits_in = raw_witin
+            .into_mles()
+            .into_iter()
+            .map(|v| v.into())
+            .collect_vec();
+        assert!(MockProver::run(&cb, &wits_in, &program, Some(lkm)).is_err());
+    }
+
+    fn ed25519_2b() -> PointValue {
+        point(
+            "36ab384c9f5a046c3d043b7d1833e7ac080d8e4515d7a45f83c5a14e2843ce0e",
+            "2260cdf3092329c21da25ee8c9a21f5697390f51643851560e5f46ae6af8a3c9",
+        )
+    }
+
+    #[test]
+    fn test_ed25519_add() {
+        let (double, add, program) = ec_steps(&ed25519_b(), ED25519_ADD, ED25519_ADD);
+        assert_eq!(output(&double), ed25519_2b());
+        assert_eq!(
+            output(&add),
+            point(
+                "67ae9c4a22928f491ff4ae743edac83a6343981981624886ac62485fd3f8e25c",
+                "1267b1d177ee69aba126a18e60269ef79f16ec176724030402c3684878f5b4d4",
+            )
+        );
+        assert_satisfied::<Ed25519AddInstruction<_>>(double, &program);
+        assert_satisfied::<Ed25519AddInstruction<_>>(add, &program);
+    }
+
+    #[test]
+    fn test_ed25519_add_identity() {
+        // p = O + B, then p = B + B.
+        let (steps, program) = run_ec(
+            &point("0", "1"),
+            &ed25519_b(),
+            (EC_POINT_WORDS * WORD_SIZE) as u32,
+            [ED25519_ADD; 2],
+        );
+        let steps = steps.unwrap();
+        assert_eq!(output(&steps[3]), ed25519_b());
+        assert_eq!(output(&steps[5]), ed25519_2b());
+        assert_satisfied::<Ed25519AddInstruction<_>>(steps[3].clone(), &program);
+        assert_satisfied::<Ed25519AddInstruction<_>>(steps[5].clone(), &program);
+    }
+
+    #[test]
+    fn test_ed25519_add_off_curve() {
+        let off_curve = point("1", "1");
+        let point_bytes = (EC_POINT_WORDS * WORD_SIZE) as u32;
+
+        // The emulator traps on a point off the curve, and on overlapping points.
+        let (steps, _) = run_ec(&off_curve, &ed25519_b(), point_bytes, [ED25519_ADD; 2]);
+        assert!(steps.is_none());
+        let (steps, _) = run_ec(&ed25519_b(), &ed25519_b(), 0, [ED25519_ADD; 2]);
+        assert!(steps.is_none());
+
+        // The circuit has no witness for the sum of a point off the curve.
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = cb
+            .namespace(|| "ed25519_add", Ed25519AddInstruction::construct_circuit)
+            .unwrap();
+        let mut instance = vec![Goldilocks::ZERO; cb.cs.num_witin as usize];
+        let assigned = panic::catch_unwind(AssertUnwindSafe(|| {
+            config.assign_sum(
+                &mut instance,
+                &mut LkMultiplicity::default(),
+                &off_curve,
+                &ed25519_b(),
+            )
+        }));
+        assert!(assigned.is_err());
+    }
+}
@@ -0,0 +1,536 @@
+//! The Keccak-f[1600] permutation of the `KECCAK_PERMUTE` syscall.
+//!
+//! The state is held as 200 bytes, so that each step of a round is a lookup per byte: the XORs
+//! of theta, chi and iota in the XOR table, the `!b & c` of chi in the AND table, and the
+//! rotations of theta and rho in the SHL table.
+
+use std::{array, marker::PhantomData};
+
+use ceno_emul::{KECCAK_PERMUTE, KECCAK_WORDS, Platform, StepRecord, Tracer, WORD_SIZE};
+use ff_ext::ExtensionField;
+use goldilocks::SmallField;
+use itertools::{Itertools, izip};
+
+use super::bytes::{
+    Bytes, BytesExpr, BytesValue, and_bytes, assign_and_bytes, assign_bytes, assign_shl_bytes,
+    assign_xor_bytes, bytes_expr, compose, new_bytes, not_bytes, rotate_bytes, shl_bytes,
+    xor_bytes,
+};
+use crate::{
+    chip_handler::RegisterChipOperations,
+    circuit_builder::CircuitBuilder,
+    error::ZKVMError,
+    expression::{ToExpr, WitIn},
+    gadgets::AssertLtConfig,
+    instructions::{
+        Instruction,
+        riscv::{constants::UInt, ecall_insn::EcallInstructionConfig, insn_base::WriteMEM},
+    },
+    set_val,
+    uint::Value,
+    witness::LkMultiplicity,
+};
+
+const ROUNDS: usize = 24;
+const LANES: usize = 25;
+const LANE_BYTES: usize = 8;
+
+const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation of lane `x + 5 * y` in rho.
+const RHO_OFFSETS: [u32; LANES] = [
+    0, 1, 62, 28, 27, //
+    36, 44, 6, 55, 20, //
+    3, 10, 43, 25, 39, //
+    41, 45, 15, 21, 8, //
+    18, 2, 61, 56, 14, //
+];
+
+/// The lane where pi moves lane `x + 5 * y`.
+fn pi_lane(lane: usize) -> usize {
+    let (x, y) = (lane % 5, lane / 5);
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
+
+type Lane = Bytes<LANE_BYTES>;
+type LaneExpr<E> = BytesExpr<E, LANE_BYTES>;
+type LaneValue = BytesValue<LANE_BYTES>;
+
+fn rotate_value(a: &LaneValue, r: u32) -> LaneValue {
+    u64::from_le_bytes(*a).rotate_left(r).to_le_bytes()
+}
+
+/// The witness of one round of the permutation.
+struct RoundConfig {
+    /// The XORs of the lanes of each column in theta, ending with the column parity.
+    column_xors: Vec<[Lane; 4]>,
+    /// The parities shifted by one bit, to rotate them.
+    parity_shl: Vec<Lane>,
+    /// The parity of the neighbour columns, by column.
+    theta_d: Vec<Lane>,
+    theta: Vec<Lane>,
+    /// The shifted lanes for rho, unless rotated by whole bytes.
+    rho_shl: Vec<Option<Lane>>,
+    chi_and_not: Vec<Lane>,
+    chi: Vec<Lane>,
+    /// XORs with the non-zero bytes of the round constant.
+    iota: [Option<WitIn>; LANE_BYTES],
+}
+
+impl RoundConfig {
+    fn construct_circuit<E: ExtensionField>(
+        cb: &mut CircuitBuilder<E>,
+        round: usize,
+        state: &[LaneExpr<E>],
+    ) -> Result<(Self, Vec<LaneExpr<E>>), ZKVMError> {
+        // Theta.
+        let mut column_xors = Vec::with_capacity(5);
+        let mut parity = Vec::with_capacity(5);
+        for x in 0..5 {
+            let mut acc = state[x].clone();
+            let mut xors = vec![];
+            for y in 1..5 {
+                let xor = xor_bytes(cb, &format!("column_{x}_xor_{y}"), &acc, &state[x + 5 * y])?;
+                acc = bytes_expr(&xor);
+                xors.push(xor);
+            }
+            column_xors.push(xors.try_into().unwrap());
+            parity.push(acc);
+        }
+        let parity_shl = (0..5)
+            .map(|x| shl_bytes(cb, &format!("parity_{x}_shl"), &parity[x], 1))
+            .collect::<Result<Vec<_>, _>>()?;
+        let theta_d = (0..5)
+            .map(|x| {
+                let rotated = rotate_bytes(&parity[(x + 1) % 5], Some(&parity_shl[(x + 1) % 5]), 1);
+                xor_bytes(cb, &format!("theta_d_{x}"), &parity[(x + 4) % 5], &rotated)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let theta = (0..LANES)
+            .map(|lane| {
+                xor_bytes(
+                    cb,
+                    &format!("theta_{lane}"),
+                    &state[lane],
+                    &bytes_expr(&theta_d[lane % 5]),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Rho and pi.
+        let rho_shl = (0..LANES)
+            .map(|lane| match RHO_OFFSETS[lane] % 8 {
+                0 => Ok(None),
+                s => shl_bytes(cb, &format!("rho_{lane}_shl"), &bytes_expr(&theta[lane]), s)
+                    .map(Some),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut permuted = vec![None; LANES];
+        for lane in 0..LANES {
+            permuted[pi_lane(lane)] = Some(rotate_bytes(
+                &bytes_expr(&theta[lane]),
+                rho_shl[lane].as_ref(),
+                RHO_OFFSETS[lane],
+            ));
+        }
+        let permuted = permuted.into_iter().map(Option::unwrap).collect_vec();
+
+        // Chi.
+        let mut chi_and_not = Vec::with_capacity(LANES);
+        let mut chi = Vec::with_capacity(LANES);
+        for lane in 0..LANES {
+            let (x, y) = (lane % 5, lane / 5);
+            let and_not = and_bytes(
+                cb,
+                &format!("chi_{lane}_and_not"),
+                &not_bytes(&permuted[(x + 1) % 5 + 5 * y]),
+                &permuted[(x + 2) % 5 + 5 * y],
+            )?;
+            chi.push(xor_bytes(
+                cb,
+                &format!("chi_{lane}"),
+                &permuted[lane],
+                &bytes_expr(&and_not),
+            )?);
+            chi_and_not.push(and_not);
+        }
+
+        // Iota.
+        let mut next = chi.iter().map(bytes_expr).collect_vec();
+        let rc = ROUND_CONSTANTS[round].to_le_bytes();
+        let iota =
+            array::from_fn(|i| (rc[i] != 0).then(|| cb.create_witin(|| format!("iota_{i}"))));
+        for (i, (out, rc)) in izip!(&iota, rc).enumerate() {
+            if let Some(out) = out {
+                cb.lookup_xor_byte(next[0][i].clone(), rc.into(), out.expr())?;
+                next[0][i] = out.expr();
+            }
+        }
+
+        Ok((
+            RoundConfig {
+                column_xors,
+                parity_shl,
+                theta_d,
+                theta,
+                rho_shl,
+                chi_and_not,
+                chi,
+                iota,
+            },
+            next,
+        ))
+    }
+
+    fn assign_instance<F: SmallField>(
+        &self,
+        instance: &mut [F],
+        lk_multiplicity: &mut LkMultiplicity,
+        round: usize,
+        state: &[LaneValue],
+    ) -> Vec<LaneValue> {
+        // Theta.
+        let parity = izip!(0..5, &self.column_xors)
+            .map(|(x, xors)| {
+                let mut acc = state[x];
+                for (y, xor) in izip!(1..5, xors) {
+                    acc = assign_xor_bytes(instance, lk_multiplicity, xor, &acc, &state[x + 5 * y]);
+                }
+                acc
+            })
+            .collect_vec();
+        for (shl, parity) in izip!(&self.parity_shl, &parity) {
+            assign_shl_bytes(instance, lk_multiplicity, shl, parity, 1);
+        }
+        let theta_d = izip!(0..5, &self.theta_d)
+            .map(|(x, d)| {
+                let rotated = rotate_value(&parity[(x + 1) % 5], 1);
+                assign_xor_bytes(instance, lk_multiplicity, d, &parity[(x + 4) % 5], &rotated)
+            })
+            .collect_vec();
+        let theta = izip!(0..LANES, &self.theta)
+            .map(|(lane, theta)| {
+                assign_xor_bytes(
+                    instance,
+                    lk_multiplicity,
+                    theta,
+                    &state[lane],
+                    &theta_d[lane % 5],
+                )
+            })
+            .collect_vec();
+
+        // Rho and pi.
+        let mut permuted = [[0; LANE_BYTES]; LANES];
+        for (lane, shl) in self.rho_shl.iter().enumerate() {
+            let r = RHO_OFFSETS[lane];
+            if let Some(shl) = shl {
+                assign_shl_bytes(instance, lk_multiplicity, shl, &theta[lane], r % 8);
+            }
+            permuted[pi_lane(lane)] = rotate_value(&theta[lane], r);
+        }
+
+        // Chi.
+        let mut next = izip!(0..LANES, &self.chi_and_not, &self.chi)
+            .map(|(lane, and_not, chi)| {
+                let (x, y) = (lane % 5, lane / 5);
+                let (b, c) = (permuted[(x + 1) % 5 + 5 * y], permuted[(x + 2) % 5 + 5 * y]);
+                let and_not_value =
+                    assign_and_bytes(instance, lk_multiplicity, and_not, &b.map(|b| !b), &c);
+                assign_xor_bytes(
+                    instance,
+                    lk_multiplicity,
+                    chi,
+                    &permuted[lane],
+                    &and_not_value,
+                )
+            })
+            .collect_vec();
+
+        // Iota.
+        let rc = ROUND_CONSTANTS[round].to_le_bytes();
+        for (i, (out, rc)) in izip!(&self.iota, rc).enumerate() {
+            if let Some(out) = out {
+                lk_multiplicity.lookup_xor_byte(next[0][i] as u64, rc as u64);
+                next[0][i] ^= rc;
+                set_val!(instance, out, next[0][i] as u64);
+            }
+        }
+
+        next
+    }
+}
+
+pub struct KeccakConfig<E: ExtensionField> {
+    ecall_cfg: EcallInstructionConfig,
+
+    state_ptr: UInt<E>,
+    prev_x10_ts: WitIn,
+    lt_x10_cfg: AssertLtConfig,
+
+    input: Vec<Lane>,
+    rounds: Vec<RoundConfig>,
+    mem_writes: Vec<WriteMEM>,
+}
+
+/// The `KECCAK_PERMUTE` syscall, which permutes the state of 25 lanes pointed to by `x10`.
+pub struct KeccakInstruction<E>(PhantomData<E>);
+
+impl<E: ExtensionField> Instruction<E> for KeccakInstruction<E> {
+    type InstructionConfig = KeccakConfig<E>;
+
+    fn name() -> String {
+        "ECALL_KECCAK".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let ecall_cfg = EcallInstructionConfig::construct_circuit(
+            cb,
+            [
+                (KECCAK_PERMUTE as usize & 0xffff).into(),
+                ((KECCAK_PERMUTE as usize) >> 16).into(),
+            ],
+            None,
+            None,
+        )?;
+
+        // read the pointer to the state from arg0 (X10 register)
+        let state_ptr = UInt::new_unchecked(|| "state_ptr", cb)?;
+        let prev_x10_ts = cb.create_witin(|| "prev_x10_ts");
+        let (_, lt_x10_cfg) = cb.register_write(
+            || "read x10",
+            E::BaseField::from(Platform::reg_arg0() as u64),
+            prev_x10_ts.expr(),
+            ecall_cfg.ts.expr() + Tracer::SUBCYCLE_RD,
+            state_ptr.register_expr(),
+            state_ptr.register_expr(),
+        )?;
+
+        let input = (0..LANES)
+            .map(|lane| new_bytes(cb, &format!("input_{lane}")))
+            .collect_vec();
+        let mut state = input.iter().map(bytes_expr).collect_vec();
+        let mut rounds = Vec::with_capacity(ROUNDS);
+        for round in 0..ROUNDS {
+            let (config, next) = cb.namespace(
+                || format!("round_{round}"),
+                |cb| RoundConfig::construct_circuit(cb, round, &state),
+            )?;
+            rounds.push(config);
+            state = next;
+        }
+
+        // The state is stored as words, with the low word of each lane first.
+        let word =
+            |lane: &LaneExpr<E>, half: usize| compose(&lane[WORD_SIZE * half..][..WORD_SIZE]);
+        let mem_writes = (0..KECCAK_WORDS)
+            .map(|i| {
+                WriteMEM::construct_circuit(
+                    cb,
+                    state_ptr.address_expr() + (i * WORD_SIZE) as u64,
+                    word(&bytes_expr(&input[i / 2]), i % 2),
+                    word(&state[i / 2], i % 2),
+                    ecall_cfg.ts,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(KeccakConfig {
+            ecall_cfg,
+            state_ptr,
+            prev_x10_ts,
+            lt_x10_cfg,
+            input,
+            rounds,
+            mem_writes,
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(step.rs1().unwrap().value, KECCAK_PERMUTE);
+        let ops = step.syscall().expect("syscall step");
+
+        config
+            .ecall_cfg
+            .assign_instance::<E>(instance, lk_multiplicity, step)?;
+
+        // the access of X10 register is the only register operation of the syscall
+        let x10_op = &ops.reg_ops[0];
+        config
+            .state_ptr
+            .assign_value(instance, Value::new_unchecked(x10_op.value.after));
+        set_val!(instance, config.prev_x10_ts, x10_op.previous_cycle);
+        config.lt_x10_cfg.assign_instance(
+            instance,
+            lk_multiplicity,
+            x10_op.previous_cycle,
+            step.cycle() + Tracer::SUBCYCLE_RD,
+        )?;
+
+        let lane_value = |lo: u32, hi: u32| (lo as u64 | (hi as u64) << 32).to_le_bytes();
+        let mut state = ops
+            .mem_ops
+            .iter()
+            .tuples()
+            .map(|(lo, hi)| lane_value(lo.value.before, hi.value.before))
+            .collect_vec();
+        for (lane, value) in izip!(&config.input, &state) {
+            assign_bytes(instance, lane, value);
+        }
+        for (round, round_config) in config.rounds.iter().enumerate() {
+            state = round_config.assign_instance(instance, lk_multiplicity, round, &state);
+        }
+        let output = ops
+            .mem_ops
+            .iter()
+            .tuples()
+            .map(|(lo, hi)| lane_value(lo.value.after, hi.value.after))
+            .collect_vec();
+        assert_eq!(state, output, "keccak permutation mismatch");
+
+        for (writer, op) in izip!(&config.mem_writes, &ops.mem_ops) {
+            writer.assign_op(instance, lk_multiplicity, step.cycle(), op)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ceno_emul::{
+        CENO_PLATFORM, InsnKind, KECCAK_PERMUTE, KECCAK_WORDS, Platform, Program, StepRecord,
+        VMState, WORD_SIZE, encode_rv32, encode_rv32u,
+    };
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use super::KeccakInstruction;
+    use crate::{
+        circuit_builder::{CircuitBuilder, ConstraintSystem},
+        instructions::Instruction,
+        scheme::mock_prover::MockProver,
+    };
+
+    /// Run the permutation in the emulator on the given state.
+    fn keccak_step(state: &[u64; 25]) -> (StepRecord, Vec<ceno_emul::Instruction>) {
+        let state_ptr = CENO_PLATFORM.heap.start;
+        let instructions = vec![
+            encode_rv32u(InsnKind::ADDI, 0, 0, Platform::reg_arg0() as u32, state_ptr),
+            encode_rv32u(
+                InsnKind::ADDI,
+                0,
+                0,
+                Platform::reg_ecall() as u32,
+                KECCAK_PERMUTE,
+            ),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            encode_rv32u(
+                InsnKind::ADDI,
+                0,
+                0,
+                Platform::reg_ecall() as u32,
+                Platform::ecall_halt(),
+            ),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+        ];
+        let pc = CENO_PLATFORM.pc_base();
+        let program = Program::new(pc, pc, instructions.clone(), Default::default());
+        let mut vm = VMState::new(CENO_PLATFORM, program.into());
+        for (i, word) in state
+            .iter()
+            .flat_map(|&c| [c as u32, (c >> 32) as u32])
+            .enumerate()
+        {
+            vm.init_memory((state_ptr + (i * WORD_SIZE) as u32).into(), word);
+        }
+        let steps = vm.iter_until_halt().map(Result::unwrap).collect_vec();
+        (steps[2].clone(), instructions)
+    }
+
+    #[test]
+    fn test_keccak_permute() {
+        let mut rng = rand::thread_rng();
+        let state: [u64; 25] = std::array::from_fn(|_| rng.gen());
+        let (step, program) = keccak_step(&state);
+
+        // The emulator computes the permutation of tiny_keccak.
+        let mut expected = state;
+        tiny_keccak::keccakf(&mut expected);
+        let output = step
+            .syscall()
+            .unwrap()
+            .mem_ops
+            .iter()
+            .map(|op| op.value.after);
+        assert_eq!(
+            output.collect_vec(),
+            expected
+                .iter()
+                .flat_map(|&c| [c as u32, (c >> 32) as u32])
+                .collect_vec()
+        );
+        assert_eq!(step.syscall().unwrap().mem_ops.len(), KECCAK_WORDS);
+
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = cb
+            .namespace(|| "keccak", KeccakInstruction::construct_circuit)
+            .unwrap();
+
+        let (raw_witin, lkm) =
+            KeccakInstruction::assign_instances(&config, cb.cs.num_witin as usize, vec![step])
+                .unwrap();
+        MockProver::assert_satisfied_raw(
+            &cb,
+            raw_witin.clone(),
+            &program,
+            None,
+            Some(lkm.deep_clone()),
+        );
+
+        // A wrong byte in the output of the last round is rejected.
+        let mut raw_witin = raw_witin;
+        let byte = config.rounds[23].chi[1][0];
+        for row in raw_witin.iter_mut() {
+            row[byte.id as usize] += Goldilocks::from(1);
+        }
+        let wits_in = raw_witin
+            .into_mles()
+            .into_iter()
+            .map(|v| v.into())
+            .collect_vec();
+        assert!(MockProver::run(&cb, &wits_in, &program, Some(lkm)).is_err());
+    }
+}
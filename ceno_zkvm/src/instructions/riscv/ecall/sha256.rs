@@ -0,0 +1,806 @@
+//! The SHA-256 message schedule and compression function of the `SHA_EXTEND` and `SHA_COMPRESS`
+//! syscalls.
+//!
+//! Words are held as 4 bytes, like the lanes of Keccak: the XORs of the sigma functions are
+//! looked up in the XOR table, their rotations and shifts in the SHL table, and the ANDs of `ch`
+//! and `maj` in the AND table. The terms of `ch = (e & f) + (!e & g)` and of
+//! `maj = (a & b) + (c & (a ^ b))` have no bits in common, so they are added rather than XORed.
+//! A sum of words modulo 2^32 is witnessed as its bytes and a carry.
+
+use std::marker::PhantomData;
+
+use ceno_emul::{
+    Cycle, Platform, RegIdx, SHA_COMPRESS, SHA_EXTEND, SHA_EXTEND_WORDS, SHA_ROUND_CONSTANTS,
+    SHA_STATE_WORDS, StepRecord, Tracer, WORD_SIZE, WriteOp,
+};
+use ff_ext::ExtensionField;
+use goldilocks::SmallField;
+use itertools::{Itertools, izip};
+
+use super::bytes::{
+    Bytes, BytesExpr, and_bytes, assign_and_bytes, assign_bytes, assign_shl_bytes,
+    assign_xor_bytes, bytes_expr, compose, new_bytes, not_bytes, rotate_bytes, shl_bytes,
+    shr_bytes, xor_bytes,
+};
+use crate::{
+    chip_handler::RegisterChipOperations,
+    circuit_builder::CircuitBuilder,
+    error::ZKVMError,
+    expression::{Expression, ToExpr, WitIn},
+    gadgets::AssertLtConfig,
+    instructions::{
+        Instruction,
+        riscv::{constants::UInt, ecall_insn::EcallInstructionConfig, insn_base::WriteMEM},
+    },
+    set_val,
+    uint::Value,
+    witness::LkMultiplicity,
+};
+
+/// The words of the message block at the start of the schedule.
+const BLOCK_WORDS: usize = 16;
+
+type Word = Bytes<WORD_SIZE>;
+type WordExpr<E> = BytesExpr<E, WORD_SIZE>;
+
+#[derive(Clone, Copy)]
+enum Shift {
+    Rotr(u32),
+    Shr(u32),
+}
+
+use Shift::*;
+
+const BIG_SIGMA0: [Shift; 3] = [Rotr(2), Rotr(13), Rotr(22)];
+const BIG_SIGMA1: [Shift; 3] = [Rotr(6), Rotr(11), Rotr(25)];
+const SMALL_SIGMA0: [Shift; 3] = [Rotr(7), Rotr(18), Shr(3)];
+const SMALL_SIGMA1: [Shift; 3] = [Rotr(17), Rotr(19), Shr(10)];
+
+impl Shift {
+    /// The shift of the bytes in the SHL table. None of the sigma functions moves whole bytes.
+    fn shl(self) -> u32 {
+        match self {
+            Rotr(r) => (32 - r) % 8,
+            Shr(r) => 8 - r % 8,
+        }
+    }
+
+    fn expr<E: ExtensionField>(self, a: &WordExpr<E>, shl: &Word) -> WordExpr<E> {
+        match self {
+            Rotr(r) => rotate_bytes(a, Some(shl), 32 - r),
+            Shr(r) => shr_bytes(a, Some(shl), r),
+        }
+    }
+
+    fn value(self, a: u32) -> u32 {
+        match self {
+            Rotr(r) => a.rotate_right(r),
+            Shr(r) => a >> r,
+        }
+    }
+}
+
+/// A word of bytes checked in the U8 table.
+fn new_word<E: ExtensionField>(cb: &mut CircuitBuilder<E>, name: &str) -> Result<Word, ZKVMError> {
+    let word = new_bytes(cb, name);
+    for (i, byte) in word.iter().enumerate() {
+        cb.assert_ux::<_, _, 8>(|| format!("{name}_{i}"), byte.expr())?;
+    }
+    Ok(word)
+}
+
+fn assign_word<F: SmallField>(
+    instance: &mut [F],
+    lk_multiplicity: &mut LkMultiplicity,
+    word: &Word,
+    value: u32,
+) {
+    let value = value.to_le_bytes();
+    for byte in value {
+        lk_multiplicity.assert_ux::<8>(byte as u64);
+    }
+    assign_bytes(instance, word, &value);
+}
+
+/// The XOR of three rotations or shifts of a word.
+struct SigmaConfig {
+    shl: [Word; 3],
+    xor: [Word; 2],
+}
+
+impl SigmaConfig {
+    fn construct_circuit<E: ExtensionField>(
+        cb: &mut CircuitBuilder<E>,
+        name: &str,
+        a: &WordExpr<E>,
+        shifts: [Shift; 3],
+    ) -> Result<(Self, WordExpr<E>), ZKVMError> {
+        let mut shl = Vec::with_capacity(3);
+        for (i, shift) in shifts.iter().enumerate() {
+            shl.push(shl_bytes(cb, &format!("{name}_shl_{i}"), a, shift.shl())?);
+        }
+        let parts = izip!(shifts, &shl)
+            .map(|(shift, shl)| shift.expr(a, shl))
+            .collect_vec();
+        let xor_0 = xor_bytes(cb, &format!("{name}_xor_0"), &parts[0], &parts[1])?;
+        let xor_1 = xor_bytes(cb, &format!("{name}_xor_1"), &bytes_expr(&xor_0), &parts[2])?;
+        let out = bytes_expr(&xor_1);
+
+        Ok((
+            SigmaConfig {
+                shl: shl.try_into().unwrap(),
+                xor: [xor_0, xor_1],
+            },
+            out,
+        ))
+    }
+
+    fn assign_instance<F: SmallField>(
+        &self,
+        instance: &mut [F],
+        lk_multiplicity: &mut LkMultiplicity,
+        a: u32,
+        shifts: [Shift; 3],
+    ) -> u32 {
+        for (shl, shift) in izip!(&self.shl, shifts) {
+            assign_shl_bytes(
+                instance,
+                lk_multiplicity,
+                shl,
+                &a.to_le_bytes(),
+                shift.shl(),
+            );
+        }
+        let parts = shifts.map(|shift| shift.value(a).to_le_bytes());
+        let xor_0 = assign_xor_bytes(
+            instance,
+            lk_multiplicity,
+            &self.xor[0],
+            &parts[0],
+            &parts[1],
+        );
+        let xor_1 = assign_xor_bytes(instance, lk_multiplicity, &self.xor[1], &xor_0, &parts[2]);
+        u32::from_le_bytes(xor_1)
+    }
+}
+
+/// A sum of words modulo 2^32.
+struct AddConfig {
+    sum: Word,
+    carry: WitIn,
+}
+
+impl AddConfig {
+    fn construct_circuit<E: ExtensionField>(
+        cb: &mut CircuitBuilder<E>,
+        name: &str,
+        terms: &[Expression<E>],
+    ) -> Result<(Self, WordExpr<E>), ZKVMError> {
+        let sum = new_word(cb, name)?;
+        // The carry is less than the number of terms.
+        let carry = cb.create_witin(|| format!("{name}_carry"));
+        cb.assert_ux::<_, _, 8>(|| format!("{name}_carry"), carry.expr())?;
+        cb.require_equal(
+            || name.to_string(),
+            terms
+                .iter()
+                .fold(Expression::ZERO, |acc, t| acc + t.clone()),
+            compose(&bytes_expr(&sum)) + carry.expr() * (1u64 << 32),
+        )?;
+
+        let out = bytes_expr(&sum);
+        Ok((AddConfig { sum, carry }, out))
+    }
+
+    fn assign_instance<F: SmallField>(
+        &self,
+        instance: &mut [F],
+        lk_multiplicity: &mut LkMultiplicity,
+        terms: &[u32],
+    ) -> u32 {
+        let total = terms.iter().map(|&t| t as u64).sum::<u64>();
+        assign_word(instance, lk_multiplicity, &self.sum, total as u32);
+        lk_multiplicity.assert_ux::<8>(total >> 32);
+        set_val!(instance, self.carry, total >> 32);
+        total as u32
+    }
+}
+
+/// The read of a pointer argument of the syscall.
+pub(super) struct ArgConfig<E: ExtensionField> {
+    pub(super) ptr: UInt<E>,
+    prev_ts: WitIn,
+    lt_cfg: AssertLtConfig,
+}
+
+impl<E: ExtensionField> ArgConfig<E> {
+    pub(super) fn construct_circuit(
+        cb: &mut CircuitBuilder<E>,
+        reg: RegIdx,
+        ts: WitIn,
+    ) -> Result<Self, ZKVMError> {
+        let ptr = UInt::new_unchecked(|| format!("x{reg}"), cb)?;
+        let prev_ts = cb.create_witin(|| format!("prev_x{reg}_ts"));
+        let (_, lt_cfg) = cb.register_write(
+            || format!("read x{reg}"),
+            E::BaseField::from(reg as u64),
+            prev_ts.expr(),
+            ts.expr() + Tracer::SUBCYCLE_RD,
+            ptr.register_expr(),
+            ptr.register_expr(),
+        )?;
+        Ok(ArgConfig {
+            ptr,
+            prev_ts,
+            lt_cfg,
+        })
+    }
+
+    pub(super) fn assign_op(
+        &self,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        cycle: Cycle,
+        op: &WriteOp,
+    ) -> Result<(), ZKVMError> {
+        self.ptr
+            .assign_value(instance, Value::new_unchecked(op.value.after));
+        set_val!(instance, self.prev_ts, op.previous_cycle);
+        self.lt_cfg.assign_instance(
+            instance,
+            lk_multiplicity,
+            op.previous_cycle,
+            cycle + Tracer::SUBCYCLE_RD,
+        )
+    }
+}
+
+pub(super) fn ecall_config<E: ExtensionField>(
+    cb: &mut CircuitBuilder<E>,
+    function_code: u32,
+) -> Result<EcallInstructionConfig, ZKVMError> {
+    EcallInstructionConfig::construct_circuit(
+        cb,
+        [
+            (function_code as usize & 0xffff).into(),
+            ((function_code as usize) >> 16).into(),
+        ],
+        None,
+        None,
+    )
+}
+
+/// The witness of one word of the message schedule.
+struct ExtendRoundConfig {
+    sigma0: SigmaConfig,
+    sigma1: SigmaConfig,
+    w: AddConfig,
+}
+
+pub struct ShaExtendConfig<E: ExtensionField> {
+    ecall_cfg: EcallInstructionConfig,
+    w_ptr: ArgConfig<E>,
+
+    block: Vec<Word>,
+    rounds: Vec<ExtendRoundConfig>,
+    /// The values overwritten by the schedule.
+    prev_w: Vec<WitIn>,
+    mem_writes: Vec<WriteMEM>,
+}
+
+/// The `SHA_EXTEND` syscall, which fills in the message schedule of 64 words pointed to by
+/// `x10`, from the message block in its first 16 words.
+pub struct ShaExtendInstruction<E>(PhantomData<E>);
+
+impl<E: ExtensionField> Instruction<E> for ShaExtendInstruction<E> {
+    type InstructionConfig = ShaExtendConfig<E>;
+
+    fn name() -> String {
+        "ECALL_SHA_EXTEND".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let ecall_cfg = ecall_config(cb, SHA_EXTEND)?;
+        let w_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg0(), ecall_cfg.ts)?;
+
+        let block = (0..BLOCK_WORDS)
+            .map(|i| new_word(cb, &format!("w_{i}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut w = block.iter().map(bytes_expr).collect_vec();
+        let mut rounds = Vec::with_capacity(SHA_EXTEND_WORDS - BLOCK_WORDS);
+        for i in BLOCK_WORDS..SHA_EXTEND_WORDS {
+            let (config, next) = cb.namespace(
+                || format!("w_{i}"),
+                |cb| {
+                    let (sigma0, s0) =
+                        SigmaConfig::construct_circuit(cb, "sigma0", &w[i - 15], SMALL_SIGMA0)?;
+                    let (sigma1, s1) =
+                        SigmaConfig::construct_circuit(cb, "sigma1", &w[i - 2], SMALL_SIGMA1)?;
+                    let (add, next) = AddConfig::construct_circuit(cb, "sum", &[
+                        compose(&w[i - 16]),
+                        compose(&s0),
+                        compose(&w[i - 7]),
+                        compose(&s1),
+                    ])?;
+                    Ok((
+                        ExtendRoundConfig {
+                            sigma0,
+                            sigma1,
+                            w: add,
+                        },
+                        next,
+                    ))
+                },
+            )?;
+            rounds.push(config);
+            w.push(next);
+        }
+
+        let prev_w = (BLOCK_WORDS..SHA_EXTEND_WORDS)
+            .map(|i| cb.create_witin(|| format!("prev_w_{i}")))
+            .collect_vec();
+        let mem_writes = izip!(0..SHA_EXTEND_WORDS, &w)
+            .map(|(i, w)| {
+                let prev = match i.checked_sub(BLOCK_WORDS) {
+                    None => compose(w),
+                    Some(j) => prev_w[j].expr(),
+                };
+                WriteMEM::construct_circuit(
+                    cb,
+                    w_ptr.ptr.address_expr() + (i * WORD_SIZE) as u64,
+                    prev,
+                    compose(w),
+                    ecall_cfg.ts,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ShaExtendConfig {
+            ecall_cfg,
+            w_ptr,
+            block,
+            rounds,
+            prev_w,
+            mem_writes,
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(step.rs1().unwrap().value, SHA_EXTEND);
+        let ops = step.syscall().expect("syscall step");
+
+        config
+            .ecall_cfg
+            .assign_instance::<E>(instance, lk_multiplicity, step)?;
+        config
+            .w_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[0])?;
+
+        let mut w = ops.mem_ops[..BLOCK_WORDS]
+            .iter()
+            .map(|op| op.value.before)
+            .collect_vec();
+        for (word, &value) in izip!(&config.block, &w) {
+            assign_word(instance, lk_multiplicity, word, value);
+        }
+        for (i, round) in izip!(BLOCK_WORDS.., &config.rounds) {
+            let s0 =
+                round
+                    .sigma0
+                    .assign_instance(instance, lk_multiplicity, w[i - 15], SMALL_SIGMA0);
+            let s1 =
+                round
+                    .sigma1
+                    .assign_instance(instance, lk_multiplicity, w[i - 2], SMALL_SIGMA1);
+            let next =
+                round
+                    .w
+                    .assign_instance(instance, lk_multiplicity, &[w[i - 16], s0, w[i - 7], s1]);
+            w.push(next);
+        }
+        let output = ops.mem_ops.iter().map(|op| op.value.after).collect_vec();
+        assert_eq!(w, output, "sha extend mismatch");
+
+        for (prev, op) in izip!(&config.prev_w, &ops.mem_ops[BLOCK_WORDS..]) {
+            set_val!(instance, prev, op.value.before as u64);
+        }
+        for (writer, op) in izip!(&config.mem_writes, &ops.mem_ops) {
+            writer.assign_op(instance, lk_multiplicity, step.cycle(), op)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The witness of one round of the compression function.
+struct CompressRoundConfig {
+    sigma1: SigmaConfig,
+    ch_and: Word,
+    ch_and_not: Word,
+    sigma0: SigmaConfig,
+    maj_and: Word,
+    maj_xor: Word,
+    maj_and_xor: Word,
+    a: AddConfig,
+    e: AddConfig,
+}
+
+impl CompressRoundConfig {
+    fn construct_circuit<E: ExtensionField>(
+        cb: &mut CircuitBuilder<E>,
+        round: usize,
+        vars: &[WordExpr<E>],
+        w: &WordExpr<E>,
+    ) -> Result<(Self, Vec<WordExpr<E>>), ZKVMError> {
+        let [a, b, c, d, e, f, g, h] = vars else {
+            unreachable!()
+        };
+
+        let (sigma1, s1) = SigmaConfig::construct_circuit(cb, "sigma1", e, BIG_SIGMA1)?;
+        let ch_and = and_bytes(cb, "ch_and", e, f)?;
+        let ch_and_not = and_bytes(cb, "ch_and_not", &not_bytes(e), g)?;
+        let ch = compose(&bytes_expr(&ch_and)) + compose(&bytes_expr(&ch_and_not));
+
+        let (sigma0, s0) = SigmaConfig::construct_circuit(cb, "sigma0", a, BIG_SIGMA0)?;
+        let maj_and = and_bytes(cb, "maj_and", a, b)?;
+        let maj_xor = xor_bytes(cb, "maj_xor", a, b)?;
+        let maj_and_xor = and_bytes(cb, "maj_and_xor", c, &bytes_expr(&maj_xor))?;
+        let maj = compose(&bytes_expr(&maj_and)) + compose(&bytes_expr(&maj_and_xor));
+
+        let temp1 = [
+            compose(h),
+            compose(&s1),
+            ch,
+            SHA_ROUND_CONSTANTS[round].into(),
+            compose(w),
+        ];
+        let (e_config, new_e) =
+            AddConfig::construct_circuit(cb, "e", &[&temp1[..], &[compose(d)]].concat())?;
+        let (a_config, new_a) =
+            AddConfig::construct_circuit(cb, "a", &[&temp1[..], &[compose(&s0), maj]].concat())?;
+
+        let next = vec![
+            new_a,
+            a.clone(),
+            b.clone(),
+            c.clone(),
+            new_e,
+            e.clone(),
+            f.clone(),
+            g.clone(),
+        ];
+        Ok((
+            CompressRoundConfig {
+                sigma1,
+                ch_and,
+                ch_and_not,
+                sigma0,
+                maj_and,
+                maj_xor,
+                maj_and_xor,
+                a: a_config,
+                e: e_config,
+            },
+            next,
+        ))
+    }
+
+    fn assign_instance<F: SmallField>(
+        &self,
+        instance: &mut [F],
+        lk_multiplicity: &mut LkMultiplicity,
+        round: usize,
+        vars: [u32; SHA_STATE_WORDS],
+        w: u32,
+    ) -> [u32; SHA_STATE_WORDS] {
+        let [a, b, c, d, e, f, g, h] = vars.map(u32::to_le_bytes);
+
+        let s1 = self
+            .sigma1
+            .assign_instance(instance, lk_multiplicity, vars[4], BIG_SIGMA1);
+        let ch_and = assign_and_bytes(instance, lk_multiplicity, &self.ch_and, &e, &f);
+        let ch_and_not = assign_and_bytes(
+            instance,
+            lk_multiplicity,
+            &self.ch_and_not,
+            &e.map(|e| !e),
+            &g,
+        );
+        let ch = u32::from_le_bytes(ch_and) + u32::from_le_bytes(ch_and_not);
+
+        let s0 = self
+            .sigma0
+            .assign_instance(instance, lk_multiplicity, vars[0], BIG_SIGMA0);
+        let maj_and = assign_and_bytes(instance, lk_multiplicity, &self.maj_and, &a, &b);
+        let maj_xor = assign_xor_bytes(instance, lk_multiplicity, &self.maj_xor, &a, &b);
+        let maj_and_xor =
+            assign_and_bytes(instance, lk_multiplicity, &self.maj_and_xor, &c, &maj_xor);
+        let maj = u32::from_le_bytes(maj_and) + u32::from_le_bytes(maj_and_xor);
+
+        let temp1 = [u32::from_le_bytes(h), s1, ch, SHA_ROUND_CONSTANTS[round], w];
+        let new_e = self.e.assign_instance(
+            instance,
+            lk_multiplicity,
+            &[&temp1[..], &[u32::from_le_bytes(d)]].concat(),
+        );
+        let new_a = self.a.assign_instance(
+            instance,
+            lk_multiplicity,
+            &[&temp1[..], &[s0, maj]].concat(),
+        );
+
+        [
+            new_a, vars[0], vars[1], vars[2], new_e, vars[4], vars[5], vars[6],
+        ]
+    }
+}
+
+pub struct ShaCompressConfig<E: ExtensionField> {
+    ecall_cfg: EcallInstructionConfig,
+    w_ptr: ArgConfig<E>,
+    state_ptr: ArgConfig<E>,
+
+    w: Vec<Word>,
+    state: Vec<Word>,
+    rounds: Vec<CompressRoundConfig>,
+    output: Vec<AddConfig>,
+    mem_writes: Vec<WriteMEM>,
+}
+
+/// The `SHA_COMPRESS` syscall, which updates the hash state of 8 words pointed to by `x11` with
+/// the message schedule of 64 words pointed to by `x10`.
+pub struct ShaCompressInstruction<E>(PhantomData<E>);
+
+impl<E: ExtensionField> Instruction<E> for ShaCompressInstruction<E> {
+    type InstructionConfig = ShaCompressConfig<E>;
+
+    fn name() -> String {
+        "ECALL_SHA_COMPRESS".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let ecall_cfg = ecall_config(cb, SHA_COMPRESS)?;
+        let w_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg0(), ecall_cfg.ts)?;
+        let state_ptr = ArgConfig::construct_circuit(cb, Platform::reg_arg1(), ecall_cfg.ts)?;
+
+        let w = (0..SHA_EXTEND_WORDS)
+            .map(|i| new_word(cb, &format!("w_{i}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let state = (0..SHA_STATE_WORDS)
+            .map(|i| new_word(cb, &format!("state_{i}")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut vars = state.iter().map(bytes_expr).collect_vec();
+        let mut rounds = Vec::with_capacity(SHA_EXTEND_WORDS);
+        for (round, w) in w.iter().enumerate() {
+            let (config, next) = cb.namespace(
+                || format!("round_{round}"),
+                |cb| CompressRoundConfig::construct_circuit(cb, round, &vars, &bytes_expr(w)),
+            )?;
+            rounds.push(config);
+            vars = next;
+        }
+        let (output, output_exprs): (Vec<_>, Vec<_>) = izip!(0..SHA_STATE_WORDS, &state, &vars)
+            .map(|(i, state, var)| {
+                AddConfig::construct_circuit(cb, &format!("output_{i}"), &[
+                    compose(&bytes_expr(state)),
+                    compose(var),
+                ])
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        // Read the message schedule, and update the state.
+        let mut mem_writes = Vec::with_capacity(SHA_EXTEND_WORDS + SHA_STATE_WORDS);
+        for (i, w) in w.iter().enumerate() {
+            mem_writes.push(WriteMEM::construct_circuit(
+                cb,
+                w_ptr.ptr.address_expr() + (i * WORD_SIZE) as u64,
+                compose(&bytes_expr(w)),
+                compose(&bytes_expr(w)),
+                ecall_cfg.ts,
+            )?);
+        }
+        for (i, (state, output)) in izip!(&state, &output_exprs).enumerate() {
+            mem_writes.push(WriteMEM::construct_circuit(
+                cb,
+                state_ptr.ptr.address_expr() + (i * WORD_SIZE) as u64,
+                compose(&bytes_expr(state)),
+                compose(output),
+                ecall_cfg.ts,
+            )?);
+        }
+
+        Ok(ShaCompressConfig {
+            ecall_cfg,
+            w_ptr,
+            state_ptr,
+            w,
+            state,
+            rounds,
+            output,
+            mem_writes,
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(step.rs1().unwrap().value, SHA_COMPRESS);
+        let ops = step.syscall().expect("syscall step");
+
+        config
+            .ecall_cfg
+            .assign_instance::<E>(instance, lk_multiplicity, step)?;
+        config
+            .w_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[0])?;
+        config
+            .state_ptr
+            .assign_op(instance, lk_multiplicity, step.cycle(), &ops.reg_ops[1])?;
+
+        let (w_ops, state_ops) = ops.mem_ops.split_at(SHA_EXTEND_WORDS);
+        for (word, op) in izip!(&config.w, w_ops) {
+            assign_word(instance, lk_multiplicity, word, op.value.before);
+        }
+        let state: [u32; SHA_STATE_WORDS] = array_of(state_ops.iter().map(|op| op.value.before));
+        for (word, &value) in izip!(&config.state, &state) {
+            assign_word(instance, lk_multiplicity, word, value);
+        }
+
+        let mut vars = state;
+        for (round, (round_config, op)) in izip!(&config.rounds, w_ops).enumerate() {
+            vars = round_config.assign_instance(
+                instance,
+                lk_multiplicity,
+                round,
+                vars,
+                op.value.before,
+            );
+        }
+        let output = izip!(&config.output, state, vars)
+            .map(|(add, h, v)| add.assign_instance(instance, lk_multiplicity, &[h, v]))
+            .collect_vec();
+        let expected = state_ops.iter().map(|op| op.value.after).collect_vec();
+        assert_eq!(output, expected, "sha compress mismatch");
+
+        for (writer, op) in izip!(&config.mem_writes, &ops.mem_ops) {
+            writer.assign_op(instance, lk_multiplicity, step.cycle(), op)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn array_of<const N: usize>(values: impl Iterator<Item = u32>) -> [u32; N] {
+    values.collect_vec().try_into().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use ceno_emul::{
+        CENO_PLATFORM, InsnKind, Platform, Program, SHA_COMPRESS, SHA_EXTEND, StepRecord, VMState,
+        WORD_SIZE, encode_rv32, encode_rv32u,
+    };
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+    use itertools::Itertools;
+
+    use super::{ShaCompressInstruction, ShaExtendInstruction};
+    use crate::{
+        circuit_builder::{CircuitBuilder, ConstraintSystem},
+        instructions::Instruction,
+        scheme::mock_prover::MockProver,
+    };
+
+    const INITIAL_STATE: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    /// The hash of "abc".
+    const ABC_HASH: [u32; 8] = [
+        0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+        0xf20015ad,
+    ];
+
+    /// Hash the padded block of "abc" in the emulator, and return the extend and compress steps.
+    fn sha_steps() -> (StepRecord, StepRecord, Vec<ceno_emul::Instruction>) {
+        let w_ptr = CENO_PLATFORM.heap.start;
+        let state_ptr = w_ptr + 0x100;
+        let load_immediate =
+            |rd: usize, imm: u32| encode_rv32u(InsnKind::ADDI, 0, 0, rd as u32, imm);
+        let instructions = vec![
+            load_immediate(Platform::reg_arg0(), w_ptr),
+            load_immediate(Platform::reg_ecall(), SHA_EXTEND),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            load_immediate(Platform::reg_arg1(), state_ptr),
+            load_immediate(Platform::reg_ecall(), SHA_COMPRESS),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+            load_immediate(Platform::reg_ecall(), Platform::ecall_halt()),
+            encode_rv32(InsnKind::ECALL, 0, 0, 0, 0),
+        ];
+        let pc = CENO_PLATFORM.pc_base();
+        let program = Program::new(pc, pc, instructions.clone(), Default::default());
+        let mut vm = VMState::new(CENO_PLATFORM, program.into());
+
+        let mut block = [0_u8; 64];
+        block[..3].copy_from_slice(b"abc");
+        block[3] = 0x80;
+        block[63] = 3 * 8;
+        for (i, bytes) in block.chunks_exact(WORD_SIZE).enumerate() {
+            let word = u32::from_be_bytes(bytes.try_into().unwrap());
+            vm.init_memory((w_ptr + (i * WORD_SIZE) as u32).into(), word);
+        }
+        for (i, &word) in INITIAL_STATE.iter().enumerate() {
+            vm.init_memory((state_ptr + (i * WORD_SIZE) as u32).into(), word);
+        }
+
+        let steps = vm.iter_until_halt().map(Result::unwrap).collect_vec();
+        (steps[2].clone(), steps[5].clone(), instructions)
+    }
+
+    #[test]
+    fn test_sha_extend() {
+        let (step, _, program) = sha_steps();
+
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = cb
+            .namespace(|| "sha_extend", ShaExtendInstruction::construct_circuit)
+            .unwrap();
+
+        let (raw_witin, lkm) =
+            ShaExtendInstruction::assign_instances(&config, cb.cs.num_witin as usize, vec![step])
+                .unwrap();
+        MockProver::assert_satisfied_raw(&cb, raw_witin, &program, None, Some(lkm));
+    }
+
+    #[test]
+    fn test_sha_compress() {
+        let (_, step, program) = sha_steps();
+        let output = step.syscall().unwrap().mem_ops[64..]
+            .iter()
+            .map(|op| op.value.after)
+            .collect_vec();
+        assert_eq!(output, ABC_HASH);
+
+        let mut cs = ConstraintSystem::<GoldilocksExt2>::new(|| "riscv");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let config = cb
+            .namespace(|| "sha_compress", ShaCompressInstruction::construct_circuit)
+            .unwrap();
+
+        let (raw_witin, lkm) =
+            ShaCompressInstruction::assign_instances(&config, cb.cs.num_witin as usize, vec![step])
+                .unwrap();
+        MockProver::assert_satisfied_raw(
+            &cb,
+            raw_witin.clone(),
+            &program,
+            None,
+            Some(lkm.deep_clone()),
+        );
+
+        // A wrong carry in the last round is rejected.
+        let mut raw_witin = raw_witin;
+        let carry = config.rounds[63].a.carry;
+        for row in raw_witin.iter_mut() {
+            row[carry.id as usize] += Goldilocks::from(1);
+        }
+        let wits_in = raw_witin
+            .into_mles()
+            .into_iter()
+            .map(|v| v.into())
+            .collect_vec();
+        assert!(MockProver::run(&cb, &wits_in, &program, Some(lkm)).is_err());
+    }
+}
@@ -43,6 +43,7 @@ impl EcallInstructionConfig {
             0.into(),
             0.into(),
             0.into(), // imm = 0
+            0.into(), // ECALL is never compressed.
         ))?;
 
         let prev_x5_ts = cb.create_witin(|| "prev_x5_ts");
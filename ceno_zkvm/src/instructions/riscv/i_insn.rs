@@ -49,6 +49,7 @@ impl<E: ExtensionField> IInstructionConfig<E> {
             rs1.id.expr(),
             0.into(),
             imm.clone(),
+            vm_state.compressed.expr(),
         ))?;
 
         Ok(IInstructionConfig { vm_state, rs1, rd })
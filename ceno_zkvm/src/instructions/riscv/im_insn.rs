@@ -49,6 +49,7 @@ impl<E: ExtensionField> IMInstructionConfig<E> {
             rs1.id.expr(),
             0.into(),
             imm.clone(),
+            vm_state.compressed.expr(),
         ))?;
 
         Ok(IMInstructionConfig {
@@ -1,4 +1,4 @@
-use ceno_emul::{Cycle, StepRecord, Word, WriteOp};
+use ceno_emul::{COMPRESSED_PC_STEP_SIZE, Cycle, StepRecord, Word, WriteOp};
 use ff::Field;
 use ff_ext::ExtensionField;
 use goldilocks::SmallField;
@@ -26,23 +26,26 @@ pub struct StateInOut<E: ExtensionField> {
     pub pc: WitIn,
     pub next_pc: Option<WitIn>,
     pub ts: WitIn,
+    /// Whether the instruction is compressed, as fetched from the program table.
+    pub compressed: WitIn,
     _field_type: PhantomData<E>,
 }
 
 impl<E: ExtensionField> StateInOut<E> {
     /// If circuit is branching, leave witness for next_pc free and return in
     /// configuration so that calling circuit can constrain its value.
-    /// Otherwise, internally increment by PC_STEP_SIZE
+    /// Otherwise, internally increment by the size of the instruction.
     pub fn construct_circuit(
         circuit_builder: &mut CircuitBuilder<E>,
         branching: bool,
     ) -> Result<Self, ZKVMError> {
         let pc = circuit_builder.create_witin(|| "pc");
+        let compressed = circuit_builder.create_witin(|| "compressed");
         let (next_pc_opt, next_pc_expr) = if branching {
             let next_pc = circuit_builder.create_witin(|| "next_pc");
             (Some(next_pc), next_pc.expr())
         } else {
-            (None, pc.expr() + PC_STEP_SIZE)
+            (None, pc.expr() + Self::pc_step_expr(compressed))
         };
         let ts = circuit_builder.create_witin(|| "ts");
         let next_ts = ts.expr() + Tracer::SUBCYCLES_PER_INSN;
@@ -53,10 +56,21 @@ impl<E: ExtensionField> StateInOut<E> {
             pc,
             next_pc: next_pc_opt,
             ts,
+            compressed,
             _field_type: PhantomData,
         })
     }
 
+    /// The size of the instruction, from the pc to the next instruction.
+    pub fn pc_step(&self) -> Expression<E> {
+        Self::pc_step_expr(self.compressed)
+    }
+
+    fn pc_step_expr(compressed: WitIn) -> Expression<E> {
+        Expression::from(PC_STEP_SIZE)
+            - compressed.expr() * (PC_STEP_SIZE - COMPRESSED_PC_STEP_SIZE)
+    }
+
     pub fn assign_instance(
         &self,
         instance: &mut [<E as ExtensionField>::BaseField],
@@ -68,6 +82,7 @@ impl<E: ExtensionField> StateInOut<E> {
             set_val!(instance, n_pc, step.pc().after.0 as u64);
         }
         set_val!(instance, self.ts, step.cycle());
+        set_val!(instance, self.compressed, step.insn().compressed as u64);
 
         Ok(())
     }
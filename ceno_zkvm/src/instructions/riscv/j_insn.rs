@@ -45,6 +45,7 @@ impl<E: ExtensionField> JInstructionConfig<E> {
             0.into(),
             0.into(),
             vm_state.next_pc.unwrap().expr() - vm_state.pc.expr(),
+            vm_state.compressed.expr(),
         ))?;
 
         Ok(JInstructionConfig { vm_state, rd })
@@ -13,7 +13,7 @@ use crate::{
     },
     witness::LkMultiplicity,
 };
-use ceno_emul::{InsnKind, PC_STEP_SIZE};
+use ceno_emul::InsnKind;
 
 pub struct JalConfig<E: ExtensionField> {
     pub j_insn: JInstructionConfig<E>,
@@ -54,7 +54,7 @@ impl<E: ExtensionField> Instruction<E> for JalInstruction<E> {
         circuit_builder.require_equal(
             || "jal rd_written",
             rd_written.value(),
-            j_insn.vm_state.pc.expr() + PC_STEP_SIZE,
+            j_insn.vm_state.pc.expr() + j_insn.vm_state.pc_step(),
         )?;
 
         Ok(JalConfig { j_insn, rd_written })
@@ -17,7 +17,7 @@ use crate::{
     utils::i64_to_base,
     witness::LkMultiplicity,
 };
-use ceno_emul::{InsnKind, PC_STEP_SIZE};
+use ceno_emul::InsnKind;
 
 pub struct JalrConfig<E: ExtensionField> {
     pub i_insn: IInstructionConfig<E>,
@@ -91,11 +91,11 @@ impl<E: ExtensionField> Instruction<E> for JalrInstruction<E> {
             i_insn.vm_state.next_pc.unwrap().expr(),
         )?;
 
-        // write pc+4 to rd
+        // write the return address pc+4, or pc+2 if compressed, to rd
         circuit_builder.require_equal(
             || "rd_written = pc+4",
             rd_written.value(),
-            i_insn.vm_state.pc.expr() + PC_STEP_SIZE,
+            i_insn.vm_state.pc.expr() + i_insn.vm_state.pc_step(),
         )?;
 
         Ok(JalrConfig {
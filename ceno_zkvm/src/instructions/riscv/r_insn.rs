@@ -48,6 +48,7 @@ impl<E: ExtensionField> RInstructionConfig<E> {
             rs1.id.expr(),
             rs2.id.expr(),
             0.into(),
+            vm_state.compressed.expr(),
         ))?;
 
         Ok(RInstructionConfig {
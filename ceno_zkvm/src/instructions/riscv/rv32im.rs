@@ -19,13 +19,16 @@ use crate::{
     },
     structs::{ZKVMConstraintSystem, ZKVMFixedTraces, ZKVMWitnesses},
     tables::{
-        AndTableCircuit, LtuTableCircuit, OrTableCircuit, PowTableCircuit, TableCircuit,
-        U5TableCircuit, U8TableCircuit, U14TableCircuit, U16TableCircuit, XorTableCircuit,
+        AndTableCircuit, LtuTableCircuit, OrTableCircuit, PowTableCircuit, ShlTableCircuit,
+        TableCircuit, U5TableCircuit, U8TableCircuit, U14TableCircuit, U16TableCircuit,
+        XorTableCircuit,
     },
 };
 use ceno_emul::{
+    ED25519_ADD,
     InsnKind::{self, *},
-    Platform, StepRecord,
+    KECCAK_PERMUTE, Platform, SECP256K1_ADD, SECP256K1_DOUBLE, SHA_COMPRESS, SHA_EXTEND,
+    StepRecord,
 };
 use ecall::EcallDummy;
 use ff_ext::ExtensionField;
@@ -41,7 +44,13 @@ use std::{
 use strum::IntoEnumIterator;
 
 use super::{
-    arith::AddInstruction, branch::BltuInstruction, ecall::HaltInstruction, jump::JalInstruction,
+    arith::AddInstruction,
+    branch::BltuInstruction,
+    ecall::{
+        Ed25519AddInstruction, HaltInstruction, KeccakInstruction, Secp256k1AddInstruction,
+        Secp256k1DoubleInstruction, ShaCompressInstruction, ShaExtendInstruction,
+    },
+    jump::JalInstruction,
     memory::LwInstruction,
 };
 
@@ -103,6 +112,13 @@ pub struct Rv32imConfig<E: ExtensionField> {
 
     // Ecall Opcodes
     pub halt_config: <HaltInstruction<E> as Instruction<E>>::InstructionConfig,
+    pub keccak_config: <KeccakInstruction<E> as Instruction<E>>::InstructionConfig,
+    pub sha_extend_config: <ShaExtendInstruction<E> as Instruction<E>>::InstructionConfig,
+    pub sha_compress_config: <ShaCompressInstruction<E> as Instruction<E>>::InstructionConfig,
+    pub secp256k1_add_config: <Secp256k1AddInstruction<E> as Instruction<E>>::InstructionConfig,
+    pub secp256k1_double_config:
+        <Secp256k1DoubleInstruction<E> as Instruction<E>>::InstructionConfig,
+    pub ed25519_add_config: <Ed25519AddInstruction<E> as Instruction<E>>::InstructionConfig,
     // Tables.
     pub u16_range_config: <U16TableCircuit<E> as TableCircuit<E>>::TableConfig,
     pub u14_range_config: <U14TableCircuit<E> as TableCircuit<E>>::TableConfig,
@@ -113,6 +129,7 @@ pub struct Rv32imConfig<E: ExtensionField> {
     pub xor_table_config: <XorTableCircuit<E> as TableCircuit<E>>::TableConfig,
     pub ltu_config: <LtuTableCircuit<E> as TableCircuit<E>>::TableConfig,
     pub pow_config: <PowTableCircuit<E> as TableCircuit<E>>::TableConfig,
+    pub shl_table_config: <ShlTableCircuit<E> as TableCircuit<E>>::TableConfig,
 }
 
 impl<E: ExtensionField> Rv32imConfig<E> {
@@ -173,6 +190,12 @@ impl<E: ExtensionField> Rv32imConfig<E> {
 
         // ecall opcodes
         let halt_config = cs.register_opcode_circuit::<HaltInstruction<E>>();
+        let keccak_config = cs.register_opcode_circuit::<KeccakInstruction<E>>();
+        let sha_extend_config = cs.register_opcode_circuit::<ShaExtendInstruction<E>>();
+        let sha_compress_config = cs.register_opcode_circuit::<ShaCompressInstruction<E>>();
+        let secp256k1_add_config = cs.register_opcode_circuit::<Secp256k1AddInstruction<E>>();
+        let secp256k1_double_config = cs.register_opcode_circuit::<Secp256k1DoubleInstruction<E>>();
+        let ed25519_add_config = cs.register_opcode_circuit::<Ed25519AddInstruction<E>>();
         // tables
         let u16_range_config = cs.register_table_circuit::<U16TableCircuit<E>>();
         let u14_range_config = cs.register_table_circuit::<U14TableCircuit<E>>();
@@ -183,6 +206,7 @@ impl<E: ExtensionField> Rv32imConfig<E> {
         let xor_table_config = cs.register_table_circuit::<XorTableCircuit<E>>();
         let ltu_config = cs.register_table_circuit::<LtuTableCircuit<E>>();
         let pow_config = cs.register_table_circuit::<PowTableCircuit<E>>();
+        let shl_table_config = cs.register_table_circuit::<ShlTableCircuit<E>>();
 
         Self {
             // alu opcodes
@@ -235,6 +259,12 @@ impl<E: ExtensionField> Rv32imConfig<E> {
             lb_config,
             // ecall opcodes
             halt_config,
+            keccak_config,
+            sha_extend_config,
+            sha_compress_config,
+            secp256k1_add_config,
+            secp256k1_double_config,
+            ed25519_add_config,
             // tables
             u16_range_config,
             u14_range_config,
@@ -245,6 +275,7 @@ impl<E: ExtensionField> Rv32imConfig<E> {
             xor_table_config,
             ltu_config,
             pow_config,
+            shl_table_config,
         }
     }
 
@@ -303,6 +334,12 @@ impl<E: ExtensionField> Rv32imConfig<E> {
         fixed.register_opcode_circuit::<LbInstruction<E>>(cs);
 
         fixed.register_opcode_circuit::<HaltInstruction<E>>(cs);
+        fixed.register_opcode_circuit::<KeccakInstruction<E>>(cs);
+        fixed.register_opcode_circuit::<ShaExtendInstruction<E>>(cs);
+        fixed.register_opcode_circuit::<ShaCompressInstruction<E>>(cs);
+        fixed.register_opcode_circuit::<Secp256k1AddInstruction<E>>(cs);
+        fixed.register_opcode_circuit::<Secp256k1DoubleInstruction<E>>(cs);
+        fixed.register_opcode_circuit::<Ed25519AddInstruction<E>>(cs);
 
         fixed.register_table_circuit::<U16TableCircuit<E>>(cs, &self.u16_range_config, &());
         fixed.register_table_circuit::<U14TableCircuit<E>>(cs, &self.u14_range_config, &());
@@ -313,8 +350,12 @@ impl<E: ExtensionField> Rv32imConfig<E> {
         fixed.register_table_circuit::<XorTableCircuit<E>>(cs, &self.xor_table_config, &());
         fixed.register_table_circuit::<LtuTableCircuit<E>>(cs, &self.ltu_config, &());
         fixed.register_table_circuit::<PowTableCircuit<E>>(cs, &self.pow_config, &());
+        fixed.register_table_circuit::<ShlTableCircuit<E>>(cs, &self.shl_table_config, &());
     }
 
+    /// Assign a chunk of steps to the opcode circuits, after the steps of the previous chunks.
+    ///
+    /// Every circuit gets a witness, possibly empty. Return the steps of unimplemented opcodes.
     pub fn assign_opcode_circuit(
         &self,
         cs: &ZKVMConstraintSystem<E>,
@@ -325,6 +366,12 @@ impl<E: ExtensionField> Rv32imConfig<E> {
             .map(|insn_kind| (insn_kind, Vec::new()))
             .collect();
         let mut halt_records = Vec::new();
+        let mut keccak_records = Vec::new();
+        let mut sha_extend_records = Vec::new();
+        let mut sha_compress_records = Vec::new();
+        let mut secp256k1_add_records = Vec::new();
+        let mut secp256k1_double_records = Vec::new();
+        let mut ed25519_add_records = Vec::new();
         steps.into_iter().for_each(|record| {
             let insn_kind = record.insn.kind;
             match insn_kind {
@@ -332,6 +379,24 @@ impl<E: ExtensionField> Rv32imConfig<E> {
                 InsnKind::ECALL if record.rs1().unwrap().value == Platform::ecall_halt() => {
                     halt_records.push(record);
                 }
+                InsnKind::ECALL if record.rs1().unwrap().value == KECCAK_PERMUTE => {
+                    keccak_records.push(record);
+                }
+                InsnKind::ECALL if record.rs1().unwrap().value == SHA_EXTEND => {
+                    sha_extend_records.push(record);
+                }
+                InsnKind::ECALL if record.rs1().unwrap().value == SHA_COMPRESS => {
+                    sha_compress_records.push(record);
+                }
+                InsnKind::ECALL if record.rs1().unwrap().value == SECP256K1_ADD => {
+                    secp256k1_add_records.push(record);
+                }
+                InsnKind::ECALL if record.rs1().unwrap().value == SECP256K1_DOUBLE => {
+                    secp256k1_double_records.push(record);
+                }
+                InsnKind::ECALL if record.rs1().unwrap().value == ED25519_ADD => {
+                    ed25519_add_records.push(record);
+                }
                 // other type of ecalls are handled by dummy ecall instruction
                 _ => {
                     // it's safe to unwrap as all_records are initialized with Vec::new()
@@ -343,15 +408,15 @@ impl<E: ExtensionField> Rv32imConfig<E> {
         for (insn_kind, (_, records)) in
             izip!(InsnKind::iter(), &all_records).sorted_by_key(|(_, (_, a))| Reverse(a.len()))
         {
-            tracing::info!("tracer generated {:?} {} records", insn_kind, records.len());
+            tracing::debug!("tracer generated {:?} {} records", insn_kind, records.len());
         }
 
         macro_rules! assign_opcode {
             ($insn_kind:ident,$instruction:ty,$config:ident) => {
-                witness.assign_opcode_circuit::<$instruction>(
+                witness.append_opcode_circuit::<$instruction>(
                     cs,
                     &self.$config,
-                    all_records.remove(&($insn_kind)).unwrap(),
+                    &all_records.remove(&($insn_kind)).unwrap(),
                 )?;
             };
         }
@@ -405,7 +470,41 @@ impl<E: ExtensionField> Rv32imConfig<E> {
         assign_opcode!(SB, SbInstruction<E>, sb_config);
 
         // ecall / halt
-        witness.assign_opcode_circuit::<HaltInstruction<E>>(cs, &self.halt_config, halt_records)?;
+        witness.append_opcode_circuit::<HaltInstruction<E>>(
+            cs,
+            &self.halt_config,
+            &halt_records,
+        )?;
+        witness.append_opcode_circuit::<KeccakInstruction<E>>(
+            cs,
+            &self.keccak_config,
+            &keccak_records,
+        )?;
+        witness.append_opcode_circuit::<ShaExtendInstruction<E>>(
+            cs,
+            &self.sha_extend_config,
+            &sha_extend_records,
+        )?;
+        witness.append_opcode_circuit::<ShaCompressInstruction<E>>(
+            cs,
+            &self.sha_compress_config,
+            &sha_compress_records,
+        )?;
+        witness.append_opcode_circuit::<Secp256k1AddInstruction<E>>(
+            cs,
+            &self.secp256k1_add_config,
+            &secp256k1_add_records,
+        )?;
+        witness.append_opcode_circuit::<Secp256k1DoubleInstruction<E>>(
+            cs,
+            &self.secp256k1_double_config,
+            &secp256k1_double_records,
+        )?;
+        witness.append_opcode_circuit::<Ed25519AddInstruction<E>>(
+            cs,
+            &self.ed25519_add_config,
+            &ed25519_add_records,
+        )?;
 
         assert_eq!(
             all_records.keys().cloned().collect::<BTreeSet<_>>(),
@@ -429,6 +528,7 @@ impl<E: ExtensionField> Rv32imConfig<E> {
         witness.assign_table_circuit::<XorTableCircuit<E>>(cs, &self.xor_table_config, &())?;
         witness.assign_table_circuit::<LtuTableCircuit<E>>(cs, &self.ltu_config, &())?;
         witness.assign_table_circuit::<PowTableCircuit<E>>(cs, &self.pow_config, &())?;
+        witness.assign_table_circuit::<ShlTableCircuit<E>>(cs, &self.shl_table_config, &())?;
 
         Ok(())
     }
@@ -466,10 +566,10 @@ impl<E: ExtensionField> DummyExtraConfig<E> {
 
         macro_rules! assign_opcode {
             ($insn_kind:ident,$instruction:ty,$config:ident) => {
-                witness.assign_opcode_circuit::<$instruction>(
+                witness.append_opcode_circuit::<$instruction>(
                     cs,
                     &self.$config,
-                    steps.remove(&($insn_kind)).unwrap(),
+                    &steps.remove(&($insn_kind)).unwrap(),
                 )?;
             };
         }
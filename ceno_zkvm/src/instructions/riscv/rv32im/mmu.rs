@@ -9,7 +9,8 @@ use crate::{
     structs::{ProgramParams, ZKVMConstraintSystem, ZKVMFixedTraces, ZKVMWitnesses},
     tables::{
         HintsCircuit, MemFinalRecord, MemInitRecord, NonVolatileTable, PubIOCircuit, PubIOTable,
-        RegTable, RegTableCircuit, StaticMemCircuit, StaticMemTable, TableCircuit,
+        PubOutCircuit, PubOutTable, RegTable, RegTableCircuit, StaticMemCircuit, StaticMemTable,
+        TableCircuit,
     },
 };
 
@@ -20,6 +21,8 @@ pub struct MmuConfig<E: ExtensionField> {
     pub static_mem_config: <StaticMemCircuit<E> as TableCircuit<E>>::TableConfig,
     /// Initialization of public IO.
     pub public_io_config: <PubIOCircuit<E> as TableCircuit<E>>::TableConfig,
+    /// Initialization and finalization of public output.
+    pub public_output_config: <PubOutCircuit<E> as TableCircuit<E>>::TableConfig,
     /// Initialization of hints.
     pub hints_config: <HintsCircuit<E> as TableCircuit<E>>::TableConfig,
     pub params: ProgramParams,
@@ -32,12 +35,14 @@ impl<E: ExtensionField> MmuConfig<E> {
         let static_mem_config = cs.register_table_circuit::<StaticMemCircuit<E>>();
 
         let public_io_config = cs.register_table_circuit::<PubIOCircuit<E>>();
+        let public_output_config = cs.register_table_circuit::<PubOutCircuit<E>>();
         let hints_config = cs.register_table_circuit::<HintsCircuit<E>>();
 
         Self {
             reg_config,
             static_mem_config,
             public_io_config,
+            public_output_config,
             hints_config,
             params: cs.params.clone(),
         }
@@ -50,11 +55,13 @@ impl<E: ExtensionField> MmuConfig<E> {
         reg_init: &[MemInitRecord],
         static_mem_init: &[MemInitRecord],
         io_addrs: &[Addr],
+        output_addrs: &[Addr],
     ) {
         assert!(
             chain!(
                 static_mem_init.iter_addresses(),
                 io_addrs.iter_addresses(),
+                output_addrs.iter_addresses(),
                 // TODO: optimize with min_max and Range.
                 self.params.platform.hints.iter_addresses(),
             )
@@ -71,9 +78,15 @@ impl<E: ExtensionField> MmuConfig<E> {
         );
 
         fixed.register_table_circuit::<PubIOCircuit<E>>(cs, &self.public_io_config, io_addrs);
+        fixed.register_table_circuit::<PubOutCircuit<E>>(
+            cs,
+            &self.public_output_config,
+            output_addrs,
+        );
         fixed.register_table_circuit::<HintsCircuit<E>>(cs, &self.hints_config, &());
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn assign_table_circuit(
         &self,
         cs: &ZKVMConstraintSystem<E>,
@@ -81,6 +94,7 @@ impl<E: ExtensionField> MmuConfig<E> {
         reg_final: &[MemFinalRecord],
         static_mem_final: &[MemFinalRecord],
         io_cycles: &[Cycle],
+        output_cycles: &[Cycle],
         hints_final: &[MemFinalRecord],
     ) -> Result<(), ZKVMError> {
         witness.assign_table_circuit::<RegTableCircuit<E>>(cs, &self.reg_config, reg_final)?;
@@ -93,6 +107,12 @@ impl<E: ExtensionField> MmuConfig<E> {
 
         witness.assign_table_circuit::<PubIOCircuit<E>>(cs, &self.public_io_config, io_cycles)?;
 
+        witness.assign_table_circuit::<PubOutCircuit<E>>(
+            cs,
+            &self.public_output_config,
+            output_cycles,
+        )?;
+
         witness.assign_table_circuit::<HintsCircuit<E>>(cs, &self.hints_config, hints_final)?;
 
         Ok(())
@@ -114,6 +134,10 @@ impl<E: ExtensionField> MmuConfig<E> {
     pub fn public_io_len(&self) -> usize {
         <PubIOTable as NonVolatileTable>::len(&self.params)
     }
+
+    pub fn public_output_len(&self) -> usize {
+        <PubOutTable as NonVolatileTable>::len(&self.params)
+    }
 }
 
 pub struct MemPadder {
@@ -48,6 +48,7 @@ impl<E: ExtensionField> SInstructionConfig<E> {
             rs1.id.expr(),
             rs2.id.expr(),
             imm.clone(),
+            vm_state.compressed.expr(),
         ))?;
 
         // Memory
@@ -26,7 +26,14 @@ impl<E: ExtensionField> ZKVMConstraintSystem<E> {
                 None
             };
 
-            let circuit_pk = cs.key_gen(&vm_pk.pp, fixed_traces);
+            let mut circuit_pk = cs.key_gen(&vm_pk.pp, fixed_traces);
+            // In continuation mode, the first segment must start from this state.
+            if let Some(init_state) = vm_fixed_traces.circuit_init_states.remove(&c_name) {
+                let init_state_commit = PCS::batch_commit(&vm_pk.pp, &init_state.into_mles())
+                    .map_err(ZKVMError::PCSError)?;
+                circuit_pk.vk.init_state_commit =
+                    Some(PCS::get_pure_commitment(&init_state_commit));
+            }
             assert!(vm_pk.circuit_pks.insert(c_name, circuit_pk).is_none());
         }
 
@@ -7,6 +7,7 @@
 
 pub mod error;
 pub mod instructions;
+pub mod recursion;
 pub mod scheme;
 pub mod tables;
 pub use utils::u64vec;
@@ -0,0 +1,43 @@
+//! Circuits which verify parts of a ceno proof, so that proofs can be aggregated by proving
+//! their verification with another ceno proof.
+//!
+//! So far this covers the sumchecks of the tower verification:
+//! [`TowerVerify::verify_and_record`] records them, and the circuits of
+//! [`SumcheckVerifierConfig`] prove that they hold. Their challenges are public values of the
+//! recursion proof rather than drawn from a transcript in circuit, and the rest of the
+//! verification, including the PCS openings, is still done natively.
+//!
+//! [`TowerVerify::verify_and_record`]: crate::scheme::verifier::TowerVerify::verify_and_record
+
+use ff_ext::ExtensionField;
+
+use crate::{
+    circuit_builder::CircuitBuilder,
+    error::ZKVMError,
+    witness::{LkMultiplicity, RowMajorMatrix},
+};
+
+mod ext;
+pub use ext::ExtWitIn;
+
+mod sumcheck;
+pub use sumcheck::*;
+
+/// A circuit with zero checks, like [`crate::instructions::Instruction`], which is assigned from
+/// parts of a proof rather than from execution steps.
+pub trait RecursionCircuit<E: ExtensionField> {
+    type Config: Send + Sync;
+    type Input: Send + Sync + ?Sized;
+
+    fn name() -> String;
+
+    fn construct_circuit(
+        circuit_builder: &mut CircuitBuilder<E>,
+    ) -> Result<Self::Config, ZKVMError>;
+
+    fn assign_instances(
+        config: &Self::Config,
+        num_witin: usize,
+        input: &Self::Input,
+    ) -> Result<(RowMajorMatrix<E::BaseField>, LkMultiplicity), ZKVMError>;
+}
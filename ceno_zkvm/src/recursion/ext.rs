@@ -0,0 +1,101 @@
+//! Elements of the extension field in circuits over the base field.
+//!
+//! An element is represented by its `E::DEGREE` limbs, as returned by `E::as_bases`.
+
+use ff::Field;
+use ff_ext::ExtensionField;
+use itertools::izip;
+
+use crate::{
+    circuit_builder::CircuitBuilder,
+    expression::{Expression, ToExpr, WitIn},
+    set_val,
+};
+
+#[derive(Clone, Debug)]
+pub struct ExtWitIn {
+    pub limbs: Vec<WitIn>,
+}
+
+impl ExtWitIn {
+    pub fn new<E: ExtensionField, NR: Into<String>, N: FnOnce() -> NR>(
+        cb: &mut CircuitBuilder<E>,
+        name_fn: N,
+    ) -> Self {
+        let name: String = name_fn().into();
+        Self {
+            limbs: (0..E::DEGREE)
+                .map(|l| cb.create_witin(|| format!("{name}_limb_{l}")))
+                .collect(),
+        }
+    }
+
+    pub fn expr<E: ExtensionField>(&self) -> Vec<Expression<E>> {
+        self.limbs.iter().map(|limb| limb.expr()).collect()
+    }
+
+    pub fn assign<E: ExtensionField>(&self, instance: &mut [E::BaseField], value: &E) {
+        for (limb, v) in izip!(&self.limbs, value.as_bases()) {
+            set_val!(instance, limb, *v);
+        }
+    }
+}
+
+/// The limbs of a constant.
+pub(crate) fn ext_constant<E: ExtensionField>(c: &E) -> Vec<Expression<E>> {
+    c.as_bases()
+        .iter()
+        .map(|v| Expression::Constant(*v))
+        .collect()
+}
+
+pub(crate) fn ext_add<E: ExtensionField>(
+    a: &[Expression<E>],
+    b: &[Expression<E>],
+) -> Vec<Expression<E>> {
+    izip!(a, b).map(|(a, b)| a.clone() + b.clone()).collect()
+}
+
+pub(crate) fn ext_sub<E: ExtensionField>(
+    a: &[Expression<E>],
+    b: &[Expression<E>],
+) -> Vec<Expression<E>> {
+    izip!(a, b).map(|(a, b)| a.clone() - b.clone()).collect()
+}
+
+/// `c * a` for a constant `c` of the base field.
+pub(crate) fn ext_scale<E: ExtensionField>(
+    a: &[Expression<E>],
+    c: E::BaseField,
+) -> Vec<Expression<E>> {
+    a.iter()
+        .map(|a| Expression::Constant(c) * a.clone())
+        .collect()
+}
+
+/// `a * b`, with the degree of `a` plus the degree of `b`.
+pub(crate) fn ext_mul<E: ExtensionField>(
+    a: &[Expression<E>],
+    b: &[Expression<E>],
+) -> Vec<Expression<E>> {
+    let basis = (0..E::DEGREE)
+        .map(|i| {
+            let mut limbs = vec![E::BaseField::ZERO; E::DEGREE];
+            limbs[i] = E::BaseField::ONE;
+            E::from_bases(&limbs)
+        })
+        .collect::<Vec<_>>();
+
+    let mut product = vec![Expression::ZERO; E::DEGREE];
+    for (a, x) in izip!(a, &basis) {
+        for (b, y) in izip!(b, &basis) {
+            // the product of two basis elements is a constant combination of the basis
+            for (out, m) in izip!(&mut product, (*x * *y).as_bases()) {
+                if *m != E::BaseField::ZERO {
+                    *out = out.clone() + Expression::Constant(*m) * a.clone() * b.clone();
+                }
+            }
+        }
+    }
+    product
+}
@@ -0,0 +1,633 @@
+//! Circuits which verify sumchecks.
+//!
+//! Each row of [`SumcheckRoundCircuit`] checks one round of a sumcheck: the round polynomial
+//! sums to the claim over `{0, 1}`, and its evaluation at the challenge is the next claim.
+//! The rounds of all sumchecks are laid out in consecutive slots, and the claim is passed from
+//! a slot to the next through read and write records of [`RAMType::SumcheckClaim`].
+//!
+//! The public part of every round, i.e. the challenge and, on the first and last rounds,
+//! the claimed sum and the expected evaluation, is looked up in [`SumcheckRoundTableCircuit`],
+//! whose columns are public values. The round polynomials stay private.
+
+use std::{collections::HashMap, iter::repeat, marker::PhantomData, sync::Arc};
+
+use ff::{Field, PrimeField};
+use ff_ext::ExtensionField;
+use itertools::{Itertools, chain, izip};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    circuit_builder::{CircuitBuilder, SetTableSpec},
+    error::ZKVMError,
+    expression::{Expression, StructuralWitIn, ToExpr, WitIn},
+    instructions::InstancePaddingStrategy,
+    scheme::constants::{MIN_PAR_SIZE, NUM_FANIN},
+    set_val,
+    state::StateCircuit,
+    structs::{RAMType, ROMType, ZKVMConstraintSystem, ZKVMFixedTraces, ZKVMWitnesses},
+    tables::TableCircuit,
+    utils::next_pow2_instance_padding,
+    witness::{LkMultiplicity, RowMajorMatrix},
+};
+
+use super::{
+    RecursionCircuit,
+    ext::{ExtWitIn, ext_add, ext_constant, ext_mul, ext_scale, ext_sub},
+};
+
+/// Degree of the round polynomials of the tower sumchecks, including the eq polynomial.
+pub const TOWER_SUMCHECK_DEGREE: usize = NUM_FANIN + 1;
+
+/// Upper bound on the number of rounds proven at once.
+const MAX_SUMCHECK_ROUNDS: usize = 1 << 24;
+
+// Layout of the public values. The challenge, the claimed sum and the expected evaluation are
+// given by `E::DEGREE` limbs each, every public value but the number of rounds is one entry
+// per round.
+pub const NUM_ROUNDS_IDX: usize = 0;
+pub const IS_FIRST_ROUND_IDX: usize = 1;
+pub const IS_LAST_ROUND_IDX: usize = 2;
+pub const CHALLENGE_IDX: usize = 3;
+
+fn claimed_sum_idx<E: ExtensionField>() -> usize {
+    CHALLENGE_IDX + E::DEGREE
+}
+
+fn expected_evaluation_idx<E: ExtensionField>() -> usize {
+    CHALLENGE_IDX + 2 * E::DEGREE
+}
+
+/// The statement of a sumcheck: the round polynomials sum to `claimed_sum`, and reduce it to
+/// `expected_evaluation` at the point `challenges`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SumcheckStatement<E: ExtensionField> {
+    pub claimed_sum: E,
+    pub challenges: Vec<E>,
+    pub expected_evaluation: E,
+}
+
+/// A sumcheck with its round polynomials, as evaluations on `0..=degree`.
+#[derive(Clone, Debug)]
+pub struct SumcheckInstance<E: ExtensionField> {
+    pub statement: SumcheckStatement<E>,
+    pub round_evals: Vec<Vec<E>>,
+}
+
+/// The public values of a proof of the given sumchecks.
+pub fn sumcheck_public_values<'a, E: ExtensionField>(
+    statements: impl IntoIterator<Item = &'a SumcheckStatement<E>>,
+) -> Vec<Vec<E::BaseField>> {
+    let mut raw_pi = vec![vec![]; expected_evaluation_idx::<E>() + E::DEGREE];
+    let mut num_rounds = 0;
+    for statement in statements {
+        let last = statement.challenges.len().saturating_sub(1);
+        for (i, challenge) in statement.challenges.iter().enumerate() {
+            let values = chain!(
+                [
+                    E::BaseField::from((i == 0) as u64),
+                    E::BaseField::from((i == last) as u64)
+                ],
+                challenge.as_bases().iter().copied(),
+                statement.claimed_sum.as_bases().iter().copied(),
+                statement.expected_evaluation.as_bases().iter().copied(),
+            );
+            for (column, v) in izip!(&mut raw_pi[IS_FIRST_ROUND_IDX..], values) {
+                column.push(v);
+            }
+        }
+        num_rounds += statement.challenges.len();
+    }
+    // the columns of the round table are padded to its height
+    for column in &mut raw_pi[IS_FIRST_ROUND_IDX..] {
+        column.resize(next_pow2_instance_padding(num_rounds), E::BaseField::ZERO);
+    }
+    raw_pi[NUM_ROUNDS_IDX] = vec![E::BaseField::from(num_rounds as u64)];
+    raw_pi
+}
+
+/// `m[k][i]` is the coefficient of `X^k` in the polynomial of degree `degree` which is 1 on `i`
+/// and 0 on the rest of `0..=degree`.
+fn interpolation_matrix<F: PrimeField>(degree: usize) -> Vec<Vec<F>> {
+    let mut m = vec![vec![F::ZERO; degree + 1]; degree + 1];
+    for i in 0..=degree {
+        let mut coeffs = vec![F::ONE];
+        let mut denominator = F::ONE;
+        for j in (0..=degree).filter(|j| *j != i) {
+            // multiply by (X - j)
+            let j_f = F::from(j as u64);
+            let mut next = vec![F::ZERO; coeffs.len() + 1];
+            for (k, c) in coeffs.iter().enumerate() {
+                next[k + 1] += *c;
+                next[k] -= *c * j_f;
+            }
+            coeffs = next;
+            denominator *= F::from(i as u64) - j_f;
+        }
+        let denominator_inv = denominator.invert().unwrap();
+        for (k, c) in coeffs.into_iter().enumerate() {
+            m[k][i] = c * denominator_inv;
+        }
+    }
+    m
+}
+
+/// Evaluate at `r` the round polynomial given by its evaluations on `0..=degree`.
+fn eval_round_poly<E: ExtensionField>(m: &[Vec<E::BaseField>], evals: &[E], r: E) -> E {
+    m.iter()
+        .rev()
+        .map(|row| izip!(row, evals).map(|(m, e)| *e * *m).sum::<E>())
+        .fold(E::ZERO, |acc, coeff| acc * r + coeff)
+}
+
+/// One row of the round circuit.
+struct SumcheckRound<'a, E: ExtensionField> {
+    slot: usize,
+    claim: E,
+    evals: &'a [E],
+    challenge: E,
+    next_claim: E,
+    is_first: bool,
+    is_last: bool,
+    statement: &'a SumcheckStatement<E>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SumcheckRoundConfig {
+    slot: WitIn,
+    claim: ExtWitIn,
+    evals: Vec<ExtWitIn>,
+    challenge: ExtWitIn,
+    // challenge^2 ..= challenge^degree
+    challenge_pows: Vec<ExtWitIn>,
+    next_claim: ExtWitIn,
+    is_first: WitIn,
+    is_last: WitIn,
+    claimed_sum: ExtWitIn,
+    expected_evaluation: ExtWitIn,
+    // the claim read from the previous round, zero on first rounds
+    claim_in: ExtWitIn,
+    // the claim written for the next round, zero on last rounds
+    claim_out: ExtWitIn,
+}
+
+pub struct SumcheckRoundCircuit<E>(PhantomData<E>);
+
+impl<E: ExtensionField> RecursionCircuit<E> for SumcheckRoundCircuit<E> {
+    type Config = SumcheckRoundConfig;
+    type Input = [SumcheckInstance<E>];
+
+    fn name() -> String {
+        "SUMCHECK_ROUND".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::Config, ZKVMError> {
+        let slot = cb.create_witin(|| "slot");
+        let claim = ExtWitIn::new(cb, || "claim");
+        let evals = (0..=TOWER_SUMCHECK_DEGREE)
+            .map(|i| ExtWitIn::new(cb, || format!("eval_{i}")))
+            .collect_vec();
+        let challenge = ExtWitIn::new(cb, || "challenge");
+        let challenge_pows = (2..=TOWER_SUMCHECK_DEGREE)
+            .map(|k| ExtWitIn::new(cb, || format!("challenge_pow_{k}")))
+            .collect_vec();
+        let next_claim = ExtWitIn::new(cb, || "next_claim");
+        let is_first = cb.create_witin(|| "is_first");
+        let is_last = cb.create_witin(|| "is_last");
+        let claimed_sum = ExtWitIn::new(cb, || "claimed_sum");
+        let expected_evaluation = ExtWitIn::new(cb, || "expected_evaluation");
+        let claim_in = ExtWitIn::new(cb, || "claim_in");
+        let claim_out = ExtWitIn::new(cb, || "claim_out");
+
+        let require_ext_zero = |cb: &mut CircuitBuilder<E>,
+                                name: &str,
+                                limbs: Vec<Expression<E>>|
+         -> Result<(), ZKVMError> {
+            for (l, limb) in limbs.into_iter().enumerate() {
+                cb.require_zero(|| format!("{name}_limb_{l}"), limb)?;
+            }
+            Ok(())
+        };
+
+        // p(0) + p(1) = claim
+        require_ext_zero(
+            cb,
+            "round_sum",
+            ext_sub(&ext_add(&evals[0].expr(), &evals[1].expr()), &claim.expr()),
+        )?;
+
+        // powers of the challenge
+        let pows = chain!(
+            [ext_constant(&E::ONE), challenge.expr()],
+            challenge_pows.iter().map(|pow| pow.expr())
+        )
+        .collect_vec();
+        for (k, pow) in challenge_pows.iter().enumerate() {
+            require_ext_zero(
+                cb,
+                "challenge_pow",
+                ext_sub(&pow.expr(), &ext_mul(&pows[k + 1], &challenge.expr())),
+            )?;
+        }
+
+        // next_claim = p(challenge), with p interpolated from its evaluations
+        let m = interpolation_matrix::<E::BaseField>(TOWER_SUMCHECK_DEGREE);
+        let p_at_challenge =
+            izip!(&m, &pows).fold(vec![Expression::ZERO; E::DEGREE], |acc, (row, pow)| {
+                let coeff = izip!(row, &evals)
+                    .fold(vec![Expression::ZERO; E::DEGREE], |coeff, (m, eval)| {
+                        ext_add(&coeff, &ext_scale(&eval.expr(), *m))
+                    });
+                ext_add(&acc, &ext_mul(&coeff, pow))
+            });
+        require_ext_zero(
+            cb,
+            "next_claim",
+            ext_sub(&next_claim.expr(), &p_at_challenge),
+        )?;
+
+        // the first round starts from the claimed sum, the last round ends at the expected
+        // evaluation
+        require_ext_zero(
+            cb,
+            "first_round",
+            ext_sub(&claim.expr(), &claimed_sum.expr())
+                .into_iter()
+                .map(|diff| is_first.expr() * diff)
+                .collect(),
+        )?;
+        require_ext_zero(
+            cb,
+            "last_round",
+            ext_sub(&next_claim.expr(), &expected_evaluation.expr())
+                .into_iter()
+                .map(|diff| is_last.expr() * diff)
+                .collect(),
+        )?;
+
+        // the claim is chained between the rounds of a sumcheck
+        require_ext_zero(
+            cb,
+            "claim_in",
+            izip!(claim_in.expr(), claim.expr())
+                .map(|(claim_in, claim)| claim_in - (Expression::ONE - is_first.expr()) * claim)
+                .collect(),
+        )?;
+        require_ext_zero(
+            cb,
+            "claim_out",
+            izip!(claim_out.expr(), next_claim.expr())
+                .map(|(claim_out, next)| claim_out - (Expression::ONE - is_last.expr()) * next)
+                .collect(),
+        )?;
+        cb.read_record(
+            || "read_claim",
+            RAMType::SumcheckClaim,
+            chain!(
+                [(RAMType::SumcheckClaim as usize).into(), slot.expr()],
+                claim_in.expr()
+            )
+            .collect(),
+        )?;
+        cb.write_record(
+            || "write_claim",
+            RAMType::SumcheckClaim,
+            chain!(
+                [
+                    (RAMType::SumcheckClaim as usize).into(),
+                    slot.expr() + Expression::ONE
+                ],
+                claim_out.expr()
+            )
+            .collect(),
+        )?;
+
+        // the public part of the round
+        cb.lk_record(
+            || "public_round",
+            ROMType::Sumcheck,
+            chain!(
+                [slot.expr(), is_first.expr(), is_last.expr()],
+                challenge.expr(),
+                claimed_sum.expr(),
+                expected_evaluation.expr()
+            )
+            .collect(),
+        )?;
+
+        Ok(SumcheckRoundConfig {
+            slot,
+            claim,
+            evals,
+            challenge,
+            challenge_pows,
+            next_claim,
+            is_first,
+            is_last,
+            claimed_sum,
+            expected_evaluation,
+            claim_in,
+            claim_out,
+        })
+    }
+
+    fn assign_instances(
+        config: &Self::Config,
+        num_witin: usize,
+        sumchecks: &Self::Input,
+    ) -> Result<(RowMajorMatrix<E::BaseField>, LkMultiplicity), ZKVMError> {
+        let m = interpolation_matrix::<E::BaseField>(TOWER_SUMCHECK_DEGREE);
+        let mut rounds = vec![];
+        for (
+            i,
+            SumcheckInstance {
+                statement,
+                round_evals,
+            },
+        ) in sumchecks.iter().enumerate()
+        {
+            if round_evals.is_empty() || round_evals.len() != statement.challenges.len() {
+                return Err(ZKVMError::InvalidWitness(format!(
+                    "sumcheck {i} has {} rounds and {} challenges",
+                    round_evals.len(),
+                    statement.challenges.len()
+                )));
+            }
+            let mut claim = statement.claimed_sum;
+            for (j, (evals, challenge)) in izip!(round_evals, &statement.challenges).enumerate() {
+                if evals.len() != TOWER_SUMCHECK_DEGREE + 1 {
+                    return Err(ZKVMError::InvalidWitness(format!(
+                        "round {j} of sumcheck {i} has {} evaluations, expected {}",
+                        evals.len(),
+                        TOWER_SUMCHECK_DEGREE + 1
+                    )));
+                }
+                let next_claim = eval_round_poly(&m, evals, *challenge);
+                rounds.push(SumcheckRound {
+                    slot: rounds.len(),
+                    claim,
+                    evals,
+                    challenge: *challenge,
+                    next_claim,
+                    is_first: j == 0,
+                    is_last: j + 1 == round_evals.len(),
+                    statement,
+                });
+                claim = next_claim;
+            }
+        }
+
+        let lk_multiplicity = LkMultiplicity::default();
+        let mut witness = RowMajorMatrix::<E::BaseField>::new(
+            rounds.len(),
+            num_witin,
+            InstancePaddingStrategy::RepeatLast,
+        );
+        witness
+            .par_iter_mut()
+            .with_min_len(MIN_PAR_SIZE)
+            .zip(rounds.par_iter())
+            .for_each(|(row, round)| {
+                let mut lk_multiplicity = lk_multiplicity.clone();
+                set_val!(row, config.slot, round.slot as u64);
+                config.claim.assign(row, &round.claim);
+                for (eval, v) in izip!(&config.evals, round.evals) {
+                    eval.assign(row, v);
+                }
+                config.challenge.assign(row, &round.challenge);
+                let mut pow = round.challenge;
+                for challenge_pow in &config.challenge_pows {
+                    pow *= round.challenge;
+                    challenge_pow.assign(row, &pow);
+                }
+                config.next_claim.assign(row, &round.next_claim);
+                set_val!(row, config.is_first, round.is_first as u64);
+                set_val!(row, config.is_last, round.is_last as u64);
+                config.claimed_sum.assign(row, &round.statement.claimed_sum);
+                config
+                    .expected_evaluation
+                    .assign(row, &round.statement.expected_evaluation);
+                let claim_in = if round.is_first { E::ZERO } else { round.claim };
+                config.claim_in.assign(row, &claim_in);
+                let claim_out = if round.is_last {
+                    E::ZERO
+                } else {
+                    round.next_claim
+                };
+                config.claim_out.assign(row, &claim_out);
+                lk_multiplicity.increment(ROMType::Sumcheck, round.slot as u64);
+            });
+
+        Ok((witness, lk_multiplicity))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SumcheckRoundTableConfig {
+    slot: StructuralWitIn,
+    mlt: WitIn,
+}
+
+/// The public part of every round, indexed by slot.
+pub struct SumcheckRoundTableCircuit<E>(PhantomData<E>);
+
+impl<E: ExtensionField> TableCircuit<E> for SumcheckRoundTableCircuit<E> {
+    type TableConfig = SumcheckRoundTableConfig;
+    type FixedInput = ();
+    /// The number of rounds.
+    type WitnessInput = usize;
+
+    fn name() -> String {
+        "SUMCHECK_ROUND_TABLE".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::TableConfig, ZKVMError> {
+        let slot = cb.create_structural_witin(|| "slot", MAX_SUMCHECK_ROUNDS, 0, 1);
+        let mlt = cb.create_witin(|| "mlt");
+
+        let is_first = cb.query_instance_limbs(|| "is_first_round", IS_FIRST_ROUND_IDX, 1)?;
+        let is_last = cb.query_instance_limbs(|| "is_last_round", IS_LAST_ROUND_IDX, 1)?;
+        let challenge = cb.query_instance_limbs(|| "challenge", CHALLENGE_IDX, E::DEGREE)?;
+        let claimed_sum =
+            cb.query_instance_limbs(|| "claimed_sum", claimed_sum_idx::<E>(), E::DEGREE)?;
+        let expected_evaluation = cb.query_instance_limbs(
+            || "expected_evaluation",
+            expected_evaluation_idx::<E>(),
+            E::DEGREE,
+        )?;
+
+        let record_exprs = chain!(
+            [slot.expr()],
+            chain!(
+                is_first,
+                is_last,
+                challenge,
+                claimed_sum,
+                expected_evaluation
+            )
+            .map(|instance| instance.expr())
+        )
+        .collect();
+
+        cb.lk_table_record(
+            || "record",
+            SetTableSpec {
+                len: None,
+                structural_witins: vec![slot],
+            },
+            ROMType::Sumcheck,
+            record_exprs,
+            mlt.expr(),
+        )?;
+
+        Ok(SumcheckRoundTableConfig { slot, mlt })
+    }
+
+    fn generate_fixed_traces(
+        _config: &Self::TableConfig,
+        _num_fixed: usize,
+        _input: &Self::FixedInput,
+    ) -> RowMajorMatrix<E::BaseField> {
+        RowMajorMatrix::<E::BaseField>::new(0, 0, InstancePaddingStrategy::Default)
+    }
+
+    fn assign_instances(
+        config: &Self::TableConfig,
+        num_witin: usize,
+        num_structural_witin: usize,
+        multiplicity: &[HashMap<u64, usize>],
+        num_rounds: &Self::WitnessInput,
+    ) -> Result<RowMajorMatrix<E::BaseField>, ZKVMError> {
+        let slot = StructuralWitIn {
+            id: config.slot.id + (num_witin as u16),
+            ..config.slot
+        };
+        let padding_fn = move |row: u64, col: u64| {
+            if col == slot.id as u64 { row } else { 0 }
+        };
+        let mut witness = RowMajorMatrix::<E::BaseField>::new(
+            *num_rounds,
+            num_witin + num_structural_witin,
+            InstancePaddingStrategy::Custom(Arc::new(padding_fn)),
+        );
+
+        let multiplicity = &multiplicity[ROMType::Sumcheck as usize];
+        witness
+            .par_iter_mut()
+            .with_min_len(MIN_PAR_SIZE)
+            .enumerate()
+            .for_each(|(i, row)| {
+                let mlt = multiplicity.get(&(i as u64)).copied().unwrap_or(0);
+                set_val!(row, config.mlt, mlt as u64);
+                set_val!(row, slot, i as u64);
+            });
+
+        Ok(witness)
+    }
+}
+
+/// The claim chain starts at slot 0 and ends after the last round.
+pub struct SumcheckClaimState;
+
+impl<E: ExtensionField> StateCircuit<E> for SumcheckClaimState {
+    fn initial_global_state(cb: &mut CircuitBuilder<E>) -> Result<Expression<E>, ZKVMError> {
+        let states = chain!(
+            [(RAMType::SumcheckClaim as usize).into(), Expression::ZERO],
+            repeat(Expression::ZERO).take(E::DEGREE)
+        )
+        .collect();
+
+        Ok(cb.rlc_chip_record(states))
+    }
+
+    fn finalize_global_state(cb: &mut CircuitBuilder<E>) -> Result<Expression<E>, ZKVMError> {
+        let num_rounds = cb.query_instance_limbs(|| "num_rounds", NUM_ROUNDS_IDX, 1)?[0];
+        let states = chain!(
+            [(RAMType::SumcheckClaim as usize).into(), num_rounds.expr()],
+            repeat(Expression::ZERO).take(E::DEGREE)
+        )
+        .collect();
+
+        Ok(cb.rlc_chip_record(states))
+    }
+}
+
+/// The circuits which prove a batch of tower sumchecks.
+///
+/// The proof has the public values returned by [`sumcheck_public_values`], and does not halt:
+/// verify it with `ZKVMVerifier::verify_proof_with_raw_pi(proof, transcript, raw_pi, false)`.
+pub struct SumcheckVerifierConfig<E: ExtensionField> {
+    round_config: SumcheckRoundConfig,
+    table_config: SumcheckRoundTableConfig,
+    phantom: PhantomData<E>,
+}
+
+impl<E: ExtensionField> SumcheckVerifierConfig<E> {
+    pub fn construct_circuits(cs: &mut ZKVMConstraintSystem<E>) -> Self {
+        let round_config = cs.register_recursion_circuit::<SumcheckRoundCircuit<E>>();
+        let table_config = cs.register_table_circuit::<SumcheckRoundTableCircuit<E>>();
+        cs.register_global_state::<SumcheckClaimState>();
+        Self {
+            round_config,
+            table_config,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn generate_fixed_traces(
+        &self,
+        cs: &ZKVMConstraintSystem<E>,
+        fixed: &mut ZKVMFixedTraces<E>,
+    ) {
+        fixed.register_recursion_circuit::<SumcheckRoundCircuit<E>>(cs);
+        fixed.register_table_circuit::<SumcheckRoundTableCircuit<E>>(cs, &self.table_config, &());
+    }
+
+    pub fn generate_witness(
+        &self,
+        cs: &ZKVMConstraintSystem<E>,
+        sumchecks: &[SumcheckInstance<E>],
+    ) -> Result<ZKVMWitnesses<E>, ZKVMError> {
+        let num_rounds = sumchecks
+            .iter()
+            .map(|sumcheck| sumcheck.round_evals.len())
+            .sum::<usize>();
+        if num_rounds == 0 {
+            return Err(ZKVMError::InvalidWitness(
+                "no sumcheck round to prove".into(),
+            ));
+        }
+
+        let mut witness = ZKVMWitnesses::default();
+        witness.assign_recursion_circuit::<SumcheckRoundCircuit<E>>(
+            cs,
+            &self.round_config,
+            sumchecks,
+        )?;
+        witness.finalize_lk_multiplicities(false);
+        witness.assign_table_circuit::<SumcheckRoundTableCircuit<E>>(
+            cs,
+            &self.table_config,
+            &num_rounds,
+        )?;
+        Ok(witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff_ext::ExtensionField;
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+
+    use super::{eval_round_poly, interpolation_matrix};
+
+    #[test]
+    fn test_eval_round_poly() {
+        type E = GoldilocksExt2;
+        // p(X) = 3 + 2X + X^3
+        let p = |x: E| E::from(3) + x * E::from(2) + x * x * x;
+        let m = interpolation_matrix::<Goldilocks>(3);
+        let evals = (0..=3).map(|x| p(E::from(x))).collect::<Vec<_>>();
+        let r = E::from_bases(&[Goldilocks::from(11), Goldilocks::from(5)]);
+        assert_eq!(eval_round_poly(&m, &evals, r), p(r));
+    }
+}
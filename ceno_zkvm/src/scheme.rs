@@ -1,13 +1,21 @@
+use ceno_emul::host_utils::decode_frames;
 use ff_ext::ExtensionField;
+use goldilocks::SmallField;
 use itertools::Itertools;
 use mpcs::PolynomialCommitmentScheme;
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt::Debug};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{collections::BTreeMap, fmt::Debug, path::Path};
 use sumcheck::structs::IOPProverMessage;
 
-use crate::structs::TowerProofs;
+use crate::{
+    error::ZKVMError,
+    instructions::riscv::constants::{EXIT_CODE_IDX, PUBLIC_OUTPUT_IDX},
+    scheme::envelope::PayloadKind,
+    structs::TowerProofs,
+};
 
 pub mod constants;
+pub mod envelope;
 pub mod prover;
 pub mod utils;
 pub mod verifier;
@@ -16,7 +24,7 @@ pub mod mock_prover;
 #[cfg(test)]
 mod tests;
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ZKVMOpcodeProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     // TODO support >1 opcodes
     pub num_instances: usize,
@@ -65,6 +73,12 @@ pub struct ZKVMTableProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>
     pub wits_commit: PCS::Commitment,
     pub wits_in_evals: Vec<E>,
     pub wits_opening_proof: PCS::Proof,
+
+    // segment state in continuation mode, committed apart from the other witnesses
+    pub init_state_commit: Option<PCS::Commitment>,
+    pub init_state_opening_proof: Option<PCS::Proof>,
+    pub final_state_commit: Option<PCS::Commitment>,
+    pub final_state_opening_proof: Option<PCS::Proof>,
 }
 
 /// each field will be interpret to (constant) polynomial
@@ -76,6 +90,22 @@ pub struct PublicValues<T: Default + Clone + Debug> {
     end_pc: T,
     end_cycle: T,
     public_io: Vec<T>,
+    public_output: Vec<T>,
+    // Segment state, in continuation mode.
+    init_public_output: Vec<T>,
+}
+
+/// The public part of the machine state at a segment boundary.
+///
+/// Registers, memory and hints are not public: segments are linked by the commitments of the
+/// tables to their content, see [`ZKVMTableProof::init_state_commit`].
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentState {
+    pub pc: u32,
+    /// The cycle of the next instruction.
+    pub cycle: u32,
+    /// Public output, in address order.
+    pub public_output: Vec<u32>,
 }
 
 impl PublicValues<u32> {
@@ -86,6 +116,7 @@ impl PublicValues<u32> {
         end_pc: u32,
         end_cycle: u32,
         public_io: Vec<u32>,
+        public_output: Vec<u32>,
     ) -> Self {
         Self {
             exit_code,
@@ -94,9 +125,42 @@ impl PublicValues<u32> {
             end_pc,
             end_cycle,
             public_io,
+            public_output,
+            init_public_output: vec![0],
         }
     }
+
+    /// Public values of a segment from `init` to `end`, in continuation mode.
+    pub fn new_segment(
+        exit_code: u32,
+        init: SegmentState,
+        end: SegmentState,
+        public_io: Vec<u32>,
+    ) -> Self {
+        Self {
+            exit_code,
+            init_pc: init.pc,
+            init_cycle: init.cycle,
+            end_pc: end.pc,
+            end_cycle: end.cycle,
+            public_io,
+            public_output: end.public_output,
+            init_public_output: init.public_output,
+        }
+    }
+
+    /// The words of the public output segment, in address order.
+    pub fn public_output(&self) -> &[u32] {
+        &self.public_output
+    }
+
     pub fn to_vec<E: ExtensionField>(&self) -> Vec<Vec<E::BaseField>> {
+        let column = |values: &[u32]| {
+            values
+                .iter()
+                .map(|e| E::BaseField::from(*e as u64))
+                .collect_vec()
+        };
         vec![
             vec![E::BaseField::from((self.exit_code & 0xffff) as u64)],
             vec![E::BaseField::from(((self.exit_code >> 16) & 0xffff) as u64)],
@@ -104,10 +168,9 @@ impl PublicValues<u32> {
             vec![E::BaseField::from(self.init_cycle as u64)],
             vec![E::BaseField::from(self.end_pc as u64)],
             vec![E::BaseField::from(self.end_cycle as u64)],
-            self.public_io
-                .iter()
-                .map(|e| E::BaseField::from(*e as u64))
-                .collect(),
+            column(&self.public_io),
+            column(&self.public_output),
+            column(&self.init_public_output),
         ]
     }
 }
@@ -115,7 +178,7 @@ impl PublicValues<u32> {
 /// Map circuit names to
 /// - an opcode or table proof,
 /// - an index unique across both types.
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ZKVMProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     // TODO preserve in serde only for auxiliary public input
     // other raw value can be construct by verifier directly.
@@ -128,7 +191,11 @@ pub struct ZKVMProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
 
 impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProof<E, PCS> {
     pub fn empty(pv: PublicValues<u32>) -> Self {
-        let raw_pi = pv.to_vec::<E>();
+        Self::from_raw_pi(pv.to_vec::<E>())
+    }
+
+    /// An empty proof over public values given as raw field elements.
+    pub fn from_raw_pi(raw_pi: Vec<Vec<E::BaseField>>) -> Self {
         let pi_evals = raw_pi
             .iter()
             .map(|pv| {
@@ -153,10 +220,66 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProof<E, PCS> {
     pub fn update_pi_eval(&mut self, idx: usize, v: E) {
         self.pi_evals[idx] = v;
     }
+
+    /// The commitments to the content of the table `name` at the start and at the end of this
+    /// segment, in continuation mode. A table without a final state keeps its initial content.
+    pub(crate) fn segment_state_commits(
+        &self,
+        name: &str,
+    ) -> (Option<&PCS::Commitment>, Option<&PCS::Commitment>) {
+        let proof = self.table_proofs.get(name).map(|(_, proof)| proof);
+        let init = proof.and_then(|proof| proof.init_state_commit.as_ref());
+        let end = proof
+            .and_then(|proof| proof.final_state_commit.as_ref())
+            .or(init);
+        (init, end)
+    }
 }
 
 impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProof<E, PCS> {
     pub fn num_circuits(&self) -> usize {
         self.opcode_proofs.len() + self.table_proofs.len()
     }
+
+    /// The exit code this proof is bound to, meaningful for a trace that halts.
+    pub fn exit_code(&self) -> u32 {
+        let half = |idx: usize| self.raw_pi[idx][0].to_canonical_u64() as u32;
+        half(EXIT_CODE_IDX) | half(EXIT_CODE_IDX + 1) << 16
+    }
+
+    /// The words of the public output segment this proof is bound to.
+    pub fn public_output_words(&self) -> Vec<u32> {
+        self.raw_pi[PUBLIC_OUTPUT_IDX]
+            .iter()
+            .map(|v| v.to_canonical_u64() as u32)
+            .collect()
+    }
+
+    /// The messages the guest revealed with `ceno_rt::commit`, in order.
+    pub fn public_output(&self) -> Vec<Vec<u8>> {
+        decode_frames(&self.public_output_words())
+    }
+}
+
+impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProof<E, PCS>
+where
+    Self: Serialize + DeserializeOwned,
+{
+    /// Encode the proof in the versioned format of [`envelope`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ZKVMError> {
+        envelope::encode::<E, PCS, _>(PayloadKind::Proof, self)
+    }
+
+    /// Decode a proof produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZKVMError> {
+        envelope::decode::<E, PCS, _>(PayloadKind::Proof, bytes)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ZKVMError> {
+        envelope::save::<E, PCS, _>(PayloadKind::Proof, self, path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ZKVMError> {
+        envelope::load::<E, PCS, _>(PayloadKind::Proof, path)
+    }
 }
@@ -0,0 +1,140 @@
+//! Versioned on-disk format for proofs and keys.
+//!
+//! A file consists of a bincode-encoded [`Header`] followed by the bincode-encoded payload.
+//! The header pins the format version, the kind of payload, and the field and PCS the payload
+//! was produced with, so that a verifier built with a different configuration rejects the file
+//! up front instead of failing somewhere inside the verification.
+
+use std::{fs, io::Cursor, path::Path};
+
+use ff_ext::ExtensionField;
+use mpcs::PolynomialCommitmentScheme;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::error::ZKVMError;
+
+/// Identifies a Ceno file.
+pub const MAGIC: [u8; 8] = *b"CENOZKVM";
+
+/// Bump whenever the encoding of any payload changes.
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadKind {
+    Proof,
+    VerifyingKey,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    pub magic: [u8; 8],
+    pub version: u32,
+    pub kind: PayloadKind,
+    /// [`ExtensionField::ID`] of the extension field.
+    pub field: String,
+    /// [`PolynomialCommitmentScheme::ID`] of the polynomial commitment scheme.
+    pub pcs: String,
+    /// Keccak-256 of the encoded payload.
+    pub payload_digest: [u8; 32],
+}
+
+impl Header {
+    fn new<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
+        kind: PayloadKind,
+        payload: &[u8],
+    ) -> Self {
+        Header {
+            magic: MAGIC,
+            version: FORMAT_VERSION,
+            kind,
+            field: E::ID.to_string(),
+            pcs: PCS::ID.to_string(),
+            payload_digest: keccak256(payload),
+        }
+    }
+
+    fn check<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
+        &self,
+        kind: PayloadKind,
+    ) -> Result<(), ZKVMError> {
+        let mismatch = |what: &str, got: &dyn std::fmt::Debug, expected: &dyn std::fmt::Debug| {
+            Err(ZKVMError::SerializationError(format!(
+                "{what} mismatch: got {got:?}, expected {expected:?}"
+            )))
+        };
+        if self.magic != MAGIC {
+            return mismatch("magic", &self.magic, &MAGIC);
+        }
+        if self.version != FORMAT_VERSION {
+            return mismatch("format version", &self.version, &FORMAT_VERSION);
+        }
+        if self.kind != kind {
+            return mismatch("payload kind", &self.kind, &kind);
+        }
+        if self.field != E::ID {
+            return mismatch("field", &self.field, &E::ID);
+        }
+        if self.pcs != PCS::ID {
+            return mismatch("pcs", &self.pcs, &PCS::ID);
+        }
+        Ok(())
+    }
+}
+
+/// Encode `payload` with a header describing it.
+pub fn encode<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>, T: Serialize>(
+    kind: PayloadKind,
+    payload: &T,
+) -> Result<Vec<u8>, ZKVMError> {
+    let payload = bincode::serialize(payload)
+        .map_err(|err| ZKVMError::SerializationError(err.to_string()))?;
+    let header = Header::new::<E, PCS>(kind, &payload);
+    let mut bytes = bincode::serialize(&header)
+        .map_err(|err| ZKVMError::SerializationError(err.to_string()))?;
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Decode a payload produced by [`encode`], checking the header and the payload digest.
+pub fn decode<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>, T: DeserializeOwned>(
+    kind: PayloadKind,
+    bytes: &[u8],
+) -> Result<T, ZKVMError> {
+    let mut cursor = Cursor::new(bytes);
+    let header: Header = bincode::deserialize_from(&mut cursor)
+        .map_err(|err| ZKVMError::SerializationError(format!("invalid header: {err}")))?;
+    header.check::<E, PCS>(kind)?;
+
+    let payload = &bytes[cursor.position() as usize..];
+    if keccak256(payload) != header.payload_digest {
+        return Err(ZKVMError::SerializationError(
+            "payload digest mismatch".into(),
+        ));
+    }
+    bincode::deserialize(payload)
+        .map_err(|err| ZKVMError::SerializationError(format!("invalid payload: {err}")))
+}
+
+pub fn save<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>, T: Serialize>(
+    kind: PayloadKind,
+    payload: &T,
+    path: impl AsRef<Path>,
+) -> Result<(), ZKVMError> {
+    fs::write(path, encode::<E, PCS, T>(kind, payload)?).map_err(ZKVMError::IOError)
+}
+
+pub fn load<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>, T: DeserializeOwned>(
+    kind: PayloadKind,
+    path: impl AsRef<Path>,
+) -> Result<T, ZKVMError> {
+    decode::<E, PCS, T>(kind, &fs::read(path).map_err(ZKVMError::IOError)?)
+}
+
+pub(crate) fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
@@ -10,7 +10,7 @@ use crate::{
     state::{GlobalState, StateCircuit},
     structs::{ProgramParams, RAMType, ZKVMConstraintSystem, ZKVMFixedTraces, ZKVMWitnesses},
     tables::{
-        AndTable, LtuTable, OpsTable, OrTable, PowTable, ProgramTableCircuit, RangeTable,
+        AndTable, LtuTable, OpsTable, OrTable, PowTable, ProgramTableCircuit, RangeTable, ShlTable,
         TableCircuit, U5Table, U8Table, U14Table, U16Table, XorTable,
     },
     witness::{LkMultiplicity, LkMultiplicityRaw, RowMajorMatrix},
@@ -305,7 +305,12 @@ impl<E: ExtensionField, K: LkMultiplicityKey> MockProverError<E, K> {
                             let (a, b) = PowTable::unpack(key);
                             format!("Element: {a} ** {b}")
                         }
+                        ROMType::Shl => {
+                            let (a, b) = ShlTable::unpack(key);
+                            format!("Element: ({a} << {b}) & 0xff")
+                        }
                         ROMType::Instruction => format!("PC: {key}"),
+                        ROMType::Sumcheck => format!("Slot: {key}"),
                     };
                     (location, element)
                 } else {
@@ -390,6 +395,7 @@ fn load_tables<E: ExtensionField>(
     load_op_table::<XorTable, _>(&mut table_vec, cs, challenge);
     load_op_table::<LtuTable, _>(&mut table_vec, cs, challenge);
     load_op_table::<PowTable, _>(&mut table_vec, cs, challenge);
+    load_op_table::<ShlTable, _>(&mut table_vec, cs, challenge);
 
     HashSet::from_iter(table_vec)
 }
@@ -634,7 +640,12 @@ impl<'a, E: ExtensionField + Hash> MockProver<E> {
                             assert_eq!(args_eval[0][inst_id], 2);
                             lkm_from_cs.lookup_pow2(args_eval[1][inst_id])
                         }
+                        ROMType::Shl => lkm_from_cs
+                            .lookup_shl_byte(args_eval[0][inst_id], args_eval[1][inst_id]),
                         ROMType::Instruction => lkm_from_cs.fetch(args_eval[0][inst_id] as u32),
+                        ROMType::Sumcheck => {
+                            lkm_from_cs.increment(ROMType::Sumcheck, args_eval[0][inst_id])
+                        }
                     };
                 }
             }
@@ -925,6 +936,12 @@ Hints:
 
         // find out r != w errors
         let mut num_rw_mismatch_errors = 0;
+        // The function and source line of a pc of the program, if known.
+        let locate = |pc: u64| {
+            program
+                .describe(pc as u32)
+                .map_or(String::new(), |location| format!(" in {location}"))
+        };
 
         macro_rules! derive_ram_rws {
             ($ram_type:expr) => {{
@@ -1062,11 +1079,12 @@ Hints:
                             let pc = gs_of_circuit.map_or(0, |gs| gs[*row][0].to_canonical_u64());
                             let ts = gs_of_circuit.map_or(0, |gs| gs[*row][1].to_canonical_u64());
                             tracing::error!(
-                                "{} at row {} (pc={:x},ts={}) not found in {:?} writes",
+                                "{} at row {} (pc={:x},ts={}){} not found in {:?} writes",
                                 annotation,
                                 row,
                                 pc,
                                 ts,
+                                locate(pc),
                                 $ram_type,
                             )
                         });
@@ -1098,11 +1116,12 @@ Hints:
                             let pc = gs_of_circuit.map_or(0, |gs| gs[*row][0].to_canonical_u64());
                             let ts = gs_of_circuit.map_or(0, |gs| gs[*row][1].to_canonical_u64());
                             tracing::error!(
-                                "{} at row {} (pc={:x},ts={}) not found in {:?} reads",
+                                "{} at row {} (pc={:x},ts={}){} not found in {:?} reads",
                                 annotation,
                                 row,
                                 pc,
                                 ts,
+                                locate(pc),
                                 $ram_type,
                             )
                         });
@@ -50,20 +50,31 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
     }
 
     /// create proof for zkvm execution
+    pub fn create_proof(
+        &self,
+        witnesses: ZKVMWitnesses<E>,
+        pi: PublicValues<u32>,
+        transcript: impl ForkableTranscript<E>,
+    ) -> Result<ZKVMProof<E, PCS>, ZKVMError> {
+        self.create_proof_with_raw_pi(witnesses, pi.to_vec::<E>(), transcript)
+    }
+
+    /// create proof over public values given as raw field elements,
+    /// for circuits which do not use the riscv public value layout
     #[tracing::instrument(
         skip_all,
         name = "ZKVM_create_proof",
         fields(profiling_1),
         level = "trace"
     )]
-    pub fn create_proof(
+    pub fn create_proof_with_raw_pi(
         &self,
         witnesses: ZKVMWitnesses<E>,
-        pi: PublicValues<u32>,
+        raw_pi: Vec<Vec<E::BaseField>>,
         mut transcript: impl ForkableTranscript<E>,
     ) -> Result<ZKVMProof<E, PCS>, ZKVMError> {
         let span = entered_span!("commit_to_fixed_commit", profiling_1 = true);
-        let mut vm_proof = ZKVMProof::empty(pi);
+        let mut vm_proof = ZKVMProof::from_raw_pi(raw_pi);
 
         // including raw public input to transcript
         for v in vm_proof.raw_pi.iter().flatten() {
@@ -102,24 +113,32 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                 circuit_name = circuit_name,
                 profiling_2 = true
             );
-            let num_witin = self
-                .pk
-                .circuit_pks
-                .get(&circuit_name)
-                .unwrap()
-                .get_cs()
-                .num_witin;
+            let cs = self.pk.circuit_pks.get(&circuit_name).unwrap().get_cs();
 
             let (witness, structural_witness) = match num_instances {
                 0 => (vec![], vec![]),
                 _ => {
                     let mut witness = witness.into_mles();
-                    let structural_witness = witness.split_off(num_witin as usize);
-                    commitments.insert(
-                        circuit_name.clone(),
-                        PCS::batch_commit_and_write(&self.pk.pp, &witness, &mut transcript)
-                            .map_err(ZKVMError::PCSError)?,
-                    );
+                    let structural_witness = witness.split_off(cs.num_witin as usize);
+                    // The segment state is committed apart from the main witnesses.
+                    let [main, init_state, final_state] = cs.witin_groups();
+                    let wits_commit =
+                        PCS::batch_commit_and_write(&self.pk.pp, &witness[main], &mut transcript)
+                            .map_err(ZKVMError::PCSError)?;
+                    let mut state_commits = [None, None];
+                    for (group, commit) in izip!([init_state, final_state], &mut state_commits) {
+                        if !group.is_empty() {
+                            *commit = Some(
+                                PCS::batch_commit_and_write(
+                                    &self.pk.pp,
+                                    &witness[group],
+                                    &mut transcript,
+                                )
+                                .map_err(ZKVMError::PCSError)?,
+                            );
+                        }
+                    }
+                    commitments.insert(circuit_name.clone(), (wits_commit, state_commits));
 
                     (witness, structural_witness)
                 }
@@ -166,7 +185,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             if witness.is_empty() {
                 continue;
             }
-            let wits_commit = commitments.remove(circuit_name).unwrap();
+            let (wits_commit, state_commits) = commitments.remove(circuit_name).unwrap();
             // TODO: add an enum for circuit type either in constraint_system or vk
             let cs = pk.get_cs();
             let is_opcode_circuit = cs.lk_table_expressions.is_empty()
@@ -174,6 +193,10 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                 && cs.w_table_expressions.is_empty();
 
             if is_opcode_circuit {
+                assert!(
+                    state_commits.iter().all(Option::is_none),
+                    "opcode circuits have no segment state"
+                );
                 tracing::debug!(
                     "opcode circuit {} has {} witnesses, {} reads, {} writes, {} lookups",
                     circuit_name,
@@ -211,6 +234,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                     pk,
                     witness,
                     wits_commit,
+                    state_commits,
                     structural_witness,
                     &pi,
                     transcript,
@@ -692,6 +716,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
         circuit_pk: &ProvingKey<E, PCS>,
         witnesses: Vec<ArcMultilinearExtension<'_, E>>,
         wits_commit: PCS::CommitmentWithWitness,
+        state_commits: [Option<PCS::CommitmentWithWitness>; 2],
         structural_witnesses: Vec<ArcMultilinearExtension<'_, E>>,
         pi: &[ArcMultilinearExtension<'_, E>],
         transcript: &mut impl Transcript<E>,
@@ -1112,16 +1137,16 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             name,
             fixed.len()
         );
+        let [main, init_state, final_state] = cs.witin_groups();
         let wits_opening_proof = PCS::simple_batch_open(
             pp,
-            &witnesses,
+            &witnesses[main.clone()],
             &wits_commit,
             &input_open_point,
-            wits_in_evals.as_slice(),
+            &wits_in_evals[main],
             transcript,
         )
         .map_err(ZKVMError::PCSError)?;
-        exit_span!(pcs_opening);
         let wits_commit = PCS::get_pure_commitment(&wits_commit);
         tracing::debug!(
             "[table {}] build opening proof for {} polys",
@@ -1129,6 +1154,31 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             witnesses.len(),
         );
 
+        // open the segment state at the same point
+        let mut state_openings = [None, None];
+        for (group, commit, opening) in izip!(
+            [init_state, final_state],
+            state_commits,
+            &mut state_openings
+        ) {
+            if let Some(commit) = commit {
+                let proof = PCS::simple_batch_open(
+                    pp,
+                    &witnesses[group.clone()],
+                    &commit,
+                    &input_open_point,
+                    &wits_in_evals[group],
+                    transcript,
+                )
+                .map_err(ZKVMError::PCSError)?;
+                *opening = Some((PCS::get_pure_commitment(&commit), proof));
+            }
+        }
+        let [init_state, final_state] = state_openings;
+        let (init_state_commit, init_state_opening_proof) = init_state.unzip();
+        let (final_state_commit, final_state_opening_proof) = final_state.unzip();
+        exit_span!(pcs_opening);
+
         Ok((
             ZKVMTableProof {
                 r_out_evals,
@@ -1144,6 +1194,10 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                 wits_in_evals,
                 wits_commit,
                 wits_opening_proof,
+                init_state_commit,
+                init_state_opening_proof,
+                final_state_commit,
+                final_state_opening_proof,
             },
             pi_in_evals,
         ))
@@ -3,14 +3,18 @@ use std::marker::PhantomData;
 use ark_std::test_rng;
 use ceno_emul::{
     CENO_PLATFORM,
-    InsnKind::{ADD, ECALL},
-    Platform, Program, StepRecord, VMState, encode_rv32,
+    InsnKind::{ADD, ADDI, BNE, ECALL, LW, SW},
+    IterAddresses, Platform, Program, StepRecord, Tracer, VMState, WORD_SIZE, encode_rv32,
+    encode_rv32u,
+    host_utils::{GuestEnv, load_env},
 };
 use ff::Field;
 use ff_ext::ExtensionField;
-use goldilocks::GoldilocksExt2;
+use goldilocks::{Goldilocks, GoldilocksExt2};
 use itertools::Itertools;
-use mpcs::{Basefold, BasefoldDefault, BasefoldRSParams, PolynomialCommitmentScheme};
+use mpcs::{
+    Basefold, BasefoldBasecodeParams, BasefoldDefault, BasefoldRSParams, PolynomialCommitmentScheme,
+};
 use multilinear_extensions::{
     mle::IntoMLE, util::ceil_log2, virtual_poly::ArcMultilinearExtension,
 };
@@ -18,27 +22,35 @@ use transcript::{BasicTranscript, BasicTranscriptWithStat, StatisticRecorder, Tr
 
 use crate::{
     circuit_builder::CircuitBuilder,
+    e2e::{
+        Checkpoint, E2EProvingSetup, GuestExit, Preset, initial_segment_state, profile_guest,
+        run_e2e_segments, run_e2e_with_checkpoint, setup_platform,
+    },
     error::ZKVMError,
     expression::{ToExpr, WitIn},
     instructions::{
         Instruction,
-        riscv::{arith::AddInstruction, ecall::HaltInstruction},
+        riscv::{arith::AddInstruction, constants::PUBLIC_OUTPUT_IDX, ecall::HaltInstruction},
     },
+    recursion::{SumcheckVerifierConfig, sumcheck_public_values},
+    scheme::mock_prover::LkMultiplicityKey,
     set_val,
     structs::{
         PointAndEval, RAMType::Register, TowerProver, TowerProverSpec, ZKVMConstraintSystem,
-        ZKVMFixedTraces, ZKVMWitnesses,
+        ZKVMFixedTraces, ZKVMVerifyingKey, ZKVMWitnesses,
     },
-    tables::{ProgramTableCircuit, U16TableCircuit},
+    tables::{ProgramTableCircuit, StaticMemCircuit, TableCircuit, U16TableCircuit},
+    utils::next_pow2_instance_padding,
     witness::LkMultiplicity,
 };
 
 use super::{
-    PublicValues,
+    PublicValues, SegmentState, ZKVMProof,
     constants::{MAX_NUM_VARIABLES, NUM_FANIN},
+    envelope::Header,
     prover::ZKVMProver,
     utils::infer_tower_product_witness,
-    verifier::{TowerVerify, ZKVMVerifier},
+    verifier::{TowerVerify, ZKVMVerifier, dummy_lk_multiplicity},
 };
 
 struct TestConfig {
@@ -288,7 +300,7 @@ fn test_single_add_instance_e2e() {
         .assign_table_circuit::<ProgramTableCircuit<E>>(&zkvm_cs, &prog_config, &program)
         .unwrap();
 
-    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0]);
+    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0], vec![0]);
     let transcript = BasicTranscript::new(b"riscv");
     let zkvm_proof = prover
         .create_proof(zkvm_witness, pi, transcript)
@@ -312,6 +324,516 @@ fn test_single_add_instance_e2e() {
     );
 }
 
+/// Prove `code` through the full e2e pipeline, with the given public input.
+fn prove_program_code<
+    E: ExtensionField + LkMultiplicityKey,
+    Pcs: PolynomialCommitmentScheme<E> + 'static,
+>(
+    code: &[ceno_emul::Instruction],
+    public_io: Vec<u32>,
+) -> (ZKVMProof<E, Pcs>, ZKVMVerifier<E, Pcs>) {
+    prove_program_code_with_env(code, public_io, &GuestEnv::default())
+}
+
+/// Prove `code` through the full e2e pipeline, with the given public input and env.
+fn prove_program_code_with_env<
+    E: ExtensionField + LkMultiplicityKey,
+    Pcs: PolynomialCommitmentScheme<E> + 'static,
+>(
+    code: &[ceno_emul::Instruction],
+    public_io: Vec<u32>,
+    env: &GuestEnv,
+) -> (ZKVMProof<E, Pcs>, ZKVMVerifier<E, Pcs>) {
+    let pc_base = CENO_PLATFORM.pc_base();
+    let image = (0..code.len() as u32)
+        .map(|i| (pc_base + i * WORD_SIZE as u32, 0))
+        .collect();
+    let mut program = Program::new(pc_base, pc_base, code.to_vec(), image);
+    load_env(&mut program, &CENO_PLATFORM.env, env);
+    let platform = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 16, 16);
+
+    let (proof, verifier, _) = run_e2e_with_checkpoint::<E, Pcs>(
+        program,
+        platform,
+        vec![],
+        public_io,
+        usize::MAX,
+        Checkpoint::PrepSanityCheck,
+    )
+    .0
+    .expect("PrepSanityCheck should yield state");
+    (proof, verifier)
+}
+
+#[test]
+fn test_proof_file_roundtrip() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let (zkvm_proof, verifier) = prove_program_code::<E, Pcs>(&PROGRAM_CODE, vec![]);
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    zkvm_proof.save(file.path()).expect("save proof");
+    let loaded = ZKVMProof::<E, Pcs>::load(file.path()).expect("load proof");
+
+    let transcript = BasicTranscript::new(b"riscv");
+    assert!(
+        verifier
+            .verify_proof(loaded, transcript)
+            .expect("verify proof return with error")
+    );
+}
+
+#[test]
+fn test_proof_file_rejects_corruption() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let zkvm_proof = ZKVMProof::<E, Pcs>::empty(PublicValues::new(0, 0, 0, 0, 0, vec![0], vec![0]));
+    let bytes = zkvm_proof.to_bytes().unwrap();
+    assert!(ZKVMProof::<E, Pcs>::from_bytes(&bytes).is_ok());
+
+    let is_rejected = |bytes: &[u8]| {
+        matches!(
+            ZKVMProof::<E, Pcs>::from_bytes(bytes),
+            Err(ZKVMError::SerializationError(_))
+        )
+    };
+
+    // Truncated file.
+    assert!(is_rejected(&bytes[..bytes.len() - 1]));
+    assert!(is_rejected(&bytes[..4]));
+    assert!(is_rejected(&[]));
+
+    // Wrong magic.
+    let mut corrupted = bytes.clone();
+    corrupted[0] ^= 1;
+    assert!(is_rejected(&corrupted));
+
+    // Flipped bit in the payload.
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert!(is_rejected(&corrupted));
+
+    // Proof produced for another PCS.
+    let other = ZKVMProof::<E, Basefold<E, BasefoldBasecodeParams>>::empty(PublicValues::new(
+        0,
+        0,
+        0,
+        0,
+        0,
+        vec![0],
+        vec![0],
+    ));
+    assert!(is_rejected(&other.to_bytes().unwrap()));
+}
+
+#[test]
+fn test_proof_file_header() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // The header names the field and PCS by their IDs, which do not depend on the compiler.
+    let zkvm_proof = ZKVMProof::<E, Pcs>::empty(PublicValues::new(0, 0, 0, 0, 0, vec![0], vec![0]));
+    let header: Header = bincode::deserialize(&zkvm_proof.to_bytes().unwrap()).unwrap();
+    assert_eq!(header.field, "goldilocks-ext2/v1");
+    assert_eq!(header.pcs, "basefold-rs/v1");
+}
+
+#[test]
+fn test_vk_file_roundtrip() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let (zkvm_proof, verifier) = prove_program_code::<E, Pcs>(&PROGRAM_CODE, vec![]);
+    let proof_bytes = zkvm_proof.to_bytes().unwrap();
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    verifier.vk().save(file.path()).expect("save vk");
+    let loaded = ZKVMVerifier::<E, Pcs>::load(file.path()).expect("load vk");
+    assert_eq!(loaded.vk().fingerprint(), verifier.vk().fingerprint());
+
+    // A verifier loaded from file accepts proofs without rebuilding circuits.
+    let transcript = BasicTranscript::new(b"riscv");
+    assert!(
+        loaded
+            .verify_proof(zkvm_proof, transcript)
+            .expect("verify proof return with error")
+    );
+
+    // Key generation is deterministic, so the fingerprint is stable across runs.
+    let (_, verifier_again) = prove_program_code::<E, Pcs>(&PROGRAM_CODE, vec![]);
+    assert_eq!(
+        verifier_again.vk().fingerprint(),
+        verifier.vk().fingerprint()
+    );
+
+    // A proof file is not a verifying key.
+    assert!(matches!(
+        ZKVMVerifyingKey::<E, Pcs>::from_bytes(&proof_bytes),
+        Err(ZKVMError::SerializationError(_))
+    ));
+}
+
+#[test]
+fn test_public_output_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // Commit the message `[42, 0, 0, 0]` as `ceno_rt::commit_slice` would.
+    let program_code = [
+        encode_rv32u(ADDI, 0, 0, 10, CENO_PLATFORM.public_output.start), // x10 = public_output
+        encode_rv32(ADDI, 0, 0, 1, 4),                                   // x1 = byte length
+        encode_rv32(SW, 10, 1, 0, 0),                                    // sw x1, 0(x10)
+        encode_rv32(ADDI, 0, 0, 2, 42),                                  // x2 = 42
+        encode_rv32(SW, 10, 2, 0, 4),                                    // sw x2, 4(x10)
+        encode_rv32(ADDI, 0, 0, 10, 0),                                  // x10 = exit code 0
+        encode_rv32(ECALL, 0, 0, 0, 0),                                  // halt
+    ];
+    let (zkvm_proof, verifier) = prove_program_code::<E, Pcs>(&program_code, vec![]);
+
+    assert_eq!(&zkvm_proof.public_output_words()[..3], &[4, 42, 0]);
+    assert_eq!(zkvm_proof.public_output(), vec![vec![42, 0, 0, 0]]);
+
+    // Claiming another output must be rejected.
+    let mut tampered = zkvm_proof.clone();
+    tampered.raw_pi[PUBLIC_OUTPUT_IDX][1] = Goldilocks::from(43u64);
+    let result = verifier.verify_proof(tampered, BasicTranscript::new(b"riscv"));
+    assert!(
+        matches!(result, Err(ZKVMError::VerifyError(_))),
+        "{result:?}"
+    );
+}
+
+/// Add the first two words of the public input, and halt.
+fn public_io_program() -> [ceno_emul::Instruction; 6] {
+    [
+        encode_rv32u(ADDI, 0, 0, 10, CENO_PLATFORM.public_io.start), // x10 = public_io
+        encode_rv32(LW, 10, 0, 1, 0),                                // lw x1, 0(x10)
+        encode_rv32(LW, 10, 0, 2, 4),                                // lw x2, 4(x10)
+        encode_rv32(ADD, 1, 2, 1, 0),                                // x1 = x1 + x2
+        encode_rv32(ADDI, 0, 0, 10, 0),                              // x10 = exit code 0
+        encode_rv32(ECALL, 0, 0, 0, 0),                              // halt
+    ]
+}
+
+#[test]
+fn test_public_io_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let (zkvm_proof, verifier) = prove_program_code::<E, Pcs>(&public_io_program(), vec![7, 35]);
+
+    // The remaining words of the segment are zero-padded.
+    assert!(
+        verifier
+            .verify_proof_with_public_io(zkvm_proof.clone(), BasicTranscript::new(b"riscv"), &[
+                7, 35
+            ])
+            .expect("verify proof return with error"),
+    );
+    assert!(matches!(
+        verifier.verify_proof_with_public_io(
+            zkvm_proof.clone(),
+            BasicTranscript::new(b"riscv"),
+            &[7, 36]
+        ),
+        Err(ZKVMError::VerifyError(_))
+    ));
+    assert!(matches!(
+        verifier.verify_proof_with_public_io(zkvm_proof, BasicTranscript::new(b"riscv"), &[
+            7, 35, 0, 0, 1
+        ]),
+        Err(ZKVMError::VerifyError(_))
+    ));
+}
+
+#[test]
+fn test_public_values_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let (zkvm_proof, verifier) = prove_program_code::<E, Pcs>(&public_io_program(), vec![7, 35]);
+    let expected = |exit_code, end_cycle, public_io| {
+        PublicValues::new(
+            exit_code,
+            CENO_PLATFORM.pc_base(),
+            Tracer::SUBCYCLES_PER_INSN as u32,
+            0,
+            end_cycle,
+            public_io,
+            vec![0; 4],
+        )
+    };
+    let verify = |proof, expected: &PublicValues<u32>| {
+        verifier.verify_proof_with_public_values(proof, BasicTranscript::new(b"riscv"), expected)
+    };
+
+    // 6 steps from the initial cycle.
+    let end_cycle = Tracer::SUBCYCLES_PER_INSN as u32 * 7;
+    assert!(
+        verify(
+            zkvm_proof.clone(),
+            &expected(0, end_cycle, vec![7, 35, 0, 0])
+        )
+        .expect("verify proof return with error")
+    );
+
+    // Values other than those proven are rejected.
+    for wrong in [
+        expected(1, end_cycle, vec![7, 35, 0, 0]),
+        expected(0, end_cycle + 1, vec![7, 35, 0, 0]),
+        expected(0, end_cycle, vec![7, 36, 0, 0]),
+        expected(0, end_cycle, vec![7, 35]),
+    ] {
+        assert!(matches!(
+            verify(zkvm_proof.clone(), &wrong),
+            Err(ZKVMError::VerifyError(_))
+        ));
+    }
+
+    // A proof with altered public input is rejected, as in the sanity check of `bin/e2e.rs`.
+    let mut tampered = zkvm_proof;
+    tampered.raw_pi[0] = vec![Goldilocks::ONE];
+    tampered.raw_pi[1] = vec![Goldilocks::ONE];
+    assert!(matches!(
+        verify(tampered, &expected(0, end_cycle, vec![7, 35, 0, 0])),
+        Err(ZKVMError::VerifyError(_))
+    ));
+}
+
+#[test]
+fn test_env_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // Commit the number of arguments plus the length of the first one.
+    let (env_start, output_start) = (CENO_PLATFORM.env.start, CENO_PLATFORM.public_output.start);
+    let program_code = [
+        encode_rv32u(ADDI, 0, 0, 10, env_start),    // x10 = env
+        encode_rv32(LW, 10, 0, 1, 0),               // lw x1, 0(x10): argc
+        encode_rv32(LW, 10, 0, 2, 8),               // lw x2, 8(x10): len(args[0])
+        encode_rv32(ADD, 1, 2, 1, 0),               // x1 = x1 + x2
+        encode_rv32u(ADDI, 0, 0, 11, output_start), // x11 = public_output
+        encode_rv32(ADDI, 0, 0, 3, 4),              // x3 = byte length
+        encode_rv32(SW, 11, 3, 0, 0),               // sw x3, 0(x11)
+        encode_rv32(SW, 11, 1, 0, 4),               // sw x1, 4(x11)
+        encode_rv32(ADDI, 0, 0, 10, 0),             // x10 = exit code 0
+        encode_rv32(ECALL, 0, 0, 0, 0),             // halt
+    ];
+    let mut env = GuestEnv::default();
+    env.arg("hello").var("MODE", "fast");
+    let (zkvm_proof, _) = prove_program_code_with_env::<E, Pcs>(&program_code, vec![], &env);
+    assert_eq!(zkvm_proof.public_output(), vec![vec![6, 0, 0, 0]]);
+
+    // The env is in the keys, so the proof does not verify with another env.
+    env.args = vec!["world".to_string()];
+    let (_, other_verifier) = prove_program_code_with_env::<E, Pcs>(&program_code, vec![], &env);
+    let result = other_verifier.verify_proof(zkvm_proof, BasicTranscript::new(b"riscv"));
+    assert!(
+        matches!(result, Err(ZKVMError::VerifyError(_))),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_setup_platform_empty_public_io() {
+    let pc_base = CENO_PLATFORM.pc_base();
+    let code = vec![encode_rv32(ECALL, 0, 0, 0, 0)];
+    let program = Program::new(pc_base, pc_base, code, [(pc_base, 0)].into());
+
+    // Without public input or output, each table still has the minimal number of instances.
+    let platform = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 0, 0);
+    assert_eq!(platform.public_io.iter_addresses().len(), 2);
+    assert_eq!(platform.public_output.iter_addresses().len(), 2);
+
+    let platform = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 12, 16);
+    assert_eq!(platform.public_io.iter_addresses().len(), 4);
+    assert_eq!(platform.public_output.iter_addresses().len(), 4);
+}
+
+#[test]
+#[should_panic(expected = "do not fit in the public segment")]
+fn test_setup_platform_public_output_size() {
+    let pc_base = CENO_PLATFORM.pc_base();
+    let code = vec![encode_rv32(ECALL, 0, 0, 0, 0)];
+    let program = Program::new(pc_base, pc_base, code, [(pc_base, 0)].into());
+
+    // The public output may fill its segment, but not overlap the env segment after it.
+    let size = CENO_PLATFORM.public_output.len() as u32;
+    let platform = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 0, size);
+    assert_eq!(platform.public_output, CENO_PLATFORM.public_output);
+    setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 0, 2 * size);
+}
+
+#[test]
+#[should_panic(expected = "increase the heap size")]
+fn test_setup_platform_guest_heap() {
+    let pc_base = CENO_PLATFORM.pc_base();
+    let code = vec![encode_rv32(ECALL, 0, 0, 0, 0)];
+    let mut program = Program::new(pc_base, pc_base, code, [(pc_base, 0)].into());
+    let heap_end = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 0, 0)
+        .heap
+        .end;
+
+    // The heap of the guest may end with the platform heap, but not after it.
+    program.addresses.insert("_eheap".to_string(), heap_end);
+    setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 0, 0);
+    program.addresses.insert("_eheap".to_string(), heap_end + 4);
+    setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 0, 0);
+}
+
+#[test]
+fn test_proving_setup_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // Commit the first word of the hints.
+    let (hints_start, output_start) =
+        (CENO_PLATFORM.hints.start, CENO_PLATFORM.public_output.start);
+    let code = [
+        encode_rv32u(ADDI, 0, 0, 10, hints_start),  // x10 = hints
+        encode_rv32(LW, 10, 0, 1, 0),               // lw x1, 0(x10)
+        encode_rv32u(ADDI, 0, 0, 11, output_start), // x11 = public_output
+        encode_rv32(ADDI, 0, 0, 3, 4),              // x3 = byte length
+        encode_rv32(SW, 11, 3, 0, 0),               // sw x3, 0(x11)
+        encode_rv32(SW, 11, 1, 0, 4),               // sw x1, 4(x11)
+        encode_rv32(ADDI, 0, 0, 10, 0),             // x10 = exit code 0
+        encode_rv32(ECALL, 0, 0, 0, 0),             // halt
+    ];
+    let pc_base = CENO_PLATFORM.pc_base();
+    let image = (0..code.len() as u32)
+        .map(|i| (pc_base + i * WORD_SIZE as u32, 0))
+        .collect();
+    let program = Program::new(pc_base, pc_base, code.to_vec(), image);
+    let platform = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 16, 16);
+
+    // One setup proves executions on different hints.
+    let setup = E2EProvingSetup::<E, Pcs>::new(program, platform, &[]);
+    let verifier = ZKVMVerifier::new(setup.get_vk());
+    for hint in [7, 0x0102_0304] {
+        let (zkvm_proof, exit) = setup.prove(&[hint], usize::MAX);
+        assert_eq!(exit, GuestExit {
+            exit_code: Some(0),
+            panic_message: None,
+        });
+        assert_eq!(zkvm_proof.exit_code(), 0);
+        assert_eq!(zkvm_proof.public_output(), vec![
+            hint.to_le_bytes().to_vec()
+        ]);
+        assert!(
+            verifier
+                .verify_proof(zkvm_proof, BasicTranscript::new(b"riscv"))
+                .expect("verify proof return with error")
+        );
+    }
+}
+
+#[test]
+fn test_segments_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // Sum n + … + 1 through a memory word, with n read from the hints at the start and at the
+    // end, and commit n as a word of output each time.
+    let stack_top = CENO_PLATFORM.stack.end - 4;
+    let output = CENO_PLATFORM.public_output.start;
+    let hints = CENO_PLATFORM.hints.start;
+    let code = vec![
+        encode_rv32u(ADDI, 0, 0, 12, hints),     // x12 = hints
+        encode_rv32(LW, 12, 0, 1, 0),            // lw x1, 0(x12)
+        encode_rv32u(ADDI, 0, 0, 10, stack_top), // x10 = stack_top
+        encode_rv32u(ADDI, 0, 0, 11, output),    // x11 = output
+        encode_rv32(SW, 11, 1, 0, 0),            // sw x1, 0(x11)
+        // loop {
+        encode_rv32(ADD, 2, 1, 2, 0),   // x2 = x2 + x1
+        encode_rv32(SW, 10, 2, 0, 0),   // sw x2, 0(x10)
+        encode_rv32(LW, 10, 0, 2, 0),   // lw x2, 0(x10)
+        encode_rv32(ADDI, 1, 0, 1, -1), // x1 = x1 - 1
+        encode_rv32(BNE, 1, 0, 0, -16), // until x1 == 0
+        // }
+        encode_rv32(LW, 12, 0, 3, 0),   // lw x3, 0(x12)
+        encode_rv32(SW, 11, 3, 0, 4),   // sw x3, 4(x11)
+        encode_rv32(ADDI, 0, 0, 10, 0), // x10 = exit code 0
+        encode_rv32(ECALL, 0, 0, 0, 0), // halt
+    ];
+    let pc_base = CENO_PLATFORM.pc_base();
+    let image = (0..code.len() as u32)
+        .map(|i| (pc_base + i * WORD_SIZE as u32, 0))
+        .collect();
+    let program = Program::new(pc_base, pc_base, code, image);
+    let platform = setup_platform(Preset::Ceno, &program, 1 << 10, 1 << 10, 16, 16);
+    let init = initial_segment_state(&program, &platform);
+
+    // 34 steps in segments of 8 steps.
+    let (segments, verifier) = run_e2e_segments::<E, Pcs>(
+        program.clone(),
+        platform.clone(),
+        vec![5],
+        vec![],
+        usize::MAX,
+        8,
+    );
+    assert_eq!(segments.len(), 5);
+    assert_eq!(segments[4].public_output_words(), vec![5, 5, 0, 0]);
+
+    let verify = |segments, init: &SegmentState| {
+        verifier.verify_segments(segments, BasicTranscript::new(b"riscv"), init)
+    };
+    assert!(verify(segments.clone(), &init).expect("verify segments return with error"));
+
+    // A chain with a missing, reordered, or unfinished segment is rejected.
+    let mut missing = segments.clone();
+    missing.remove(1);
+    let mut reordered = segments.clone();
+    reordered.swap(1, 2);
+    let unfinished = segments[..4].to_vec();
+    // So is a segment whose memory at its end is not where the next segment starts,
+    let static_mem = StaticMemCircuit::<E>::name();
+    let mut tampered_mem = segments.clone();
+    let (_, proof) = tampered_mem[1].table_proofs.get_mut(&static_mem).unwrap();
+    proof.final_state_commit = Some(Default::default());
+    // or a chain which does not start from the initial memory.
+    let mut tampered_init_mem = segments.clone();
+    let (_, proof) = tampered_init_mem[0]
+        .table_proofs
+        .get_mut(&static_mem)
+        .unwrap();
+    proof.init_state_commit = Some(Default::default());
+    // So is a chain whose segments run on other hints, even though each of them is valid and
+    // reads the same words.
+    let (other_hints, _) =
+        run_e2e_segments::<E, Pcs>(program, platform, vec![5, 7], vec![], usize::MAX, 8);
+    assert_eq!(other_hints[4].public_output_words(), vec![5, 5, 0, 0]);
+    let spliced = segments[..1]
+        .iter()
+        .chain(&other_hints[1..])
+        .cloned()
+        .collect();
+    for wrong in [
+        missing,
+        reordered,
+        unfinished,
+        vec![],
+        tampered_mem,
+        tampered_init_mem,
+        spliced,
+    ] {
+        assert!(matches!(
+            verify(wrong, &init),
+            Err(ZKVMError::VerifyError(_))
+        ));
+    }
+
+    // A chain from another initial state is rejected.
+    let mut wrong_init = init.clone();
+    wrong_init.public_output[0] = 1;
+    assert!(matches!(
+        verify(segments, &wrong_init),
+        Err(ZKVMError::VerifyError(_))
+    ));
+}
+
 /// test various product argument size, starting from minimal leaf size 2
 #[test]
 fn test_tower_proof_various_prod_size() {
@@ -371,3 +893,150 @@ fn test_tower_proof_various_prod_size() {
         _test_tower_proof_prod_size_2(1 << leaf_layer_size);
     }
 }
+
+#[test]
+fn test_dummy_lk_multiplicity() {
+    // The tower of lookups has `1 << ceil_log2(num_lks)` slots per padded instance.
+    for num_lks in 1..=33 {
+        for num_instances in [1, 3, 4, 100] {
+            let slots = next_pow2_instance_padding(num_instances) << ceil_log2(num_lks);
+            assert_eq!(
+                dummy_lk_multiplicity(num_lks, num_instances),
+                slots - num_lks * num_instances
+            );
+        }
+    }
+    // A single lookup needs no padding within an instance. Padding it to two instead counted
+    // one dummy lookup too many for each instance.
+    assert_eq!(dummy_lk_multiplicity(1, 4), 0);
+}
+
+#[test]
+fn test_recursive_tower_sumchecks() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    let num_vars = 4;
+    let mut rng = test_rng();
+
+    // tower proof of a product argument
+    let leaf_layer: ArcMultilinearExtension<E> = (0..1 << num_vars)
+        .map(|_| E::random(&mut rng))
+        .collect_vec()
+        .into_mle()
+        .into();
+    let (first, second): (&[E], &[E]) = leaf_layer
+        .get_ext_field_vec()
+        .split_at(leaf_layer.evaluations().len() / 2);
+    let layers = infer_tower_product_witness(
+        num_vars,
+        vec![
+            first.to_vec().into_mle().into(),
+            second.to_vec().into_mle().into(),
+        ],
+        2,
+    );
+    let (_, tower_proof) = TowerProver::create_proof(
+        vec![TowerProverSpec {
+            witness: layers.clone(),
+        }],
+        vec![],
+        2,
+        &mut BasicTranscript::new(b"test_tower_proof"),
+    );
+    let mut sumchecks = vec![];
+    TowerVerify::verify_and_record(
+        vec![
+            layers[0]
+                .iter()
+                .flat_map(|mle| mle.get_ext_field_vec().to_vec())
+                .collect_vec(),
+        ],
+        vec![],
+        &tower_proof,
+        vec![num_vars],
+        2,
+        &mut BasicTranscript::new(b"test_tower_proof"),
+        Some(&mut sumchecks),
+    )
+    .unwrap();
+    assert_eq!(sumchecks.len(), num_vars - 1);
+
+    // prove the sumchecks of the tower verification
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    let config = SumcheckVerifierConfig::construct_circuits(&mut zkvm_cs);
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    config.generate_fixed_traces(&zkvm_cs, &mut zkvm_fixed_traces);
+    let pk = zkvm_cs
+        .clone()
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+        .unwrap();
+    let verifier = ZKVMVerifier::new(pk.get_vk());
+    let prover = ZKVMProver::new(pk);
+
+    let statements = sumchecks
+        .iter()
+        .map(|sumcheck| sumcheck.statement.clone())
+        .collect_vec();
+    let raw_pi = sumcheck_public_values(&statements);
+    let proof = prover
+        .create_proof_with_raw_pi(
+            config.generate_witness(&zkvm_cs, &sumchecks).unwrap(),
+            raw_pi.clone(),
+            BasicTranscript::new(b"recursion"),
+        )
+        .unwrap();
+    assert!(
+        verifier
+            .verify_proof_with_raw_pi(proof, BasicTranscript::new(b"recursion"), raw_pi, false)
+            .unwrap()
+    );
+
+    // the same rounds do not prove another claimed sum
+    let mut tampered = statements;
+    tampered[1].claimed_sum += E::ONE;
+    let tampered_pi = sumcheck_public_values(&tampered);
+    let proof = prover
+        .create_proof_with_raw_pi(
+            config.generate_witness(&zkvm_cs, &sumchecks).unwrap(),
+            tampered_pi.clone(),
+            BasicTranscript::new(b"recursion"),
+        )
+        .unwrap();
+    assert!(
+        verifier
+            .verify_proof_with_raw_pi(
+                proof,
+                BasicTranscript::new(b"recursion"),
+                tampered_pi,
+                false
+            )
+            .is_err()
+    );
+}
+
+#[test]
+fn test_profile_guest() {
+    let elf = include_bytes!("../../examples/fibonacci.elf");
+    let program = Program::load_elf(elf, u32::MAX).unwrap();
+    let (_, entry) = program.symbol_at(program.entry).unwrap();
+    assert_eq!(entry.name, "_start");
+    let platform = setup_platform(Preset::Sp1, &program, 1 << 15, 1 << 21, 16, 16);
+
+    let max_steps = 1 << 12;
+    let mut folded = vec![];
+    profile_guest(&program, &platform, &[], &[], max_steps, &mut folded).unwrap();
+    let folded = String::from_utf8(folded).unwrap();
+
+    let steps: usize = folded
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().1.parse::<usize>().unwrap())
+        .sum();
+    assert_eq!(steps, max_steps);
+    assert!(
+        folded
+            .lines()
+            .any(|line| line.starts_with("_start;__start;main "))
+    );
+}
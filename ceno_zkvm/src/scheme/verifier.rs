@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{collections::BTreeSet, iter::repeat, marker::PhantomData, path::Path};
 
 use ark_std::iterable::Iterable;
 use ff_ext::ExtensionField;
@@ -10,13 +10,24 @@ use multilinear_extensions::{
     util::ceil_log2,
     virtual_poly::{VPAuxInfo, build_eq_x_r_vec_sequential, eq_eval},
 };
+use serde::{Serialize, de::DeserializeOwned};
 use sumcheck::structs::{IOPProof, IOPVerifierState};
 use transcript::{ForkableTranscript, Transcript};
 
 use crate::{
     error::ZKVMError,
-    expression::{Instance, StructuralWitIn},
-    instructions::{Instruction, riscv::ecall::HaltInstruction},
+    expression::{Expression, Instance, StructuralWitIn},
+    instructions::{
+        Instruction,
+        riscv::{
+            constants::{
+                END_CYCLE_IDX, END_PC_IDX, INIT_CYCLE_IDX, INIT_PC_IDX, INIT_PUBLIC_OUTPUT_IDX,
+                PUBLIC_IO_IDX, PUBLIC_OUTPUT_IDX,
+            },
+            ecall::HaltInstruction,
+        },
+    },
+    recursion::{SumcheckInstance, SumcheckStatement},
     scheme::{
         constants::{NUM_FANIN, NUM_FANIN_LOGUP, SEL_DEGREE},
         utils::eval_by_expr_with_instance,
@@ -29,9 +40,39 @@ use crate::{
 };
 
 use super::{
-    ZKVMOpcodeProof, ZKVMProof, ZKVMTableProof, constants::MAINCONSTRAIN_SUMCHECK_BATCH_SIZE,
+    PublicValues, SegmentState, ZKVMOpcodeProof, ZKVMProof, ZKVMTableProof,
+    constants::MAINCONSTRAIN_SUMCHECK_BATCH_SIZE,
 };
 
+/// Pairs of public values (end of a segment, start of the next segment) which link segments.
+const SEGMENT_LINKS: [(usize, usize); 3] = [
+    (END_PC_IDX, INIT_PC_IDX),
+    (END_CYCLE_IDX, INIT_CYCLE_IDX),
+    (PUBLIC_OUTPUT_IDX, INIT_PUBLIC_OUTPUT_IDX),
+];
+
+/// Public values which are the same in all segments of an execution.
+const SEGMENT_INVARIANTS: [usize; 1] = [PUBLIC_IO_IDX];
+
+/// The public values which an expression reads.
+fn queried_instances<E: ExtensionField>(expr: &Expression<E>) -> BTreeSet<Instance> {
+    let union = |mut a: BTreeSet<Instance>, b: BTreeSet<Instance>| {
+        a.extend(b);
+        a
+    };
+    expr.evaluate_with_instance(
+        &|_| BTreeSet::new(),
+        &|_| BTreeSet::new(),
+        &|_, _, _, _| BTreeSet::new(),
+        &|i| BTreeSet::from([i]),
+        &|_| BTreeSet::new(),
+        &|_, _, _, _| BTreeSet::new(),
+        &union,
+        &union,
+        &|a, b, c| union(union(a, b), c),
+    )
+}
+
 pub struct ZKVMVerifier<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     pub(crate) vk: ZKVMVerifyingKey<E, PCS>,
 }
@@ -41,6 +82,18 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         ZKVMVerifier { vk }
     }
 
+    pub fn vk(&self) -> &ZKVMVerifyingKey<E, PCS> {
+        &self.vk
+    }
+
+    /// Construct a verifier from a verifying key saved with [`ZKVMVerifyingKey::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ZKVMError>
+    where
+        ZKVMVerifyingKey<E, PCS>: Serialize + DeserializeOwned,
+    {
+        ZKVMVerifyingKey::load(path).map(Self::new)
+    }
+
     /// Verify a trace from start to halt.
     #[tracing::instrument(skip_all, name = "verify_proof")]
     pub fn verify_proof(
@@ -51,6 +104,187 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         self.verify_proof_halt(vm_proof, transcript, true)
     }
 
+    /// Verify a trace from start to halt, on the public input supplied by the caller.
+    ///
+    /// `public_io` is zero-padded to the size of the public input table.
+    pub fn verify_proof_with_public_io(
+        &self,
+        vm_proof: ZKVMProof<E, PCS>,
+        transcript: impl ForkableTranscript<E>,
+        public_io: &[u32],
+    ) -> Result<bool, ZKVMError> {
+        self.verify_proof_halt_with_public_io(vm_proof, transcript, public_io, true)
+    }
+
+    /// Verify a trace from start to optional halt, on the public input supplied by the caller.
+    pub fn verify_proof_halt_with_public_io(
+        &self,
+        vm_proof: ZKVMProof<E, PCS>,
+        transcript: impl ForkableTranscript<E>,
+        public_io: &[u32],
+        does_halt: bool,
+    ) -> Result<bool, ZKVMError> {
+        let Some(raw_io) = vm_proof.raw_pi.get(PUBLIC_IO_IDX) else {
+            return Err(ZKVMError::VerifyError("missing public input".into()));
+        };
+        if public_io.len() > raw_io.len() {
+            return Err(ZKVMError::VerifyError(format!(
+                "public input of {} words does not fit in {} words",
+                public_io.len(),
+                raw_io.len()
+            )));
+        }
+        let expected_io = chain!(public_io.iter().copied(), repeat(0))
+            .take(raw_io.len())
+            .map(|v| E::BaseField::from(v as u64));
+        if !raw_io.iter().copied().eq(expected_io) {
+            return Err(ZKVMError::VerifyError("public input mismatch".into()));
+        }
+        self.verify_proof_halt(vm_proof, transcript, does_halt)
+    }
+
+    /// Verify a trace from start to halt, against public values supplied by the caller.
+    ///
+    /// The public values are rebuilt from `expected` rather than taken from the proof, and a
+    /// proof which embeds different values is rejected. The public input and output of
+    /// `expected` must have the sizes of their tables.
+    pub fn verify_proof_with_public_values(
+        &self,
+        vm_proof: ZKVMProof<E, PCS>,
+        transcript: impl ForkableTranscript<E>,
+        expected: &PublicValues<u32>,
+    ) -> Result<bool, ZKVMError> {
+        self.verify_proof_with_raw_pi(vm_proof, transcript, expected.to_vec::<E>(), true)
+    }
+
+    /// Verify a trace to optional halt, against public values given as raw field elements.
+    ///
+    /// This is [`Self::verify_proof_with_public_values`] for circuits which do not use the
+    /// riscv public value layout.
+    pub fn verify_proof_with_raw_pi(
+        &self,
+        mut vm_proof: ZKVMProof<E, PCS>,
+        transcript: impl ForkableTranscript<E>,
+        raw_pi: Vec<Vec<E::BaseField>>,
+        does_halt: bool,
+    ) -> Result<bool, ZKVMError> {
+        if vm_proof.raw_pi.len() != raw_pi.len() || vm_proof.pi_evals.len() != raw_pi.len() {
+            return Err(ZKVMError::VerifyError(format!(
+                "expected {} public values, got {}",
+                raw_pi.len(),
+                vm_proof.raw_pi.len()
+            )));
+        }
+        for (i, (got, expected)) in izip!(&vm_proof.raw_pi, &raw_pi).enumerate() {
+            if got != expected {
+                return Err(ZKVMError::VerifyError(format!(
+                    "pub input on index {i} mismatch {got:?} != {expected:?}"
+                )));
+            }
+        }
+        // Constant public values evaluate to themselves. The evaluations of the others are
+        // checked against `raw_pi` by the table proofs which use them.
+        for (eval, raw) in izip!(&mut vm_proof.pi_evals, &raw_pi) {
+            if let [v] = raw[..] {
+                *eval = E::from(v);
+            }
+        }
+        vm_proof.raw_pi = raw_pi;
+        self.verify_proof_halt(vm_proof, transcript, does_halt)
+    }
+
+    /// Verify the segments of an execution, from the state `init` to halt.
+    ///
+    /// Each segment must start from the state where the previous one ended, on the same public
+    /// input and hints, and only the last segment may halt. Registers, memory and hints are
+    /// linked by the commitments of their tables to the segment state, and the first segment
+    /// starts from the initial state committed in the verifying key. This requires a verifying
+    /// key built in continuation mode.
+    pub fn verify_segments(
+        &self,
+        segments: Vec<ZKVMProof<E, PCS>>,
+        transcript: impl ForkableTranscript<E>,
+        init: &SegmentState,
+    ) -> Result<bool, ZKVMError> {
+        // The links are only sound if the circuits bind the segment state to the public values.
+        // PC and cycle are bound by the global state rather than by a circuit.
+        let queried = chain!(
+            self.vk.circuit_vks.values().flat_map(|vk| vk
+                .get_cs()
+                .instance_name_map
+                .keys()
+                .copied()),
+            queried_instances(&self.vk.initial_global_state_expr),
+            queried_instances(&self.vk.finalize_global_state_expr),
+        )
+        .collect::<BTreeSet<_>>();
+        let mut bound = SEGMENT_LINKS
+            .iter()
+            .flat_map(|(end_idx, init_idx)| [end_idx, init_idx]);
+        // The tables whose content is linked across segments.
+        let state_tables = self
+            .vk
+            .circuit_vks
+            .iter()
+            .filter(|(_, vk)| vk.get_cs().num_init_state_witin > 0)
+            .collect_vec();
+        if state_tables.is_empty() || !bound.all(|idx| queried.contains(&Instance(idx))) {
+            return Err(ZKVMError::VerifyError(
+                "verifying key is not in continuation mode".into(),
+            ));
+        }
+
+        let Some(first) = segments.first() else {
+            return Err(ZKVMError::VerifyError("no segment to verify".into()));
+        };
+        let expected = PublicValues::new_segment(0, init.clone(), SegmentState::default(), vec![])
+            .to_vec::<E>();
+        for &(_, init_idx) in &SEGMENT_LINKS {
+            if first.raw_pi.get(init_idx) != expected.get(init_idx) {
+                return Err(ZKVMError::VerifyError(format!(
+                    "segment 0 does not start from the initial state on index {init_idx}"
+                )));
+            }
+        }
+        for (name, vk) in &state_tables {
+            let (init_commit, _) = first.segment_state_commits(name);
+            if vk.init_state_commit.is_some() && init_commit != vk.init_state_commit.as_ref() {
+                return Err(ZKVMError::VerifyError(format!(
+                    "segment 0 does not start from the initial state of {name}"
+                )));
+            }
+        }
+        for (i, (prev, next)) in segments.iter().tuple_windows().enumerate() {
+            let invariants = SEGMENT_INVARIANTS.iter().map(|idx| (idx, idx));
+            for (end_idx, init_idx) in chain!(SEGMENT_LINKS, invariants) {
+                if prev.raw_pi.get(end_idx) != next.raw_pi.get(init_idx) {
+                    return Err(ZKVMError::VerifyError(format!(
+                        "segment {} does not continue segment {i} on index {init_idx}",
+                        i + 1
+                    )));
+                }
+            }
+            for (name, _) in &state_tables {
+                let (_, end_commit) = prev.segment_state_commits(name);
+                let (init_commit, _) = next.segment_state_commits(name);
+                if end_commit != init_commit {
+                    return Err(ZKVMError::VerifyError(format!(
+                        "segment {} does not continue segment {i} in {name}",
+                        i + 1
+                    )));
+                }
+            }
+        }
+
+        let num_segments = segments.len();
+        for (i, segment) in segments.into_iter().enumerate() {
+            if !self.verify_proof_halt(segment, transcript.clone(), i + 1 == num_segments)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
     /// Verify a trace from start to optional halt.
     pub fn verify_proof_halt(
         &self,
@@ -86,7 +320,8 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
 
         let pi_evals = &vm_proof.pi_evals;
 
-        // TODO fix soundness: construct raw public input by ourself and trustless from proof
+        // raw public input is taken from the proof as is;
+        // use `verify_proof_with_public_values` to bind it to values known to the verifier.
         // including raw public input to transcript
         vm_proof
             .raw_pi
@@ -124,6 +359,9 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             tracing::debug!("read {}'s commit", name);
             PCS::write_commitment(&proof.wits_commit, &mut transcript)
                 .map_err(ZKVMError::PCSError)?;
+            for commit in chain!(&proof.init_state_commit, &proof.final_state_commit) {
+                PCS::write_commitment(commit, &mut transcript).map_err(ZKVMError::PCSError)?;
+            }
         }
 
         // alpha, beta
@@ -160,13 +398,10 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             tracing::info!("verified proof for opcode {}", name);
 
             // getting the number of dummy padding item that we used in this opcode circuit
-            let num_lks = circuit_vk.get_cs().lk_expressions.len();
-            let num_padded_lks_per_instance = next_pow2_instance_padding(num_lks) - num_lks;
-            let num_padded_instance =
-                next_pow2_instance_padding(opcode_proof.num_instances) - opcode_proof.num_instances;
-            dummy_table_item_multiplicity += num_padded_lks_per_instance
-                * opcode_proof.num_instances
-                + num_lks.next_power_of_two() * num_padded_instance;
+            dummy_table_item_multiplicity += dummy_lk_multiplicity(
+                circuit_vk.get_cs().lk_expressions.len(),
+                opcode_proof.num_instances,
+            );
 
             prod_r *= opcode_proof.record_r_out_evals.iter().product::<E>();
             prod_w *= opcode_proof.record_w_out_evals.iter().product::<E>();
@@ -348,7 +583,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                 phantom: PhantomData,
             },
             transcript,
-        );
+        )?;
         let (input_opening_point, expected_evaluation) = (
             main_sel_subclaim
                 .point
@@ -650,7 +885,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                     phantom: PhantomData,
                 },
                 transcript,
-            );
+            )?;
             let (input_opening_point, expected_evaluation) = (
                 sel_subclaim.point.iter().map(|c| c.elements).collect_vec(),
                 sel_subclaim.expected_evaluation,
@@ -757,6 +992,13 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
 
         // assume public io is tiny vector, so we evaluate it directly without PCS
         for &Instance(idx) in cs.instance_name_map.keys() {
+            if !raw_pi[idx].len().is_power_of_two()
+                || ceil_log2(raw_pi[idx].len()) > input_opening_point.len()
+            {
+                return Err(ZKVMError::VerifyError(format!(
+                    "pub input on index {idx} does not fit table {name}"
+                )));
+            }
             let poly = raw_pi[idx].to_vec().into_mle();
             let expected_eval = poly.evaluate(&input_opening_point[..poly.num_vars()]);
             let eval = pi[idx];
@@ -794,11 +1036,18 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             proof.fixed_in_evals.len(),
         );
 
+        if proof.wits_in_evals.len() != cs.num_witin as usize {
+            return Err(ZKVMError::VerifyError(format!(
+                "[table {name}] expected {} witness evaluations",
+                cs.num_witin
+            )));
+        }
+        let [main, init_state, final_state] = cs.witin_groups();
         PCS::simple_batch_verify(
             vp,
             &proof.wits_commit,
             &input_opening_point,
-            &proof.wits_in_evals,
+            &proof.wits_in_evals[main],
             &proof.wits_opening_proof,
             transcript,
         )
@@ -809,10 +1058,54 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             proof.wits_in_evals.len(),
         );
 
+        // the segment state is opened at the same point, if the table has one
+        for (group, commit, opening_proof) in [
+            (
+                init_state,
+                &proof.init_state_commit,
+                &proof.init_state_opening_proof,
+            ),
+            (
+                final_state,
+                &proof.final_state_commit,
+                &proof.final_state_opening_proof,
+            ),
+        ] {
+            match (commit, opening_proof) {
+                (Some(commit), Some(opening_proof)) if !group.is_empty() => {
+                    PCS::simple_batch_verify(
+                        vp,
+                        commit,
+                        &input_opening_point,
+                        &proof.wits_in_evals[group],
+                        opening_proof,
+                        transcript,
+                    )
+                    .map_err(ZKVMError::PCSError)?;
+                }
+                (None, None) if group.is_empty() => {}
+                _ => {
+                    return Err(ZKVMError::VerifyError(format!(
+                        "[table {name}] segment state does not match its commitment"
+                    )));
+                }
+            }
+        }
+
         Ok(input_opening_point)
     }
 }
 
+/// The number of dummy lookups in the tower of an opcode circuit with `num_lks` lookups.
+///
+/// The prover interleaves the lookups of each instance into `num_lks.next_power_of_two()`
+/// slots, and pads the instances with ones that have only dummy lookups.
+pub(crate) fn dummy_lk_multiplicity(num_lks: usize, num_instances: usize) -> usize {
+    let num_padded_lks_per_instance = num_lks.next_power_of_two() - num_lks;
+    let num_padded_instance = next_pow2_instance_padding(num_instances) - num_instances;
+    num_padded_lks_per_instance * num_instances + num_lks.next_power_of_two() * num_padded_instance
+}
+
 pub struct TowerVerify;
 
 pub type TowerVerifyResult<E> = Result<
@@ -833,6 +1126,28 @@ impl TowerVerify {
         num_variables: Vec<usize>,
         num_fanin: usize,
         transcript: &mut impl Transcript<E>,
+    ) -> TowerVerifyResult<E> {
+        Self::verify_and_record(
+            prod_out_evals,
+            logup_out_evals,
+            tower_proofs,
+            num_variables,
+            num_fanin,
+            transcript,
+            None,
+        )
+    }
+
+    /// Same as [`Self::verify`], and push the sumcheck of every layer to `sumchecks`,
+    /// so that they can be proven again by the recursion circuits.
+    pub fn verify_and_record<E: ExtensionField>(
+        prod_out_evals: Vec<Vec<E>>,
+        logup_out_evals: Vec<Vec<E>>,
+        tower_proofs: &TowerProofs<E>,
+        num_variables: Vec<usize>,
+        num_fanin: usize,
+        transcript: &mut impl Transcript<E>,
+        mut sumchecks: Option<&mut Vec<SumcheckInstance<E>>>,
     ) -> TowerVerifyResult<E> {
         // XXX to sumcheck batched product argument with logup, we limit num_product_fanin to 2
         // TODO mayber give a better naming?
@@ -921,10 +1236,23 @@ impl TowerVerify {
                         phantom: PhantomData,
                     },
                     transcript,
-                );
+                )?;
 
                 // check expected_evaluation
                 let rt: Point<E> = sumcheck_claim.point.iter().map(|c| c.elements).collect();
+                if let Some(sumchecks) = sumchecks.as_deref_mut() {
+                    sumchecks.push(SumcheckInstance {
+                        statement: SumcheckStatement {
+                            claimed_sum: *out_claim,
+                            challenges: rt.clone(),
+                            expected_evaluation: sumcheck_claim.expected_evaluation,
+                        },
+                        round_evals: tower_proofs.proofs[round]
+                            .iter()
+                            .map(|msg| msg.evaluations().to_vec())
+                            .collect(),
+                    });
+                }
                 let expected_evaluation: E = (0..num_prod_spec)
                     .zip(alpha_pows.iter())
                     .zip(num_variables.iter())
@@ -3,6 +3,8 @@ use crate::{
     error::ZKVMError,
     expression::Expression,
     instructions::Instruction,
+    recursion::RecursionCircuit,
+    scheme::envelope::{self, PayloadKind},
     state::StateCircuit,
     tables::TableCircuit,
     witness::{LkMultiplicity, RowMajorMatrix},
@@ -14,8 +16,11 @@ use mpcs::PolynomialCommitmentScheme;
 use multilinear_extensions::{
     mle::DenseMultilinearExtension, virtual_poly::ArcMultilinearExtension,
 };
-use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 use strum_macros::EnumIter;
 use sumcheck::structs::IOPProverMessage;
 
@@ -43,7 +48,7 @@ pub struct TowerProverSpec<'a, E: ExtensionField> {
 pub type WitnessId = u16;
 pub type ChallengeId = u16;
 
-#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ROMType {
     U5 = 0,      // 2^5 = 32
     U8,          // 2^8 = 256
@@ -54,14 +59,17 @@ pub enum ROMType {
     Xor,         // a ^ b where a, b are bytes
     Ltu,         // a <(usign) b where a, b are bytes and the result is 0/1.
     Pow,         // a ** b where a is 2 and b is 5-bit value
+    Shl,         // (a << b) & 0xff where a is a byte and b < 8
     Instruction, // Decoded instruction from the fixed program.
+    Sumcheck,    // Public part of a sumcheck round, in the recursion circuits.
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RAMType {
     GlobalState,
     Register,
     Memory,
+    SumcheckClaim,
 }
 
 /// A point is a vector of num_var length
@@ -113,10 +121,17 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ProvingKey<E, PCS> {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct VerifyingKey<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     pub(crate) cs: ConstraintSystem<E>,
     pub fixed_commit: Option<PCS::Commitment>,
+    /// In continuation mode, the commitment to the initial segment state of a table whose
+    /// content is known at the start of an execution.
+    pub init_state_commit: Option<PCS::Commitment>,
 }
 
 impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> VerifyingKey<E, PCS> {
@@ -130,7 +145,12 @@ pub struct ProgramParams {
     pub platform: Platform,
     pub program_size: usize,
     pub pub_io_len: usize,
+    pub pub_output_len: usize,
     pub static_memory_len: usize,
+    /// Prove segments of an execution: the registers, memory and hints at both ends of a segment
+    /// are committed apart from the other witnesses, so that the verifier can link consecutive
+    /// segments by their commitments.
+    pub continuation: bool,
 }
 
 impl Default for ProgramParams {
@@ -139,7 +159,9 @@ impl Default for ProgramParams {
             platform: CENO_PLATFORM,
             program_size: (1 << 14),
             pub_io_len: (1 << 2),
+            pub_output_len: (1 << 2),
             static_memory_len: (1 << 16),
+            continuation: false,
         }
     }
 }
@@ -190,6 +212,16 @@ impl<E: ExtensionField> ZKVMConstraintSystem<E> {
         config
     }
 
+    pub fn register_recursion_circuit<RC: RecursionCircuit<E>>(&mut self) -> RC::Config {
+        let mut cs = ConstraintSystem::new(|| format!("recursion/{}", RC::name()));
+        let mut circuit_builder =
+            CircuitBuilder::<E>::new_with_params(&mut cs, self.params.clone());
+        let config = RC::construct_circuit(&mut circuit_builder).unwrap();
+        assert!(self.circuit_css.insert(RC::name(), cs).is_none());
+
+        config
+    }
+
     pub fn register_global_state<SC: StateCircuit<E>>(&mut self) {
         let mut cs = ConstraintSystem::new(|| "riscv_state");
         let mut circuit_builder =
@@ -212,6 +244,9 @@ impl<E: ExtensionField> ZKVMConstraintSystem<E> {
 #[derive(Default, Clone)]
 pub struct ZKVMFixedTraces<E: ExtensionField> {
     pub circuit_fixed_traces: BTreeMap<String, Option<RowMajorMatrix<E::BaseField>>>,
+    /// In continuation mode, the initial segment state of tables, see
+    /// [`TableCircuit::generate_init_state`].
+    pub circuit_init_states: BTreeMap<String, RowMajorMatrix<E::BaseField>>,
 }
 
 impl<E: ExtensionField> ZKVMFixedTraces<E> {
@@ -219,6 +254,13 @@ impl<E: ExtensionField> ZKVMFixedTraces<E> {
         assert!(self.circuit_fixed_traces.insert(OC::name(), None).is_none());
     }
 
+    pub fn register_recursion_circuit<RC: RecursionCircuit<E>>(
+        &mut self,
+        _cs: &ZKVMConstraintSystem<E>,
+    ) {
+        assert!(self.circuit_fixed_traces.insert(RC::name(), None).is_none());
+    }
+
     pub fn register_table_circuit<TC: TableCircuit<E>>(
         &mut self,
         cs: &ZKVMConstraintSystem<E>,
@@ -234,6 +276,9 @@ impl<E: ExtensionField> ZKVMFixedTraces<E> {
                 )
                 .is_none()
         );
+        if let Some(init_state) = TC::generate_init_state(config, input) {
+            self.circuit_init_states.insert(TC::name(), init_state);
+        }
     }
 }
 
@@ -280,6 +325,48 @@ impl<E: ExtensionField> ZKVMWitnesses<E> {
         Ok(())
     }
 
+    /// Assign `records` to an opcode circuit, after the records of the previous calls.
+    ///
+    /// This lets the steps of an execution be assigned in chunks, as the emulator produces them.
+    pub fn append_opcode_circuit<OC: Instruction<E>>(
+        &mut self,
+        cs: &ZKVMConstraintSystem<E>,
+        config: &OC::InstructionConfig,
+        records: &[StepRecord],
+    ) -> Result<(), ZKVMError> {
+        assert!(self.combined_lk_mlt.is_none());
+        assert!(!self.witnesses_tables.contains_key(&OC::name()));
+
+        let cs = cs.get_cs(&OC::name()).unwrap();
+        let witness = self.witnesses_opcodes.entry(OC::name()).or_insert_with(|| {
+            RowMajorMatrix::new(0, cs.num_witin as usize, OC::padding_strategy())
+        });
+        let logup_multiplicity = self.lk_mlts.entry(OC::name()).or_default();
+        OC::append_instances(config, witness, logup_multiplicity, records)
+    }
+
+    pub fn assign_recursion_circuit<RC: RecursionCircuit<E>>(
+        &mut self,
+        cs: &ZKVMConstraintSystem<E>,
+        config: &RC::Config,
+        input: &RC::Input,
+    ) -> Result<(), ZKVMError> {
+        assert!(self.combined_lk_mlt.is_none());
+
+        let cs = cs.get_cs(&RC::name()).unwrap();
+        let (witness, logup_multiplicity) =
+            RC::assign_instances(config, cs.num_witin as usize, input)?;
+        assert!(self.witnesses_opcodes.insert(RC::name(), witness).is_none());
+        assert!(!self.witnesses_tables.contains_key(&RC::name()));
+        assert!(
+            self.lk_mlts
+                .insert(RC::name(), logup_multiplicity)
+                .is_none()
+        );
+
+        Ok(())
+    }
+
     // merge the multiplicities in each opcode circuit into one
     pub fn finalize_lk_multiplicities(&mut self, is_keep_raw_lk_mlts: bool) {
         assert!(self.combined_lk_mlt.is_none());
@@ -384,7 +471,11 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProvingKey<E, PC
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct ZKVMVerifyingKey<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     pub vp: PCS::VerifierParam,
     // vk for opcode and table circuits
@@ -393,3 +484,32 @@ pub struct ZKVMVerifyingKey<E: ExtensionField, PCS: PolynomialCommitmentScheme<E
     pub initial_global_state_expr: Expression<E>,
     pub finalize_global_state_expr: Expression<E>,
 }
+
+impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifyingKey<E, PCS>
+where
+    Self: Serialize + DeserializeOwned,
+{
+    /// Keccak-256 of the canonical encoding of the verifying key: the circuit constraint
+    /// systems, the fixed commitments, the global state expressions and the PCS parameters.
+    ///
+    /// Verifiers with the same fingerprint accept the same proofs.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        envelope::keccak256(&bincode::serialize(self).expect("serialize verifying key"))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ZKVMError> {
+        envelope::encode::<E, PCS, _>(PayloadKind::VerifyingKey, self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZKVMError> {
+        envelope::decode::<E, PCS, _>(PayloadKind::VerifyingKey, bytes)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ZKVMError> {
+        envelope::save::<E, PCS, _>(PayloadKind::VerifyingKey, self, path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ZKVMError> {
+        envelope::load::<E, PCS, _>(PayloadKind::VerifyingKey, path)
+    }
+}
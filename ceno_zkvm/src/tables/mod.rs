@@ -30,6 +30,15 @@ pub trait TableCircuit<E: ExtensionField> {
         input: &Self::FixedInput,
     ) -> RowMajorMatrix<E::BaseField>;
 
+    /// In continuation mode, the content of the table at the start of an execution, as the
+    /// columns of its initial segment state witnesses. `None` if it is not known in advance.
+    fn generate_init_state(
+        _config: &Self::TableConfig,
+        _input: &Self::FixedInput,
+    ) -> Option<RowMajorMatrix<E::BaseField>> {
+        None
+    }
+
     fn assign_instances(
         config: &Self::TableConfig,
         num_witin: usize,
@@ -103,6 +103,24 @@ impl OpsTable for PowTable {
 }
 pub type PowTableCircuit<E> = OpsTableCircuit<E, PowTable>;
 
+pub struct ShlTable;
+impl OpsTable for ShlTable {
+    const ROM_TYPE: ROMType = ROMType::Shl;
+    fn len() -> usize {
+        1 << 11
+    }
+
+    fn content() -> Vec<[u64; 3]> {
+        (0..Self::len() as u64)
+            .map(|i| {
+                let (a, b) = Self::unpack(i);
+                [a, b, (a << b) & 0xff]
+            })
+            .collect()
+    }
+}
+pub type ShlTableCircuit<E> = OpsTableCircuit<E, ShlTable>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -12,9 +12,7 @@ use crate::{
     utils::i64_to_base,
     witness::RowMajorMatrix,
 };
-use ceno_emul::{
-    InsnFormat, InsnFormat::*, InsnKind::*, Instruction, PC_STEP_SIZE, Program, WORD_SIZE,
-};
+use ceno_emul::{InsnFormat, InsnFormat::*, InsnKind::*, Instruction, Program};
 use ff_ext::ExtensionField;
 use goldilocks::SmallField;
 use itertools::Itertools;
@@ -22,15 +20,25 @@ use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterato
 
 /// This structure establishes the order of the fields in instruction records, common to the program table and circuit fetches.
 #[derive(Clone, Debug)]
-pub struct InsnRecord<T>([T; 6]);
+pub struct InsnRecord<T>([T; 7]);
 
 impl<T> InsnRecord<T> {
-    pub fn new(pc: T, kind: T, rd: Option<T>, rs1: T, rs2: T, imm_internal: T) -> Self
+    /// `compressed` is 1 for an instruction expanded from a 16-bit compressed instruction, which
+    /// steps the pc by 2 instead of 4.
+    pub fn new(
+        pc: T,
+        kind: T,
+        rd: Option<T>,
+        rs1: T,
+        rs2: T,
+        imm_internal: T,
+        compressed: T,
+    ) -> Self
     where
         T: From<u32>,
     {
         let rd = rd.unwrap_or_else(|| T::from(Instruction::RD_NULL));
-        InsnRecord([pc, kind, rd, rs1, rs2, imm_internal])
+        InsnRecord([pc, kind, rd, rs1, rs2, imm_internal, compressed])
     }
 
     pub fn as_slice(&self) -> &[T] {
@@ -47,6 +55,7 @@ impl<F: SmallField> InsnRecord<F> {
             (insn.rs1_or_zero() as u64).into(),
             (insn.rs2_or_zero() as u64).into(),
             i64_to_base(InsnRecord::imm_internal(insn)),
+            (insn.compressed as u64).into(),
         ])
     }
 }
@@ -103,6 +112,7 @@ impl<E: ExtensionField> TableCircuit<E> for ProgramTableCircuit<E> {
             cb.create_fixed(|| "rs1")?,
             cb.create_fixed(|| "rs2")?,
             cb.create_fixed(|| "imm_internal")?,
+            cb.create_fixed(|| "compressed")?,
         ]);
 
         let mlt = cb.create_witin(|| "mlt");
@@ -137,7 +147,6 @@ impl<E: ExtensionField> TableCircuit<E> for ProgramTableCircuit<E> {
         program: &Self::FixedInput,
     ) -> RowMajorMatrix<E::BaseField> {
         let num_instructions = program.instructions.len();
-        let pc_base = program.base_address;
         assert!(num_instructions <= config.program_size);
 
         let mut fixed = RowMajorMatrix::<E::BaseField>::new(
@@ -151,7 +160,7 @@ impl<E: ExtensionField> TableCircuit<E> for ProgramTableCircuit<E> {
             .with_min_len(MIN_PAR_SIZE)
             .zip((0..num_instructions).into_par_iter())
             .for_each(|(row, i)| {
-                let pc = pc_base + (i * PC_STEP_SIZE) as u32;
+                let pc = program.pcs()[i];
                 let insn = program.instructions[i];
                 let values: InsnRecord<_> = InsnRecord::from_decoded(pc, &insn);
 
@@ -175,7 +184,9 @@ impl<E: ExtensionField> TableCircuit<E> for ProgramTableCircuit<E> {
 
         let mut prog_mlt = vec![0_usize; program.instructions.len()];
         for (pc, mlt) in multiplicity {
-            let i = (*pc as usize - program.base_address as usize) / WORD_SIZE;
+            let i = program
+                .index_at(*pc as u32)
+                .expect("fetch of an instruction in the program");
             prog_mlt[i] = *mlt;
         }
 
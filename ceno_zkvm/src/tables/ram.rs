@@ -39,6 +39,7 @@ impl DynVolatileRamTable for HintsTable {
     const RAM_TYPE: RAMType = RAMType::Memory;
     const V_LIMBS: usize = 1; // See `MemoryExpr`.
     const ZERO_INIT: bool = false;
+    const SEGMENT_STATE: bool = true;
 
     fn offset_addr(params: &ProgramParams) -> Addr {
         params.platform.hints.start
@@ -62,6 +63,7 @@ impl NonVolatileTable for RegTable {
     const RAM_TYPE: RAMType = RAMType::Register;
     const V_LIMBS: usize = UINT_LIMBS; // See `RegisterExpr`.
     const WRITABLE: bool = true;
+    const SEGMENT_STATE: bool = true;
 
     fn name() -> &'static str {
         "RegTable"
@@ -81,6 +83,7 @@ impl NonVolatileTable for StaticMemTable {
     const RAM_TYPE: RAMType = RAMType::Memory;
     const V_LIMBS: usize = 1; // See `MemoryExpr`.
     const WRITABLE: bool = true;
+    const SEGMENT_STATE: bool = true;
 
     fn len(params: &ProgramParams) -> usize {
         params.static_memory_len
@@ -111,3 +114,23 @@ impl NonVolatileTable for PubIOTable {
 }
 
 pub type PubIOCircuit<E> = PubIORamCircuit<E, PubIOTable>;
+
+/// Public output: starts zeroed, and its final content is bound to the public values.
+#[derive(Clone)]
+pub struct PubOutTable;
+
+impl NonVolatileTable for PubOutTable {
+    const RAM_TYPE: RAMType = RAMType::Memory;
+    const V_LIMBS: usize = 1; // See `MemoryExpr`.
+    const WRITABLE: bool = true;
+
+    fn len(params: &ProgramParams) -> usize {
+        params.pub_output_len
+    }
+
+    fn name() -> &'static str {
+        "PubOutTable"
+    }
+}
+
+pub type PubOutCircuit<E> = PubIORamCircuit<E, PubOutTable>;
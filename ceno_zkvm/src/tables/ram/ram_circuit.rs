@@ -25,6 +25,8 @@ pub struct MemFinalRecord {
     pub addr: Addr,
     pub cycle: Cycle,
     pub value: Word,
+    /// The value at the start of the segment.
+    pub init_value: Word,
 }
 
 impl GetAddr for MemInitRecord {
@@ -49,6 +51,9 @@ pub trait NonVolatileTable {
     const RAM_TYPE: RAMType;
     const V_LIMBS: usize;
     const WRITABLE: bool;
+    /// In continuation mode, the content at both ends of a segment is the segment state.
+    /// Otherwise, the table is initialized as in a full execution.
+    const SEGMENT_STATE: bool = false;
 
     fn name() -> &'static str;
 
@@ -61,6 +66,9 @@ pub trait NonVolatileTable {
 /// - with fixed initial content,
 /// - with witnessed final content that the program wrote, if WRITABLE,
 /// - or final content equal to initial content, if not WRITABLE.
+///
+/// In continuation mode, tables with a `SEGMENT_STATE` take both their initial and final
+/// content from witnesses instead, see `ConstraintSystem::num_init_state_witin`.
 pub struct NonVolatileRamCircuit<E, R>(PhantomData<(E, R)>);
 
 impl<E: ExtensionField, NVRAM: NonVolatileTable + Send + Sync + Clone> TableCircuit<E>
@@ -90,6 +98,13 @@ impl<E: ExtensionField, NVRAM: NonVolatileTable + Send + Sync + Clone> TableCirc
         config.gen_init_state(num_fixed, init_v)
     }
 
+    fn generate_init_state(
+        config: &Self::TableConfig,
+        init_v: &Self::FixedInput,
+    ) -> Option<RowMajorMatrix<E::BaseField>> {
+        config.gen_segment_init_state(init_v)
+    }
+
     fn assign_instances(
         config: &Self::TableConfig,
         num_witin: usize,
@@ -104,11 +119,15 @@ impl<E: ExtensionField, NVRAM: NonVolatileTable + Send + Sync + Clone> TableCirc
 
 /// PubIORamCircuit initializes and finalizes memory
 /// - at fixed addresses,
-/// - with content from the public input of proofs.
+/// - with content from the public values of proofs.
 ///
-/// This circuit does not and cannot decide whether the memory is mutable or not.
+/// If not WRITABLE, the initial and final content both equal the public input.
+/// This does not and cannot decide whether the memory is mutable or not.
 /// It supports LOAD where the program reads the public input,
 /// or STORE where the memory content must equal the public input after execution.
+///
+/// If WRITABLE, the initial content is zero and the final content equals the public output.
+/// In continuation mode, the initial content is the public output of the previous segments.
 pub struct PubIORamCircuit<E, R>(PhantomData<(E, R)>);
 
 impl<E: ExtensionField, NVRAM: NonVolatileTable + Send + Sync + Clone> TableCircuit<E>
@@ -157,6 +176,9 @@ pub trait DynVolatileRamTable {
     const RAM_TYPE: RAMType;
     const V_LIMBS: usize;
     const ZERO_INIT: bool;
+    /// In continuation mode, the content of a table which is not ZERO_INIT is the segment
+    /// state. Otherwise, the table is initialized as in a full execution.
+    const SEGMENT_STATE: bool = false;
 
     fn offset_addr(params: &ProgramParams) -> Addr;
     fn end_addr(params: &ProgramParams) -> Addr;
@@ -182,6 +204,9 @@ pub trait DynVolatileRamTable {
 /// If not ZERO_INIT:
 /// - The initial content is an unconstrained prover hint.
 /// - The final content is equal to this initial content.
+///
+/// In continuation mode, tables with a `SEGMENT_STATE` commit this content as their initial
+/// segment state, which the verifier links across segments so that it is the same in all.
 pub struct DynVolatileRamCircuit<E, R>(PhantomData<(E, R)>);
 
 impl<E: ExtensionField, DVRAM: DynVolatileRamTable + Send + Sync + Clone> TableCircuit<E>
@@ -1,6 +1,6 @@
 use std::{marker::PhantomData, sync::Arc};
 
-use ceno_emul::{Addr, Cycle, WORD_SIZE};
+use ceno_emul::{Addr, Cycle, WORD_SIZE, Word};
 use ff_ext::ExtensionField;
 use goldilocks::SmallField;
 use itertools::Itertools;
@@ -31,6 +31,8 @@ pub struct NonVolatileTableConfig<NVRAM: NonVolatileTable + Send + Sync + Clone>
     init_v: Vec<Fixed>,
     addr: Fixed,
 
+    /// In continuation mode, the content at the start of the segment.
+    segment_init_v: Option<Vec<WitIn>>,
     final_v: Option<Vec<WitIn>>,
     final_cycle: WitIn,
 
@@ -42,26 +44,50 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> NonVolatileTableConfig<NVRAM
     pub fn construct_circuit<E: ExtensionField>(
         cb: &mut CircuitBuilder<E>,
     ) -> Result<Self, ZKVMError> {
-        let init_v = (0..NVRAM::V_LIMBS)
-            .map(|i| cb.create_fixed(|| format!("init_v_limb_{i}")))
-            .collect::<Result<Vec<Fixed>, ZKVMError>>()?;
+        // In continuation mode, the content at both ends of the segment is the segment state.
+        let segment_state = NVRAM::SEGMENT_STATE && cb.params.continuation;
+
+        let init_v = if !segment_state {
+            (0..NVRAM::V_LIMBS)
+                .map(|i| cb.create_fixed(|| format!("init_v_limb_{i}")))
+                .collect::<Result<Vec<Fixed>, ZKVMError>>()?
+        } else {
+            vec![]
+        };
         let addr = cb.create_fixed(|| "addr")?;
 
         let final_cycle = cb.create_witin(|| "final_cycle");
-        let final_v = if NVRAM::WRITABLE {
-            Some(
-                (0..NVRAM::V_LIMBS)
-                    .map(|i| cb.create_witin(|| format!("final_v_limb_{i}")))
-                    .collect::<Vec<WitIn>>(),
-            )
-        } else {
-            None
+        let segment_init_v = segment_state.then(|| {
+            (0..NVRAM::V_LIMBS)
+                .map(|i| cb.create_init_state_witin(|| format!("init_v_limb_{i}")))
+                .collect::<Vec<WitIn>>()
+        });
+        let final_v = NVRAM::WRITABLE.then(|| {
+            (0..NVRAM::V_LIMBS)
+                .map(|i| {
+                    let name = || format!("final_v_limb_{i}");
+                    if segment_state {
+                        cb.create_final_state_witin(name)
+                    } else {
+                        cb.create_witin(name)
+                    }
+                })
+                .collect::<Vec<WitIn>>()
+        });
+
+        let init_expr = match &segment_init_v {
+            Some(v_limb) => v_limb.iter().map(|v| v.expr()).collect_vec(),
+            None => init_v.iter().map(|v| v.expr()).collect_vec(),
         };
+        let final_expr = final_v
+            .as_ref()
+            .map(|v_limb| v_limb.iter().map(|v| v.expr()).collect_vec())
+            .unwrap_or_else(|| init_expr.clone());
 
         let init_table = [
             vec![(NVRAM::RAM_TYPE as usize).into()],
             vec![Expression::Fixed(addr)],
-            init_v.iter().map(|v| v.expr()).collect_vec(),
+            init_expr,
             vec![Expression::ZERO], // Initial cycle.
         ]
         .concat();
@@ -70,10 +96,7 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> NonVolatileTableConfig<NVRAM
             // a v t
             vec![(NVRAM::RAM_TYPE as usize).into()],
             vec![Expression::Fixed(addr)],
-            final_v
-                .as_ref()
-                .map(|v_limb| v_limb.iter().map(|v| v.expr()).collect_vec())
-                .unwrap_or_else(|| init_v.iter().map(|v| v.expr()).collect_vec()),
+            final_expr,
             vec![final_cycle.expr()],
         ]
         .concat();
@@ -99,6 +122,7 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> NonVolatileTableConfig<NVRAM
 
         Ok(Self {
             init_v,
+            segment_init_v,
             final_v,
             addr,
             final_cycle,
@@ -147,6 +171,32 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> NonVolatileTableConfig<NVRAM
         init_table
     }
 
+    /// In continuation mode, the columns of the initial segment state witnesses holding
+    /// `init_mem`, or `None` if the table has no segment state.
+    pub fn gen_segment_init_state<F: SmallField>(
+        &self,
+        init_mem: &[MemInitRecord],
+    ) -> Option<RowMajorMatrix<F>> {
+        let segment_init_v = self.segment_init_v.as_ref()?;
+        // The witnesses of the state, numbered from the first one.
+        let limbs = (0..segment_init_v.len() as u16)
+            .map(|id| WitIn { id })
+            .collect_vec();
+
+        let mut init_state = RowMajorMatrix::<F>::new(
+            NVRAM::len(&self.params),
+            limbs.len(),
+            InstancePaddingStrategy::Default,
+        );
+        init_state
+            .par_iter_mut()
+            .with_min_len(MIN_PAR_SIZE)
+            .zip_eq(init_mem.into_par_iter())
+            .for_each(|(row, rec)| set_limbs(row, &limbs, rec.value));
+
+        Some(init_state)
+    }
+
     /// TODO consider taking RowMajorMatrix as argument to save allocations.
     pub fn assign_instances<F: SmallField>(
         &self,
@@ -165,17 +215,11 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> NonVolatileTableConfig<NVRAM
             .with_min_len(MIN_PAR_SIZE)
             .zip_eq(final_mem.into_par_iter())
             .for_each(|(row, rec)| {
+                if let Some(segment_init_v) = &self.segment_init_v {
+                    set_limbs(row, segment_init_v, rec.init_value);
+                }
                 if let Some(final_v) = &self.final_v {
-                    if final_v.len() == 1 {
-                        // Assign value directly.
-                        set_val!(row, final_v[0], rec.value as u64);
-                    } else {
-                        // Assign value limbs.
-                        final_v.iter().enumerate().for_each(|(l, limb)| {
-                            let val = (rec.value >> (l * LIMB_BITS)) & LIMB_MASK;
-                            set_val!(row, limb, val as u64);
-                        });
-                    }
+                    set_limbs(row, final_v, rec.value);
                 }
                 set_val!(row, self.final_cycle, rec.cycle);
             });
@@ -184,8 +228,23 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> NonVolatileTableConfig<NVRAM
     }
 }
 
+/// Assign `value` to the witnesses `limbs` of `row`, directly or as 16-bit limbs.
+fn set_limbs<F: SmallField>(row: &mut [F], limbs: &[WitIn], value: Word) {
+    if limbs.len() == 1 {
+        // Assign value directly.
+        set_val!(row, limbs[0], value as u64);
+    } else {
+        // Assign value limbs.
+        limbs.iter().enumerate().for_each(|(l, limb)| {
+            let val = (value >> (l * LIMB_BITS)) & LIMB_MASK;
+            set_val!(row, limb, val as u64);
+        });
+    }
+}
+
 /// define public io
-/// init value set by instance
+/// init value set by instance, or zero with final value set by instance if WRITABLE
+/// (init value set by instance as well in continuation mode)
 #[derive(Clone, Debug)]
 pub struct PubIOTableConfig<NVRAM: NonVolatileTable + Send + Sync + Clone> {
     addr: Fixed,
@@ -200,8 +259,17 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> PubIOTableConfig<NVRAM> {
     pub fn construct_circuit<E: ExtensionField>(
         cb: &mut CircuitBuilder<E>,
     ) -> Result<Self, ZKVMError> {
-        assert!(!NVRAM::WRITABLE);
-        let init_v = cb.query_public_io()?;
+        let (init_v, final_v) = if NVRAM::WRITABLE {
+            let init_v = if cb.params.continuation {
+                cb.query_init_public_output()?.expr()
+            } else {
+                Expression::ZERO
+            };
+            (init_v, cb.query_public_output()?.expr())
+        } else {
+            let v = cb.query_public_io()?;
+            (v.expr(), v.expr())
+        };
         let addr = cb.create_fixed(|| "addr")?;
 
         let final_cycle = cb.create_witin(|| "final_cycle");
@@ -209,7 +277,7 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> PubIOTableConfig<NVRAM> {
         let init_table = [
             vec![(NVRAM::RAM_TYPE as usize).into()],
             vec![Expression::Fixed(addr)],
-            vec![init_v.expr()],
+            vec![init_v],
             vec![Expression::ZERO], // Initial cycle.
         ]
         .concat();
@@ -218,7 +286,7 @@ impl<NVRAM: NonVolatileTable + Send + Sync + Clone> PubIOTableConfig<NVRAM> {
             // a v t
             vec![(NVRAM::RAM_TYPE as usize).into()],
             vec![Expression::Fixed(addr)],
-            vec![init_v.expr()],
+            vec![final_v],
             vec![final_cycle.expr()],
         ]
         .concat();
@@ -325,10 +393,22 @@ impl<DVRAM: DynVolatileRamTable + Send + Sync + Clone> DynVolatileRamTableConfig
             WORD_SIZE,
         );
 
-        let final_v = (0..DVRAM::V_LIMBS)
-            .map(|i| cb.create_witin(|| format!("final_v_limb_{i}")))
-            .collect::<Vec<WitIn>>();
-        let final_cycle = cb.create_witin(|| "final_cycle");
+        // In continuation mode, the content is the segment state, which does not change.
+        let segment_state = DVRAM::SEGMENT_STATE && cb.params.continuation && !DVRAM::ZERO_INIT;
+
+        let (final_v, final_cycle) = if segment_state {
+            // The segment state comes last.
+            let final_cycle = cb.create_witin(|| "final_cycle");
+            let final_v = (0..DVRAM::V_LIMBS)
+                .map(|i| cb.create_init_state_witin(|| format!("final_v_limb_{i}")))
+                .collect::<Vec<WitIn>>();
+            (final_v, final_cycle)
+        } else {
+            let final_v = (0..DVRAM::V_LIMBS)
+                .map(|i| cb.create_witin(|| format!("final_v_limb_{i}")))
+                .collect::<Vec<WitIn>>();
+            (final_v, cb.create_witin(|| "final_cycle"))
+        };
 
         let final_expr = final_v.iter().map(|v| v.expr()).collect_vec();
         let init_expr = if DVRAM::ZERO_INIT {
@@ -420,16 +500,7 @@ impl<DVRAM: DynVolatileRamTable + Send + Sync + Clone> DynVolatileRamTableConfig
             .for_each(|(i, (row, rec))| {
                 assert_eq!(rec.addr, DVRAM::addr(&self.params, i));
 
-                if self.final_v.len() == 1 {
-                    // Assign value directly.
-                    set_val!(row, self.final_v[0], rec.value as u64);
-                } else {
-                    // Assign value limbs.
-                    self.final_v.iter().enumerate().for_each(|(l, limb)| {
-                        let val = (rec.value >> (l * LIMB_BITS)) & LIMB_MASK;
-                        set_val!(row, limb, val as u64);
-                    });
-                }
+                set_limbs(row, &self.final_v, rec.value);
                 set_val!(row, self.final_cycle, rec.cycle);
 
                 set_val!(row, offset_addr, rec.addr as u64);
@@ -471,6 +542,7 @@ mod tests {
                 addr: HintsTable::addr(&def_params, i),
                 cycle: 0,
                 value: 0,
+                init_value: 0,
             })
             .collect_vec();
         let wit = HintsCircuit::<E>::assign_instances(
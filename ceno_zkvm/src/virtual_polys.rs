@@ -299,7 +299,8 @@ mod tests {
                 phantom: std::marker::PhantomData,
             },
             &mut transcript,
-        );
+        )
+        .expect("the proof should verify");
 
         let mut verifier_poly = VirtualPolynomial::new(max_num_vars);
         verifier_poly.add_mle_list(f1.to_vec(), E::ONE);
@@ -20,7 +20,7 @@ use thread_local::ThreadLocal;
 use crate::{
     instructions::InstancePaddingStrategy,
     structs::ROMType,
-    tables::{AndTable, LtuTable, OpsTable, OrTable, PowTable, XorTable},
+    tables::{AndTable, LtuTable, OpsTable, OrTable, PowTable, ShlTable, XorTable},
     utils::next_pow2_instance_padding,
 };
 
@@ -66,6 +66,18 @@ impl<T: Sized + Sync + Clone + Send + Copy + Default + From<u64>> RowMajorMatrix
         self.values.len() / self.num_col
     }
 
+    pub fn num_col(&self) -> usize {
+        self.num_col
+    }
+
+    /// Add `num_rows` rows of default values at the end, and return them for assignment.
+    pub fn append_rows(&mut self, num_rows: usize) -> &mut [T] {
+        let start = self.values.len();
+        self.values
+            .resize(start + num_rows * self.num_col, T::default());
+        &mut self.values[start..]
+    }
+
     pub fn iter_rows(&self) -> Chunks<T> {
         self.values.chunks(self.num_col)
     }
@@ -288,6 +300,11 @@ impl LkMultiplicity {
         self.logic_u8::<PowTable>(2, v)
     }
 
+    /// lookup (a << b) & 0xff
+    pub fn lookup_shl_byte(&mut self, a: u64, b: u64) {
+        self.logic_u8::<ShlTable>(a, b)
+    }
+
     /// Fetch instruction at pc
     pub fn fetch(&mut self, pc: u32) {
         self.increment(ROMType::Instruction, pc as u64);
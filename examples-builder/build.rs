@@ -17,15 +17,27 @@ fn rerun_all_but_target(dir: &Path) {
     }
 }
 
-fn build_elfs() {
-    let out_dir = std::env::var_os("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("vars.rs");
-    let mut dest = File::create(dest_path).expect("failed to create vars.rs");
+/// The target of the examples, in ceno_rt.
+const TARGET: &str = "riscv32im-ceno-zkvm-elf";
 
-    // TODO(Matthias): skip building the elfs if we are in clippy or check mode.
-    // See git history for an attempt to do this.
+/// The examples with `required-features` in examples/Cargo.toml, their features and their
+/// target.
+///
+/// Features of ceno_rt and the target apply to every example of a build, so these are built
+/// separately.
+const FEATURE_EXAMPLES: &[(&str, &str, &str)] = &[
+    ("ceno_rt_free_list", "free-list-allocator", TARGET),
+    (
+        "ceno_rt_compressed",
+        "compressed",
+        "riscv32imc-ceno-zkvm-elf",
+    ),
+];
+
+fn cargo_build_examples(args: &[&str]) {
     let output = Command::new("cargo")
-        .args(["build", "--release", "--examples", "--target-dir", "target"])
+        .args(["build", "--release", "--target-dir", "target"])
+        .args(args)
         .current_dir("../examples")
         .env_clear()
         .envs(std::env::vars().filter(|x| !x.0.starts_with("CARGO_")))
@@ -36,17 +48,42 @@ fn build_elfs() {
         io::stderr().write_all(&output.stderr).unwrap();
         panic!("cargo build of examples failed.");
     }
+}
+
+fn build_elfs() {
+    let out_dir = std::env::var_os("OUT_DIR").unwrap();
+    let dest_path = Path::new(&out_dir).join("vars.rs");
+    let mut dest = File::create(dest_path).expect("failed to create vars.rs");
+
+    // TODO(Matthias): skip building the elfs if we are in clippy or check mode.
+    // See git history for an attempt to do this.
+    cargo_build_examples(&["--examples"]);
+    for (example, features, target) in FEATURE_EXAMPLES {
+        let target = format!("../ceno_rt/{target}.json");
+        cargo_build_examples(&[
+            "--example",
+            example,
+            "--features",
+            features,
+            "--target",
+            &target,
+        ]);
+    }
     // Contact Matthias, if your examples get complicated enough to need their own crates, instead of just being one file.
     for example in glob("../examples/examples/*.rs")
         .unwrap()
         .map(Result::unwrap)
     {
         let example = example.file_stem().unwrap().to_str().unwrap();
+        let target = FEATURE_EXAMPLES
+            .iter()
+            .find(|(name, ..)| *name == example)
+            .map_or(TARGET, |&(.., target)| target);
         writeln!(
             dest,
             r#"#[allow(non_upper_case_globals)]
             pub const {example}: &[u8] =
-                include_bytes!(r"{CARGO_MANIFEST_DIR}/../examples/target/riscv32im-ceno-zkvm-elf/release/examples/{example}");"#
+                include_bytes!(r"{CARGO_MANIFEST_DIR}/../examples/target/{target}/release/examples/{example}");"#
         ).expect("failed to write vars.rs");
     }
     rerun_all_but_target(Path::new("../examples"));
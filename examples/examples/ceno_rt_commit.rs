@@ -0,0 +1,12 @@
+extern crate ceno_rt;
+use rkyv::Archived;
+
+fn main() {
+    let a: &Archived<u32> = ceno_rt::read_public();
+    let b: &Archived<u32> = ceno_rt::read();
+    let product: u32 = a * b;
+
+    // Reveal the inputs and the result to the verifier.
+    ceno_rt::commit(&[a.to_native(), b.to_native()]);
+    ceno_rt::commit(&product);
+}
@@ -0,0 +1,19 @@
+//! Built for the target with the compressed instructions of the C extension.
+extern crate ceno_rt;
+
+extern crate alloc;
+use alloc::vec;
+use rkyv::Archived;
+
+fn main() {
+    let n: &Archived<u32> = ceno_rt::read();
+    let n = n.to_native() as usize;
+
+    // The Fibonacci numbers up to n, through memory.
+    let mut fib = vec![0u32; n.max(1) + 1];
+    fib[1] = 1;
+    for i in 2..=n {
+        fib[i] = fib[i - 1].wrapping_add(fib[i - 2]);
+    }
+    ceno_rt::commit(&fib[n]);
+}
@@ -0,0 +1,210 @@
+//! Verify a secp256k1 ECDSA signature using the curve syscalls.
+//!
+//! Scalars are reduced modulo the group order with plain 256-bit arithmetic, and the point
+//! `u1 * G + u2 * Q` is computed by double-and-add. Log the x coordinate of that point, which
+//! must equal `r`.
+
+extern crate ceno_rt;
+use ceno_rt::{
+    info_out,
+    syscalls::{secp256k1_add, secp256k1_double},
+};
+use core::slice;
+
+/// A 256-bit value, as little-endian words.
+type U256 = [u32; 8];
+/// A point in affine coordinates, `x` then `y`.
+type Point = [u32; 16];
+
+/// The order of the group of secp256k1.
+const N: U256 = [
+    0xd0364141, 0xbfd25e8c, 0xaf48a03b, 0xbaaedce6, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff,
+];
+
+/// The generator of secp256k1.
+const G: Point = [
+    0x16f81798, 0x59f2815b, 0x2dce28d9, 0x029bfcdb, 0xce870b07, 0x55a06295, 0xf9dcbbac, 0x79be667e,
+    0xfb10d4b8, 0x9c47d08f, 0xa6855419, 0xfd17b448, 0x0e1108a8, 0x5da4fbfc, 0x26a3c465, 0x483ada77,
+];
+
+/// The public key.
+const PUBLIC_KEY: Point = [
+    0xfdf2bdaf, 0x0c76176d, 0x755fcf9b, 0xe8d937ef, 0x74da4c51, 0x036020b1, 0x58d577ab, 0xfc2f295d,
+    0xf1bf5e11, 0x7000a2bd, 0xc97b6356, 0xb1c67259, 0x5c03b9d6, 0x05888461, 0xbbcb772d, 0xca27bf11,
+];
+
+/// The SHA-256 hash of the message "Hello, Ceno!".
+const Z: U256 = [
+    0xee05ff08, 0x561a3c8e, 0x9b3a274f, 0x31b88506, 0xcae5d642, 0x312c2624, 0xabc3b23c, 0x7b6a76ef,
+];
+
+/// The signature `(r, s)`.
+const R: U256 = [
+    0xf29dcbcc, 0x8e77ce52, 0xea01d327, 0x9f846e0e, 0x3590a5ef, 0xc89717e6, 0x7a253c3e, 0xd0087ee8,
+];
+const S: U256 = [
+    0x07db2574, 0x28ed0724, 0x26610a0f, 0x7267ca78, 0x0f53e3b1, 0x540fa9c4, 0x1c20a616, 0xe4d76081,
+];
+
+fn main() {
+    let x = verify(&PUBLIC_KEY, &Z, &R, &S).expect("the signature point is at infinity");
+    log_u256(&x);
+    assert_eq!(x, R, "invalid signature");
+}
+
+/// Return `(u1 * G + u2 * Q).x mod n`, where `u1 = z / s` and `u2 = r / s`.
+fn verify(public_key: &Point, z: &U256, r: &U256, s: &U256) -> Option<U256> {
+    let w = inv_mod(s);
+    let u1 = mul_mod(z, &w);
+    let u2 = mul_mod(r, &w);
+
+    let mut acc: Option<Point> = None;
+    for i in (0..256).rev() {
+        acc = acc.and_then(double);
+        if bit(&u1, i) {
+            acc = add(acc, &G);
+        }
+        if bit(&u2, i) {
+            acc = add(acc, public_key);
+        }
+    }
+
+    let x: U256 = acc?[..8].try_into().unwrap();
+    // The x coordinate is less than p < 2n.
+    Some(if geq(&x, &N) { sub(&x, &N).0 } else { x })
+}
+
+/// `p + q`, where `None` is the point at infinity.
+fn add(p: Option<Point>, q: &Point) -> Option<Point> {
+    let Some(mut p) = p else {
+        return Some(*q);
+    };
+    if p[..8] == q[..8] {
+        return if p == *q { double(p) } else { None };
+    }
+    secp256k1_add(&mut p, q);
+    Some(p)
+}
+
+fn double(mut p: Point) -> Option<Point> {
+    if p[8..] == [0; 8] {
+        return None;
+    }
+    secp256k1_double(&mut p);
+    Some(p)
+}
+
+fn bit(a: &U256, i: usize) -> bool {
+    (a[i / 32] >> (i % 32)) & 1 == 1
+}
+
+fn geq(a: &U256, b: &U256) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_ge()
+}
+
+/// `a + b`, and the carry.
+fn add_words(a: &U256, b: &U256) -> (U256, bool) {
+    let mut c = [0; 8];
+    let mut carry = false;
+    for i in 0..8 {
+        let (sum, c1) = a[i].overflowing_add(b[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u32);
+        c[i] = sum;
+        carry = c1 || c2;
+    }
+    (c, carry)
+}
+
+/// `a - b`, and the borrow.
+fn sub(a: &U256, b: &U256) -> (U256, bool) {
+    let mut c = [0; 8];
+    let mut borrow = false;
+    for i in 0..8 {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u32);
+        c[i] = diff;
+        borrow = b1 || b2;
+    }
+    (c, borrow)
+}
+
+/// `a + b mod n`, for reduced `a` and `b`.
+fn add_mod(a: &U256, b: &U256) -> U256 {
+    let (c, carry) = add_words(a, b);
+    if carry || geq(&c, &N) {
+        sub(&c, &N).0
+    } else {
+        c
+    }
+}
+
+/// `a - b mod n`, for reduced `a` and `b`.
+fn sub_mod(a: &U256, b: &U256) -> U256 {
+    let (c, borrow) = sub(a, b);
+    if borrow { add_words(&c, &N).0 } else { c }
+}
+
+/// `a * b mod n`, by double-and-add.
+fn mul_mod(a: &U256, b: &U256) -> U256 {
+    let mut c = [0; 8];
+    for i in (0..256).rev() {
+        c = add_mod(&c, &c);
+        if bit(b, i) {
+            c = add_mod(&c, a);
+        }
+    }
+    c
+}
+
+/// `a / 2 mod n`.
+fn half_mod(a: &U256) -> U256 {
+    let (a, carry) = if a[0] & 1 == 1 {
+        add_words(a, &N)
+    } else {
+        (*a, false)
+    };
+    let mut c = [0; 8];
+    for i in 0..8 {
+        let high = if i < 7 { a[i + 1] & 1 } else { carry as u32 };
+        c[i] = (a[i] >> 1) | (high << 31);
+    }
+    c
+}
+
+fn shr1(a: &mut U256) {
+    for i in 0..8 {
+        let high = if i < 7 { a[i + 1] & 1 } else { 0 };
+        a[i] = (a[i] >> 1) | (high << 31);
+    }
+}
+
+/// `1 / a mod n` for a non-zero `a`, by the binary extended Euclidean algorithm.
+fn inv_mod(a: &U256) -> U256 {
+    let one = [1, 0, 0, 0, 0, 0, 0, 0];
+    let (mut u, mut v) = (*a, N);
+    let (mut x1, mut x2) = (one, [0; 8]);
+    // Invariants: x1 * a ≡ u and x2 * a ≡ v (mod n).
+    while u != one && v != one {
+        while u[0] & 1 == 0 {
+            shr1(&mut u);
+            x1 = half_mod(&x1);
+        }
+        while v[0] & 1 == 0 {
+            shr1(&mut v);
+            x2 = half_mod(&x2);
+        }
+        if geq(&u, &v) {
+            u = sub(&u, &v).0;
+            x1 = sub_mod(&x1, &x2);
+        } else {
+            v = sub(&v, &u).0;
+            x2 = sub_mod(&x2, &x1);
+        }
+    }
+    if u == one { x1 } else { x2 }
+}
+
+fn log_u256(a: &U256) {
+    let out = unsafe { slice::from_raw_parts(a.as_ptr() as *const u8, size_of::<U256>()) };
+    info_out().write_frame(out);
+}
@@ -0,0 +1,14 @@
+extern crate ceno_rt;
+use std::env;
+
+fn main() {
+    for arg in ceno_rt::args() {
+        println!("arg: {arg}");
+    }
+    println!("MODE: {:?}", ceno_rt::var("MODE"));
+
+    // The std library reads the same env.
+    assert_eq!(env::args().collect::<Vec<_>>(), ceno_rt::args().collect::<Vec<_>>());
+    assert_eq!(env::var("MODE").ok().as_deref(), ceno_rt::var("MODE"));
+    assert!(env::var("MISSING").is_err());
+}
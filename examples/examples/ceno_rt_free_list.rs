@@ -0,0 +1,28 @@
+//! Built with the `free-list-allocator` feature of ceno_rt.
+extern crate ceno_rt;
+
+extern crate alloc;
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::hint::black_box;
+
+fn main() {
+    // A freed block is reused by the next allocation of the same size.
+    let first = Box::new([1u32; 16]);
+    let addr = first.as_ptr();
+    drop(first);
+    let second = Box::new([2u32; 16]);
+    assert_eq!(second.as_ptr(), addr);
+    drop(second);
+
+    // A reused block is zeroed when zeroed memory is asked for.
+    let zeroed = vec![0u32; 16];
+    assert_eq!(zeroed.as_ptr(), addr);
+    assert!(black_box(&zeroed).iter().all(|&x| x == 0));
+    drop(zeroed);
+
+    // Allocate without freeing until the heap is exhausted, which halts the guest.
+    let mut blocks = Vec::new();
+    loop {
+        blocks.push(black_box(Vec::<u8>::with_capacity(1 << 16)));
+    }
+}
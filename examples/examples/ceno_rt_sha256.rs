@@ -0,0 +1,39 @@
+//! Compute the SHA-256 hash of a one-block message using the syscalls.
+//!
+//! Log the hash state after the compression of the block.
+
+extern crate ceno_rt;
+use ceno_rt::{
+    info_out,
+    syscalls::{sha_compress, sha_extend},
+};
+use core::slice;
+
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+fn main() {
+    // The padded block of the message "abc".
+    let mut block = [0_u8; 64];
+    block[..3].copy_from_slice(b"abc");
+    block[3] = 0x80;
+    block[63] = 3 * 8;
+
+    let mut w = [0_u32; 64];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+
+    let mut state = INITIAL_STATE;
+    sha_extend(&mut w);
+    sha_compress(&w, &mut state);
+    log_state(&state);
+}
+
+fn log_state(state: &[u32; 8]) {
+    let out = unsafe {
+        slice::from_raw_parts(state.as_ptr() as *const u8, state.len() * size_of::<u32>())
+    };
+    info_out().write_frame(out);
+}
@@ -0,0 +1,8 @@
+extern crate ceno_rt;
+
+fn main() {
+    println!("Hello from std!");
+    eprintln!("Something went wrong.");
+    print!("one, ");
+    println!("two");
+}
@@ -27,6 +27,9 @@ pub trait ExtensionField:
 {
     const DEGREE: usize;
 
+    /// A stable, versioned name of the field, for file formats.
+    const ID: &'static str;
+
     type BaseField: SmallField + FromUniformBytes<64> + Poseidon + Ord;
 
     fn from_bases(bases: &[Self::BaseField]) -> Self;
@@ -47,6 +50,8 @@ mod impl_goldilocks {
     impl ExtensionField for GoldilocksExt2 {
         const DEGREE: usize = 2;
 
+        const ID: &'static str = "goldilocks-ext2/v1";
+
         type BaseField = Goldilocks;
 
         fn from_bases(bases: &[Goldilocks]) -> Self {
@@ -5,7 +5,7 @@ use crate::{
 use core::fmt::Debug;
 use ff_ext::ExtensionField;
 
-use serde::{Deserialize, Serialize, Serializer, de::DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned};
 
 use multilinear_extensions::mle::FieldType;
 
@@ -150,7 +150,7 @@ where
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(bound(serialize = "", deserialize = ""))]
 pub struct BasefoldCommitment<E: ExtensionField>
 where
@@ -209,6 +209,9 @@ impl<E: ExtensionField> Eq for BasefoldCommitmentWithWitness<E> where
 }
 
 pub trait BasefoldSpec<E: ExtensionField>: Debug + Clone {
+    /// See [`crate::PolynomialCommitmentScheme::ID`].
+    const ID: &'static str;
+
     type EncodingScheme: EncodingScheme<E>;
 
     fn get_number_queries() -> usize {
@@ -231,6 +234,8 @@ impl<E: ExtensionField> BasefoldSpec<E> for BasefoldBasecodeParams
 where
     E::BaseField: Serialize + DeserializeOwned,
 {
+    const ID: &'static str = "basefold-basecode/v1";
+
     type EncodingScheme = Basecode<BasecodeDefaultSpec>;
 }
 
@@ -241,6 +246,8 @@ impl<E: ExtensionField> BasefoldSpec<E> for BasefoldRSParams
 where
     E::BaseField: Serialize + DeserializeOwned,
 {
+    const ID: &'static str = "basefold-rs/v1";
+
     type EncodingScheme = RSCode<RSCodeDefaultSpec>;
 }
 
@@ -256,6 +263,21 @@ impl<E: ExtensionField, Spec: BasefoldSpec<E>> Serialize for Basefold<E, Spec> {
     }
 }
 
+impl<'de, E: ExtensionField, Spec: BasefoldSpec<E>> Deserialize<'de> for Basefold<E, Spec> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        if name != "base_fold" {
+            return Err(serde::de::Error::custom(format!(
+                "expected base_fold, got {name}"
+            )));
+        }
+        Ok(Self(PhantomData))
+    }
+}
+
 pub type BasefoldDefault<F> = Basefold<F, BasefoldRSParams>;
 
 impl<E: ExtensionField, Spec: BasefoldSpec<E>> Clone for Basefold<E, Spec> {
@@ -321,6 +343,10 @@ where
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct BasefoldProof<E: ExtensionField>
 where
     E::BaseField: Serialize + DeserializeOwned,
@@ -110,11 +110,14 @@ where
 }
 
 pub trait PolynomialCommitmentScheme<E: ExtensionField>: Clone + Debug {
+    /// A stable, versioned name of the scheme and its parameters, for file formats.
+    const ID: &'static str;
+
     type Param: Clone + Debug + Serialize + DeserializeOwned;
     type ProverParam: Clone + Debug + Serialize + DeserializeOwned;
     type VerifierParam: Clone + Debug + Serialize + DeserializeOwned;
     type CommitmentWithWitness: Clone + Debug;
-    type Commitment: Clone + Debug + Default + Serialize + DeserializeOwned;
+    type Commitment: Clone + Debug + Default + PartialEq + Eq + Serialize + DeserializeOwned;
     type CommitmentChunk: Clone + Debug + Default;
     type Proof: Clone + Debug + Serialize + DeserializeOwned;
 
@@ -8,7 +8,7 @@ use ff_ext::ExtensionField;
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::fmt::Debug;
 
 pub trait MultilinearExtension<E: ExtensionField>: Send + Sync {
@@ -123,10 +123,13 @@ impl<F: Field, E: ExtensionField<BaseField = F>> IntoMLEs<DenseMultilinearExtens
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Default, Debug, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 /// Differentiate inner vector on base/extension field.
 pub enum FieldType<E: ExtensionField> {
-    Base(#[serde(skip)] Vec<E::BaseField>),
+    Base(Vec<E::BaseField>),
     Ext(Vec<E>),
     #[default]
     Unreachable,
@@ -160,6 +163,10 @@ impl<E: ExtensionField> FieldType<E> {
 
 /// Stores a multilinear polynomial in dense evaluation form.
 #[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E: DeserializeOwned, E::BaseField: DeserializeOwned"
+))]
 pub struct DenseMultilinearExtension<E: ExtensionField> {
     /// The evaluation over {0,1}^`num_vars`
     pub evaluations: FieldType<E>,
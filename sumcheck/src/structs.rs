@@ -24,6 +24,13 @@ pub struct IOPProverMessage<E: ExtensionField> {
     pub(crate) evaluations: Vec<E>,
 }
 
+impl<E: ExtensionField> IOPProverMessage<E> {
+    /// The evaluations of the round polynomial on `0..=degree`.
+    pub fn evaluations(&self) -> &[E] {
+        &self.evaluations
+    }
+}
+
 /// Prover State of a PolyIOP.
 #[derive(Default)]
 pub struct IOPProverState<'a, E: ExtensionField> {
@@ -51,6 +58,21 @@ pub struct IOPVerifierState<E: ExtensionField> {
     pub(crate) challenges: Vec<Challenge<E>>,
 }
 
+/// The reason why the verifier rejects a sumcheck proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SumCheckError {
+    /// The proof has fewer round messages than the polynomial has variables.
+    IncompleteProof { rounds: usize, num_vars: usize },
+    /// The round message does not have `max_degree + 1` evaluations.
+    WrongNumberOfEvaluations {
+        round: usize,
+        got: usize,
+        expected: usize,
+    },
+    /// The round message `P` does not satisfy `P(0) + P(1) = expected`.
+    InconsistentRound { round: usize },
+}
+
 /// A SumCheckSubClaim is a claim generated by the verifier at the end of
 /// verification when it is convinced.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -9,7 +9,7 @@ use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use transcript::{BasicTranscript, Transcript};
 
 use crate::{
-    structs::{IOPProverState, IOPVerifierState},
+    structs::{IOPProverState, IOPVerifierState, SumCheckError},
     util::interpolate_uni_poly,
 };
 
@@ -30,7 +30,8 @@ fn test_sumcheck<E: ExtensionField>(
     let (proof, _) = IOPProverState::<E>::prove_parallel(poly.clone(), &mut transcript);
 
     let mut transcript = BasicTranscript::new(b"test");
-    let subclaim = IOPVerifierState::<E>::verify(asserted_sum, &proof, &poly_info, &mut transcript);
+    let subclaim = IOPVerifierState::<E>::verify(asserted_sum, &proof, &poly_info, &mut transcript)
+        .expect("the proof should verify");
     assert!(
         poly.evaluate(
             subclaim
@@ -86,7 +87,8 @@ fn test_sumcheck_internal<E: ExtensionField>(
                     .fix_variables_in_place(&[p.elements]);
             });
     };
-    let subclaim = IOPVerifierState::check_and_generate_subclaim(&verifier_state, &asserted_sum);
+    let subclaim = IOPVerifierState::check_and_generate_subclaim(&verifier_state, &asserted_sum)
+        .expect("the proof should verify");
     assert!(
         poly.evaluate(
             subclaim
@@ -154,6 +156,33 @@ fn test_extract_sum_helper<E: ExtensionField>() {
     assert_eq!(proof.extract_sum(), asserted_sum);
 }
 
+#[test]
+fn test_reject_invalid_proof() {
+    type E = GoldilocksExt2;
+    let mut rng = test_rng();
+    let (poly, asserted_sum) = VirtualPolynomial::<E>::random(8, (2, 3), 3, &mut rng);
+    let poly_info = poly.aux_info.clone();
+    #[allow(deprecated)]
+    let (mut proof, _) =
+        IOPProverState::<E>::prove_parallel(poly, &mut BasicTranscript::new(b"test"));
+    let verify = |sum: E, proof: &_| {
+        IOPVerifierState::<E>::verify(sum, proof, &poly_info, &mut BasicTranscript::new(b"test"))
+    };
+
+    assert_eq!(
+        verify(asserted_sum + E::ONE, &proof),
+        Err(SumCheckError::InconsistentRound { round: 0 })
+    );
+    proof.proofs.pop();
+    assert_eq!(
+        verify(asserted_sum, &proof),
+        Err(SumCheckError::IncompleteProof {
+            rounds: 7,
+            num_vars: 8
+        })
+    );
+}
+
 struct DensePolynomial(Vec<GoldilocksExt2>);
 
 impl DensePolynomial {
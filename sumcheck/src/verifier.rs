@@ -4,7 +4,7 @@ use multilinear_extensions::virtual_poly::VPAuxInfo;
 use transcript::{Challenge, Transcript};
 
 use crate::{
-    structs::{IOPProof, IOPProverMessage, IOPVerifierState, SumCheckSubClaim},
+    structs::{IOPProof, IOPProverMessage, IOPVerifierState, SumCheckError, SumCheckSubClaim},
     util::interpolate_uni_poly,
 };
 
@@ -14,12 +14,12 @@ impl<E: ExtensionField> IOPVerifierState<E> {
         proof: &IOPProof<E>,
         aux_info: &VPAuxInfo<E>,
         transcript: &mut impl Transcript<E>,
-    ) -> SumCheckSubClaim<E> {
+    ) -> Result<SumCheckSubClaim<E>, SumCheckError> {
         if aux_info.max_num_variables == 0 {
-            return SumCheckSubClaim {
+            return Ok(SumCheckSubClaim {
                 point: vec![],
                 expected_evaluation: claimed_sum,
-            };
+            });
         }
         let start = start_timer!(|| "sum check verify");
 
@@ -28,7 +28,10 @@ impl<E: ExtensionField> IOPVerifierState<E> {
 
         let mut verifier_state = IOPVerifierState::verifier_init(aux_info);
         for i in 0..aux_info.max_num_variables {
-            let prover_msg = proof.proofs.get(i).expect("proof is incomplete");
+            let prover_msg = proof.proofs.get(i).ok_or(SumCheckError::IncompleteProof {
+                rounds: proof.proofs.len(),
+                num_vars: aux_info.max_num_variables,
+            })?;
             prover_msg
                 .evaluations
                 .iter()
@@ -109,7 +112,10 @@ impl<E: ExtensionField> IOPVerifierState<E> {
     /// evaluated at `subclaim.point` will be `subclaim.expected_evaluation`.
     /// Otherwise, it is highly unlikely that those two will be equal.
     /// Larger field size guarantees smaller soundness error.
-    pub(crate) fn check_and_generate_subclaim(&self, asserted_sum: &E) -> SumCheckSubClaim<E> {
+    pub(crate) fn check_and_generate_subclaim(
+        &self,
+        asserted_sum: &E,
+    ) -> Result<SumCheckSubClaim<E>, SumCheckError> {
         let start = start_timer!(|| "sum check check and generate subclaim");
         if !self.finished {
             panic!("Incorrect verifier state: Verifier has not finished.",);
@@ -125,17 +131,18 @@ impl<E: ExtensionField> IOPVerifierState<E> {
             .polynomials_received
             .iter()
             .zip(self.challenges.iter())
-            .map(|(evaluations, challenge)| {
+            .enumerate()
+            .map(|(round, (evaluations, challenge))| {
                 if evaluations.len() != self.max_degree + 1 {
-                    panic!(
-                        "incorrect number of evaluations: {} vs {}",
-                        evaluations.len(),
-                        self.max_degree + 1
-                    );
+                    return Err(SumCheckError::WrongNumberOfEvaluations {
+                        round,
+                        got: evaluations.len(),
+                        expected: self.max_degree + 1,
+                    });
                 }
-                interpolate_uni_poly::<E>(evaluations, challenge.elements)
+                Ok(interpolate_uni_poly::<E>(evaluations, challenge.elements))
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
 
         // l-append asserted_sum to the first position of the expected vector
         expected_vec.insert(0, *asserted_sum);
@@ -150,20 +157,15 @@ impl<E: ExtensionField> IOPVerifierState<E> {
             // the deferred check during the interactive phase:
             // 1. check if the received 'P(0) + P(1) = expected`.
             if evaluations[0] + evaluations[1] != expected {
-                panic!(
-                    "{}th round's prover message is not consistent with the claim. {:?} {:?}",
-                    i,
-                    evaluations[0] + evaluations[1],
-                    expected
-                );
+                return Err(SumCheckError::InconsistentRound { round: i });
             }
         }
         end_timer!(start);
-        SumCheckSubClaim {
+        Ok(SumCheckSubClaim {
             point: self.challenges.clone(),
             // the last expected value (not checked within this function) will be included in the
             // subclaim
             expected_evaluation: expected_vec[self.num_vars],
-        }
+        })
     }
 }